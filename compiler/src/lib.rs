@@ -8,6 +8,7 @@ mod parser;
 pub mod stats;
 mod validator;
 
+use bladeink::compiled_story::CompiledStory;
 pub use error::CompilerError;
 
 /// Maps each line of the expanded source (0-indexed) to its origin:
@@ -54,6 +55,26 @@ impl Compiler {
         })
     }
 
+    /// Compile the ink source to the compact binary story format, that can be
+    /// loaded with `Story::from_binary`. The JSON remains the interchange
+    /// format, the binary is only understood by the `bladeink` runtime.
+    pub fn compile_to_binary(&self, source: &str) -> Result<Vec<u8>, CompilerError> {
+        json_to_binary(&self.compile(source)?)
+    }
+
+    /// Like [`compile_to_binary`](Compiler::compile_to_binary), resolving
+    /// INCLUDEs via `file_handler`.
+    pub fn compile_to_binary_with_file_handler<F>(
+        &self,
+        source: &str,
+        file_handler: F,
+    ) -> Result<Vec<u8>, CompilerError>
+    where
+        F: Fn(&str) -> Result<String, CompilerError>,
+    {
+        json_to_binary(&self.compile_with_file_handler(source, file_handler)?)
+    }
+
     /// Parse the ink source and return story statistics without emitting JSON.
     ///
     /// Useful for the `-s` flag of `rinklecate`.
@@ -109,3 +130,10 @@ impl Compiler {
             .map_err(|e| CompilerError::invalid_source(e.to_string()))
     }
 }
+
+/// Convert a compiled `.ink.json` story to the binary story format.
+pub fn json_to_binary(json: &str) -> Result<Vec<u8>, CompilerError> {
+    CompiledStory::new(json)
+        .and_then(|story| story.to_binary())
+        .map_err(|e| CompilerError::invalid_source(e.to_string()))
+}
@@ -0,0 +1,262 @@
+use std::{error::Error, fs, path::Path};
+
+use bladeink::{compiled_story::CompiledStory, story::Story, story_error::StoryError};
+use bladeink_compiler::Compiler;
+use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+mod common;
+
+fn collect_json_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.is_dir() {
+            collect_json_files(&path, files);
+        } else if path.to_string_lossy().ends_with(".ink.json") {
+            files.push(path);
+        }
+    }
+}
+
+#[test]
+fn binary_round_trip_all_inkfiles_test() -> Result<(), Box<dyn Error>> {
+    let mut files = Vec::new();
+    collect_json_files(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("inkfiles"),
+        &mut files,
+    );
+
+    for file in files {
+        let json = fs::read_to_string(&file)?;
+        let json = json.strip_prefix('\u{feff}').unwrap_or(&json);
+
+        let binary = CompiledStory::new(json)?.to_binary()?;
+        let reloaded = CompiledStory::from_binary(&binary)?;
+
+        assert_eq!(
+            binary,
+            reloaded.to_binary()?,
+            "binary round trip differs for {}",
+            file.display()
+        );
+
+        assert_eq!(
+            play_transcript(Story::new(json)?),
+            play_transcript(Story::from_binary(&binary)?),
+            "binary story plays differently for {}",
+            file.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Plays the story always taking the first choice and returns the text, the
+/// choices and the errors it produced.
+fn play_transcript(mut story: Story) -> String {
+    let mut transcript = String::new();
+    story.set_seed(0);
+
+    for _ in 0..100 {
+        match story.continue_maximally() {
+            Ok(text) => transcript.push_str(&text),
+            Err(e) => {
+                transcript.push_str(&format!("ERROR: {}\n", e));
+                break;
+            }
+        }
+
+        let choices = story.get_current_choices();
+
+        if choices.is_empty() {
+            break;
+        }
+
+        for choice in &choices {
+            transcript.push_str(&format!("* {}\n", choice.text));
+        }
+
+        if let Err(e) = story.choose_choice_index(0) {
+            transcript.push_str(&format!("ERROR: {}\n", e));
+            break;
+        }
+    }
+
+    transcript
+}
+
+#[test]
+fn binary_story_plays_like_json_test() -> Result<(), StoryError> {
+    let json = common::get_json_string("inkfiles/TheIntercept.ink.json").unwrap();
+    let binary = CompiledStory::new(&json)?.to_binary()?;
+
+    let mut json_story = Story::new(&json)?;
+    let mut binary_story = Story::from_binary(&binary)?;
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for _ in 0..1_000 {
+        assert_eq!(
+            json_story.continue_maximally()?,
+            binary_story.continue_maximally()?
+        );
+
+        let choices = json_story.get_current_choices();
+        assert_eq!(choices.len(), binary_story.get_current_choices().len());
+
+        if choices.is_empty() {
+            break;
+        }
+
+        let choice_index = rng.random_range(0..choices.len());
+        json_story.choose_choice_index(choice_index)?;
+        binary_story.choose_choice_index(choice_index)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn binary_story_with_lists_test() -> Result<(), StoryError> {
+    let ink_source = r#"
+LIST colors = red, (green), blue
+LIST empty = a, b
+VAR col = ()
+~ col = LIST_ALL(empty)
+{colors} {LIST_ALL(colors)} {col} {LIST_COUNT(LIST_INVERT(colors))}
+"#;
+    let json = Compiler::new().compile(ink_source).unwrap();
+    let binary = CompiledStory::new(&json)?.to_binary()?;
+
+    let mut json_story = Story::new(&json)?;
+    let mut binary_story = Story::from_binary(&binary)?;
+
+    assert_eq!(
+        json_story.continue_maximally()?,
+        binary_story.continue_maximally()?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn bad_binary_test() {
+    assert!(matches!(
+        Story::from_binary(b"{\"inkVersion\":21}"),
+        Err(StoryError::BadBinary(_))
+    ));
+
+    let json = r##"{"inkVersion":21,"root":[["^Line.","\n",["done",{"#n":"g-0"}],null],"done",null],"listDefs":{}}"##;
+    let binary = CompiledStory::new(json).unwrap().to_binary().unwrap();
+
+    assert!(matches!(
+        Story::from_binary(&binary[..binary.len() - 1]),
+        Err(StoryError::BadBinary(_))
+    ));
+
+    assert!(matches!(
+        Story::from_binary(b"INKB\x01\xff\xff\xff\xff\x0f"),
+        Err(StoryError::BadBinary(_))
+    ));
+
+    // A story with containers nested 10000 levels deep.
+    let mut nested = b"INKB\x01\x00\x2a\x00".to_vec();
+    for _ in 0..10_000 {
+        nested.extend_from_slice(&[0x00, 0x00, 0x01, 0x00]);
+    }
+
+    assert!(matches!(
+        Story::from_binary(&nested),
+        Err(StoryError::BadBinary(msg)) if msg.contains("nested too deeply")
+    ));
+}
+
+#[test]
+fn binary_save_state_test() -> Result<(), StoryError> {
+    let json = common::get_json_string("inkfiles/TheIntercept.ink.json").unwrap();
+    let compiled = CompiledStory::new(&json)?;
+
+    let mut story = Story::from_compiled(&compiled)?;
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for _ in 0..100 {
+        story.continue_maximally()?;
+
+        let json_state = story.save_state()?;
+        let binary_state = story.save_state_binary()?;
+        assert!(binary_state.len() < json_state.len());
+
+        let mut json_loaded = Story::from_compiled(&compiled)?;
+        json_loaded.load_state(&json_state)?;
+        let mut binary_loaded = Story::from_compiled(&compiled)?;
+        binary_loaded.load_state_binary(&binary_state)?;
+
+        assert_eq!(binary_state, binary_loaded.save_state_binary()?);
+        assert_eq!(binary_state, json_loaded.save_state_binary()?);
+
+        let choices = story.get_current_choices();
+
+        if choices.is_empty() {
+            break;
+        }
+
+        let choice_index = rng.random_range(0..choices.len());
+        json_loaded.choose_choice_index(choice_index)?;
+        binary_loaded.choose_choice_index(choice_index)?;
+
+        assert_eq!(
+            json_loaded.continue_maximally()?,
+            binary_loaded.continue_maximally()?
+        );
+
+        story = binary_loaded;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn bad_binary_save_state_test() -> Result<(), StoryError> {
+    let json = r##"{"inkVersion":21,"root":[["^Line.","\n",["done",{"#n":"g-0"}],null],"done",null],"listDefs":{}}"##;
+    let mut story = Story::new(json)?;
+
+    let saved = story.save_state_binary()?;
+    let story_binary = CompiledStory::new(json)?.to_binary()?;
+
+    assert!(matches!(
+        story.load_state_binary(&story_binary),
+        Err(StoryError::BadBinary(_))
+    ));
+    assert!(matches!(
+        story.load_state_binary(&saved[..saved.len() - 1]),
+        Err(StoryError::BadBinary(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn bad_binary_save_state_keeps_state_test() -> Result<(), StoryError> {
+    let json = Compiler::new()
+        .compile("VAR x = 0\n~ x = 5\nHello.\n* Choice\n-> END")
+        .unwrap();
+
+    let mut story = Story::new(&json)?;
+    story.continue_maximally()?;
+    let saved = story.save_state_binary()?;
+
+    let mut story = Story::new(&json)?;
+    assert!(matches!(
+        story.load_state_binary(&saved[..saved.len() - 1]),
+        Err(StoryError::BadBinary(_))
+    ));
+
+    assert_eq!(0, story.get_variable("x").unwrap().get::<i32>().unwrap());
+    assert!(story.get_current_choices().is_empty());
+    assert!(story.can_continue());
+
+    story.load_state_binary(&saved)?;
+    assert_eq!(5, story.get_variable("x").unwrap().get::<i32>().unwrap());
+    assert_eq!(1, story.get_current_choices().len());
+
+    Ok(())
+}
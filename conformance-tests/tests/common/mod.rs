@@ -106,6 +106,12 @@ pub fn get_file_string(filename: &str) -> Result<String, Box<dyn Error>> {
     Ok(content)
 }
 
+pub fn open_file(filename: &str) -> Result<fs::File, Box<dyn Error>> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(filename);
+
+    Ok(fs::File::open(path)?)
+}
+
 pub fn get_json_string(filename: &str) -> Result<String, Box<dyn Error>> {
     get_file_string(filename)
 }
@@ -1,6 +1,8 @@
 use std::error::Error;
 
-use bladeink::story::Story;
+use bladeink::{
+    ink_list_item::InkListItem, story::Story, story_error::StoryError, value_type::ValueType,
+};
 use bladeink_compiler::Compiler;
 
 mod common;
@@ -197,3 +199,73 @@ LIST l = A, (B), (C), (D), E
     }
     Ok(())
 }
+
+#[test]
+fn list_from_host_test() -> Result<(), Box<dyn Error>> {
+    let ink_source = r#"
+LIST colors = red, green, blue
+VAR favourite = ()
+{favourite}
+{LIST_INVERT(favourite)}
+"#;
+    let json_string = Compiler::new().compile(ink_source).unwrap();
+    let mut story = Story::new(&json_string)?;
+
+    let list = story.list_from_items("colors", &["red", "colors.blue"])?;
+    assert_eq!(2, list.len());
+    assert!(list.contains_item(&InkListItem::new(
+        Some("colors".to_owned()),
+        "red".to_owned()
+    )));
+    assert!(list.contains_item_named("blue"));
+    assert!(list.contains_item_named("colors.blue"));
+    assert!(!list.contains_item_named("green"));
+
+    let items: Vec<(String, i32)> = list
+        .iter()
+        .map(|(item, value)| (item.get_full_name(), value))
+        .collect();
+    assert_eq!(
+        vec![("colors.red".to_owned(), 1), ("colors.blue".to_owned(), 3)],
+        items
+    );
+
+    let all = story.list_all(&list);
+    assert_eq!("red, green, blue", all.to_string());
+    assert_eq!("green", list.inverse().to_string());
+    assert_eq!("red, green, blue", list.union(&all).to_string());
+    assert_eq!("red, blue", list.intersect(&all).to_string());
+
+    story.set_variable("favourite", &ValueType::List(list))?;
+    assert_eq!("red, blue\ngreen\n", story.continue_maximally()?);
+
+    match story.get_variable("favourite") {
+        Some(ValueType::List(l)) => assert_eq!("red, green, blue", story.list_all(&l).to_string()),
+        _ => panic!("favourite should be a list"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn list_from_unknown_items_test() -> Result<(), Box<dyn Error>> {
+    let ink_source = "LIST colors = red, green, blue\nLIST sizes = small, big\nHi.\n";
+    let json_string = Compiler::new().compile(ink_source).unwrap();
+    let story = Story::new(&json_string)?;
+
+    assert!(matches!(
+        story.list_from_items("shapes", &["circle"]),
+        Err(StoryError::BadArgument(_))
+    ));
+    assert!(matches!(
+        story.list_from_items("colors", &["yellow"]),
+        Err(StoryError::BadArgument(_))
+    ));
+    assert!(matches!(
+        story.list_from_items("colors", &["sizes.small"]),
+        Err(StoryError::BadArgument(_))
+    ));
+    assert!(story.list_from_items("colors", &[])?.is_empty());
+
+    Ok(())
+}
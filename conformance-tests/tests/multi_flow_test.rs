@@ -98,3 +98,47 @@ fn multiflow_save_load_threads() -> Result<(), StoryError> {
 
     Ok(())
 }
+
+#[test]
+fn multiflow_save_load_binary() -> Result<(), StoryError> {
+    let ink_source =
+        common::get_file_string("inkfiles/runtime/multiflow-saveloadthreads.ink").unwrap();
+    let json_string = Compiler::new().compile(&ink_source).unwrap();
+    let mut story = Story::new(&json_string)?;
+
+    assert_eq!("Default line 1\n", story.cont()?);
+
+    story.switch_flow("Blue Flow")?;
+    story.choose_path_string("blue", true, None)?;
+    assert_eq!("Hello I'm blue\n", story.cont()?);
+
+    story.switch_flow("Red Flow")?;
+    story.choose_path_string("red", true, None)?;
+    assert_eq!("Hello I'm red\n", story.cont()?);
+
+    let saved = story.save_state_binary()?;
+
+    let mut story = Story::new(&json_string)?;
+    story.load_state_binary(&saved)?;
+
+    assert_eq!("Hello I'm red\n", story.get_current_text()?);
+    story.choose_choice_index(1)?;
+    assert_eq!(
+        "Thread 2 red choice\nAfter thread 2 choice (red)\n",
+        story.continue_maximally()?
+    );
+
+    story.load_state_binary(&saved)?;
+    story.switch_flow("Blue Flow")?;
+    assert_eq!("Hello I'm blue\n", story.get_current_text()?);
+    story.choose_choice_index(0)?;
+    assert_eq!(
+        "Thread 1 blue choice\nAfter thread 1 choice (blue)\n",
+        story.continue_maximally()?
+    );
+
+    story.remove_flow("Blue Flow")?;
+    assert_eq!("Default line 2\n", story.cont()?);
+
+    Ok(())
+}
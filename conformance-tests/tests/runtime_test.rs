@@ -1,9 +1,25 @@
 use core::panic;
-use std::{cell::RefCell, error::Error, rc::Rc};
+use std::{error::Error, time::Duration};
 
 use bladeink::{
-    story::{Story, external_functions::ExternalFunction, variable_observer::VariableObserver},
-    story_error::StoryError,
+    choice::Choice,
+    compiled_story::CompiledStory,
+    explorer::{ExploreStrategy, Explorer, ExplorerOptions},
+    push_pop::PushPopType,
+    save_state_view::{self, FlowChange, SaveStateView},
+    story::{
+        Story,
+        debugger::DebugStop,
+        errors::{ErrorHandler, ErrorType},
+        events::StoryEventListener,
+        external_functions::{ExternalFunction, FallibleExternalFunction},
+        lines::{Line, TurnEvent},
+        migration::{MigrationFallback, SaveMigration, SavedPathKind},
+        random::StoryRng,
+        variable_observer::VariableObserver,
+    },
+    story_error::{RuntimeError, RuntimeErrorKind, StoryError},
+    threadsafe::{BrCell, Brc},
     value_type::ValueType,
 };
 use bladeink_compiler::Compiler;
@@ -16,12 +32,12 @@ struct ExtFunc4;
 struct ExtFunc5;
 struct ExtFunc6;
 struct MessageRecorder {
-    message: Rc<RefCell<Option<String>>>,
+    message: Brc<BrCell<Option<String>>>,
 }
 struct MultiplyFunc;
 struct TimesFunc;
 struct CallCounter {
-    count: Rc<RefCell<i32>>,
+    count: Brc<BrCell<i32>>,
 }
 
 impl ExternalFunction for ExtFunc1 {
@@ -111,7 +127,7 @@ fn external_function() -> Result<(), Box<dyn Error>> {
     let mut story = Story::new(&json_string)?;
     let mut text: Vec<String> = Vec::new();
 
-    story.bind_external_function("externalFunction", Rc::new(RefCell::new(ExtFunc1 {})), true)?;
+    story.bind_external_function("externalFunction", Brc::new(BrCell::new(ExtFunc1 {})), true)?;
 
     common::next_all(&mut story, &mut text)?;
     assert_eq!(1, text.len());
@@ -224,17 +240,17 @@ EXTERNAL times(i,str)
 "#;
     let json = Compiler::new().compile(ink).unwrap();
     let mut story = Story::new(&json)?;
-    let message = Rc::new(RefCell::new(None));
+    let message = Brc::new(BrCell::new(None));
 
     story.bind_external_function(
         "message",
-        Rc::new(RefCell::new(MessageRecorder {
+        Brc::new(BrCell::new(MessageRecorder {
             message: message.clone(),
         })),
         true,
     )?;
-    story.bind_external_function("multiply", Rc::new(RefCell::new(MultiplyFunc)), true)?;
-    story.bind_external_function("times", Rc::new(RefCell::new(TimesFunc)), true)?;
+    story.bind_external_function("multiply", Brc::new(BrCell::new(MultiplyFunc)), true)?;
+    story.bind_external_function("times", Brc::new(BrCell::new(TimesFunc)), true)?;
 
     assert_eq!("15\n", story.cont()?);
     assert_eq!("knock knock knock\n", story.cont()?);
@@ -258,11 +274,11 @@ Two
 "#;
     let json = Compiler::new().compile(ink).unwrap();
     let mut story = Story::new(&json)?;
-    let safe_count = Rc::new(RefCell::new(0));
+    let safe_count = Brc::new(BrCell::new(0));
 
     story.bind_external_function(
         "myAction",
-        Rc::new(RefCell::new(CallCounter {
+        Brc::new(BrCell::new(CallCounter {
             count: safe_count.clone(),
         })),
         true,
@@ -273,10 +289,10 @@ Two
     story.reset_state()?;
     story.unbind_external_function("myAction")?;
 
-    let unsafe_count = Rc::new(RefCell::new(0));
+    let unsafe_count = Brc::new(BrCell::new(0));
     story.bind_external_function(
         "myAction",
-        Rc::new(RefCell::new(CallCounter {
+        Brc::new(BrCell::new(CallCounter {
             count: unsafe_count.clone(),
         })),
         false,
@@ -295,8 +311,8 @@ One
     let mut story = Story::new(&json)?;
     story.bind_external_function(
         "myAction",
-        Rc::new(RefCell::new(CallCounter {
-            count: Rc::new(RefCell::new(0)),
+        Brc::new(BrCell::new(CallCounter {
+            count: Brc::new(BrCell::new(0)),
         })),
         false,
     )?;
@@ -312,7 +328,7 @@ fn external_function_one_arguments() -> Result<(), Box<dyn Error>> {
     let mut story = Story::new(&json_string)?;
     let mut text: Vec<String> = Vec::new();
 
-    story.bind_external_function("externalFunction", Rc::new(RefCell::new(ExtFunc3 {})), true)?;
+    story.bind_external_function("externalFunction", Brc::new(BrCell::new(ExtFunc3 {})), true)?;
 
     common::next_all(&mut story, &mut text)?;
     assert_eq!(1, text.len());
@@ -328,7 +344,7 @@ fn external_function_coerce_test() -> Result<(), Box<dyn Error>> {
     let mut story = Story::new(&json_string)?;
     let mut text: Vec<String> = Vec::new();
 
-    story.bind_external_function("externalFunction", Rc::new(RefCell::new(ExtFunc4 {})), true)?;
+    story.bind_external_function("externalFunction", Brc::new(BrCell::new(ExtFunc4 {})), true)?;
 
     common::next_all(&mut story, &mut text)?;
     assert_eq!(1, text.len());
@@ -380,7 +396,7 @@ fn variable_observers_test() -> Result<(), Box<dyn Error>> {
     let mut story = Story::new(&json_string)?;
     let mut text: Vec<String> = Vec::new();
 
-    let observer = Rc::new(RefCell::new(VObserver { expected_value: 5 }));
+    let observer = Brc::new(BrCell::new(VObserver { expected_value: 5 }));
     story.observe_variable("x", observer.clone())?;
 
     common::next_all(&mut story, &mut text)?;
@@ -394,6 +410,70 @@ fn variable_observers_test() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn variable_subscriptions_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+VAR x = 0
+VAR y = 0
+~ x = 1
+~ y = 2
+Hello
+* [Go]
+~ x = 3
+Bye
+-> END
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+
+    let x_values = Brc::new(BrCell::new(Vec::new()));
+    let batches = Brc::new(BrCell::new(Vec::new()));
+
+    let x_values_clone = x_values.clone();
+    let x_subscription = story.observe_variable_fn("x", move |_, value| {
+        x_values_clone
+            .borrow_mut()
+            .push(value.get::<i32>().unwrap())
+    })?;
+    let batches_clone = batches.clone();
+    let all_subscription = story.observe_all_variables(move |changes| {
+        batches_clone
+            .borrow_mut()
+            .push(changes.iter().map(|c| c.0.clone()).collect::<Vec<_>>())
+    })?;
+
+    assert!(story.observe_variable_fn("z", |_, _| {}).is_err());
+
+    story.continue_maximally()?;
+    assert_eq!(vec![1], *x_values.borrow());
+    assert_eq!(
+        vec![vec!["x".to_owned(), "y".to_owned()]],
+        *batches.borrow()
+    );
+
+    story.set_variable("y", &ValueType::Int(5))?;
+    assert_eq!(vec!["y".to_owned()], batches.borrow()[1]);
+
+    // Dropped subscriptions are not called anymore
+    assert!(x_subscription.is_active());
+    drop(x_subscription);
+    all_subscription.detach();
+
+    story.choose_choice_index(0)?;
+    story.continue_maximally()?;
+    assert_eq!(vec![1], *x_values.borrow());
+    assert_eq!(3, batches.borrow().len());
+
+    // Removing an observer that isn't registered does nothing
+    let observer: Brc<BrCell<dyn VariableObserver>> =
+        Brc::new(BrCell::new(VObserver { expected_value: 0 }));
+    story.observe_variable("x", Brc::new(BrCell::new(VObserver { expected_value: 0 })))?;
+    story.remove_variable_observer(&observer, Some("x"))?;
+    story.remove_variable_observer(&observer, None)?;
+
+    Ok(())
+}
+
 #[test]
 fn set_and_get_variable_test() -> Result<(), Box<dyn Error>> {
     let ink_source = common::get_file_string("inkfiles/runtime/set-get-variables.ink")?;
@@ -592,7 +672,7 @@ fn external_function_two_arguments_test() -> Result<(), Box<dyn Error>> {
     let mut story = Story::new(&json_string)?;
     let mut text: Vec<String> = Vec::new();
 
-    story.bind_external_function("externalFunction", Rc::new(RefCell::new(ExtFunc1 {})), true)?;
+    story.bind_external_function("externalFunction", Brc::new(BrCell::new(ExtFunc1 {})), true)?;
 
     common::next_all(&mut story, &mut text)?;
     assert_eq!(1, text.len());
@@ -609,7 +689,7 @@ fn external_function_two_arguments_coerce_override_test() -> Result<(), Box<dyn
     let mut text: Vec<String> = Vec::new();
 
     // Uses coerce_to_int explicitly for both args — same result
-    story.bind_external_function("externalFunction", Rc::new(RefCell::new(ExtFunc1 {})), true)?;
+    story.bind_external_function("externalFunction", Brc::new(BrCell::new(ExtFunc1 {})), true)?;
 
     common::next_all(&mut story, &mut text)?;
     assert_eq!(1, text.len());
@@ -625,7 +705,7 @@ fn external_function_three_arguments_test() -> Result<(), Box<dyn Error>> {
     let mut story = Story::new(&json_string)?;
     let mut text: Vec<String> = Vec::new();
 
-    story.bind_external_function("externalFunction", Rc::new(RefCell::new(ExtFunc5 {})), true)?;
+    story.bind_external_function("externalFunction", Brc::new(BrCell::new(ExtFunc5 {})), true)?;
 
     common::next_all(&mut story, &mut text)?;
     assert_eq!(1, text.len());
@@ -642,7 +722,7 @@ fn external_function_three_arguments_coerce_override_test() -> Result<(), Box<dy
     let mut text: Vec<String> = Vec::new();
 
     // Uses explicit coerce_to_int for all args — same result
-    story.bind_external_function("externalFunction", Rc::new(RefCell::new(ExtFunc6 {})), true)?;
+    story.bind_external_function("externalFunction", Brc::new(BrCell::new(ExtFunc6 {})), true)?;
 
     common::next_all(&mut story, &mut text)?;
     assert_eq!(1, text.len());
@@ -650,3 +730,1388 @@ fn external_function_three_arguments_coerce_override_test() -> Result<(), Box<dy
 
     Ok(())
 }
+
+#[test]
+fn compiled_story_shared_between_stories_test() -> Result<(), Box<dyn Error>> {
+    let ink_source = r#"
+VAR x = 0
+Start.
+* [Go]
+    ~ x = 5
+    Value {x}.
+"#;
+    let json_string = Compiler::new().compile(ink_source).unwrap();
+    let compiled = CompiledStory::new(&json_string)?;
+
+    let mut story1 = Story::from_compiled(&compiled)?;
+    let mut story2 = Story::from_compiled(&compiled)?;
+
+    assert_eq!("Start.\n", story1.continue_maximally()?);
+    story1.choose_choice_index(0)?;
+    assert_eq!("Value 5.\n", story1.continue_maximally()?);
+
+    // The second story has its own state
+    assert_eq!("Start.\n", story2.continue_maximally()?);
+    assert_eq!(1, story2.get_current_choices().len());
+    assert_eq!(0, story2.get_variable("x").unwrap().get::<i32>().unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn bind_fn_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+EXTERNAL message(x)
+EXTERNAL multiply(x,y)
+EXTERNAL times(i,str)
+EXTERNAL answer()
+~ message("hello world")
+{multiply(5.0, 3.0)}
+{times(3, "knock ")}
+{answer()}
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+    let message = Brc::new(BrCell::new(None));
+
+    let recorder = message.clone();
+    story.bind_fn("message", move |m: String| {
+        recorder.replace(Some(format!("MESSAGE: {m}")));
+    })?;
+    story.bind_fn("multiply", |x: f32, y: f32| x * y)?;
+    story.bind_fn("times", |n: i32, s: String| {
+        s.repeat(n as usize).trim().to_owned()
+    })?;
+    story.bind_fn("answer", || 42)?;
+
+    assert_eq!("15\n", story.cont()?);
+    assert_eq!("knock knock knock\n", story.cont()?);
+    assert_eq!("42\n", story.cont()?);
+    assert_eq!(
+        Some("MESSAGE: hello world".to_owned()),
+        message.borrow().clone()
+    );
+
+    assert!(matches!(
+        story.bind_fn("answer", || 0),
+        Err(StoryError::BadArgument(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn bind_fn_bad_arguments_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+EXTERNAL double(x)
+{double(2)}
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+
+    let mut story = Story::new(&json)?;
+    story.bind_fn("double", |x: String| x.repeat(2))?;
+    match story.cont() {
+        Err(StoryError::BadArgument(msg)) => {
+            assert!(msg.contains("'double'"), "{msg}");
+            assert!(msg.contains("int"), "{msg}");
+        }
+        r => panic!(
+            "expected a bad argument error: {:?}",
+            r.map_err(|e| e.to_string())
+        ),
+    }
+
+    let mut story = Story::new(&json)?;
+    story.bind_fn("double", |x: i32, y: i32| x * y)?;
+    match story.cont() {
+        Err(StoryError::BadArgument(msg)) => {
+            assert!(msg.contains("'double' expects 2 arguments"), "{msg}")
+        }
+        _ => panic!("expected a bad argument error"),
+    }
+
+    let mut story = Story::new(&json)?;
+    story.bind_fn("double", |x: ValueType| x.coerce_to_int().unwrap() * 2)?;
+    assert_eq!("4\n", story.cont()?);
+
+    Ok(())
+}
+
+#[test]
+fn bind_fn_lookahead_unsafe_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+EXTERNAL myAction()
+
+One
+~ myAction()
+Two
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+
+    let count = Brc::new(BrCell::new(0));
+    let counter = count.clone();
+    story.bind_fn_lookahead_unsafe("myAction", move || {
+        *counter.borrow_mut() += 1;
+    })?;
+    story.continue_maximally()?;
+    assert_eq!(1, *count.borrow());
+
+    Ok(())
+}
+
+struct CheckPositive;
+
+impl FallibleExternalFunction for CheckPositive {
+    fn call(&mut self, _: &str, args: Vec<ValueType>) -> Result<Option<ValueType>, String> {
+        match args[0].get::<i32>() {
+            Some(n) if n > 0 => Ok(Some(ValueType::Bool(true))),
+            _ => Err("expected a positive number".to_owned()),
+        }
+    }
+}
+
+struct ErrorRecorder {
+    errors: Vec<String>,
+}
+
+impl ErrorHandler for ErrorRecorder {
+    fn error(&mut self, message: &str, error_type: ErrorType) {
+        if error_type == ErrorType::Error {
+            self.errors.push(message.to_owned());
+        }
+    }
+}
+
+#[test]
+fn fallible_external_function_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+EXTERNAL check(x)
+{check(1)}
+-> knot
+== knot
+{check(-1)}
+Unreachable.
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+    story.bind_fallible_external_function("check", Brc::new(BrCell::new(CheckPositive)), true)?;
+
+    assert_eq!("true\n", story.cont()?);
+
+    let result = story.cont();
+    match result {
+        Err(StoryError::InvalidStoryState(msg)) => {
+            assert!(msg.contains("(knot"), "{msg}");
+            assert!(
+                msg.contains("External function 'check' failed: expected a positive number"),
+                "{msg}"
+            );
+        }
+        _ => panic!("expected a runtime error"),
+    }
+
+    // With an error handler the error is reported there
+    let errors = Brc::new(BrCell::new(ErrorRecorder { errors: Vec::new() }));
+    let mut story = Story::new(&json)?;
+    story.set_error_handler(errors.clone());
+    story.bind_fn("check", |x: i32| {
+        if x > 0 {
+            Ok(true)
+        } else {
+            Err(format!("{x} is not positive"))
+        }
+    })?;
+
+    assert_eq!("true\n", story.continue_maximally()?);
+    assert!(!story.can_continue());
+
+    let errors = &errors.borrow().errors;
+    assert_eq!(1, errors.len());
+    assert!(
+        errors[0].starts_with("RUNTIME ERROR: (knot"),
+        "{}",
+        errors[0]
+    );
+    assert!(
+        errors[0].ends_with("External function 'check' failed: -1 is not positive"),
+        "{}",
+        errors[0]
+    );
+
+    Ok(())
+}
+
+struct EventRecorder {
+    events: Vec<String>,
+}
+
+impl StoryEventListener for EventRecorder {
+    fn on_did_continue(&mut self, text: &str, tags: &[String]) {
+        self.events
+            .push(format!("continue: {} {:?}", text.trim(), tags));
+    }
+
+    fn on_make_choice(&mut self, choice: &Choice) {
+        self.events.push(format!("choice: {}", choice.text));
+    }
+
+    fn on_evaluate_function(&mut self, function_name: &str, args: &[ValueType]) {
+        self.events
+            .push(format!("evaluate: {} {}", function_name, args.len()));
+    }
+
+    fn on_complete_evaluate_function(
+        &mut self,
+        function_name: &str,
+        _args: &[ValueType],
+        text_output: &str,
+        result: Option<&ValueType>,
+    ) {
+        self.events.push(format!(
+            "complete: {} {} {}",
+            function_name,
+            text_output.trim(),
+            result.and_then(|r| r.get::<i32>()).unwrap()
+        ));
+    }
+
+    fn on_choose_path_string(&mut self, path: &str, args: &[ValueType]) {
+        self.events.push(format!("path: {} {}", path, args.len()));
+    }
+}
+
+#[test]
+fn event_listener_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+Hello. #greeting
+* Choice A
+  After A.
+  -> END
+
+== knot
+In knot.
+-> END
+
+== function double(x)
+Doubling.
+~ return x * 2
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+    let recorder = Brc::new(BrCell::new(EventRecorder { events: Vec::new() }));
+    let listener: Brc<BrCell<dyn StoryEventListener>> = recorder.clone();
+    story.add_event_listener(listener.clone());
+
+    story.cont()?;
+    story.choose_choice_index(0)?;
+    story.continue_maximally()?;
+
+    let mut text_output = String::new();
+    let result =
+        story.evaluate_function("double", Some(&vec![ValueType::Int(3)]), &mut text_output)?;
+    assert_eq!(Some(6), result.and_then(|r| r.get::<i32>()));
+
+    story.choose_path_string("knot", true, None)?;
+    story.cont()?;
+
+    assert_eq!(
+        vec![
+            "continue: Hello. [\"greeting\"]",
+            "choice: Choice A",
+            "continue: Choice A []",
+            "continue: After A. []",
+            "evaluate: double 1",
+            "continue: Doubling. []",
+            "complete: double Doubling. 6",
+            "path: knot 0",
+            "continue: In knot. []",
+        ],
+        recorder.borrow().events
+    );
+
+    assert!(story.remove_event_listener(&listener));
+    assert!(!story.remove_event_listener(&listener));
+
+    story.choose_path_string("knot", true, None)?;
+    story.cont()?;
+    assert_eq!(9, recorder.borrow().events.len());
+
+    Ok(())
+}
+
+#[test]
+fn profiler_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+-> knot
+== knot
+{add(1, 2)} {add(3, 4)}
+-> knot.stitch
+= stitch
+In stitch.
+-> END
+
+== function add(a, b)
+~ return a + b
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+
+    assert!(story.get_profiler().is_none());
+    story.start_profiling()?;
+    assert_eq!("3 7\nIn stitch.\n", story.continue_maximally()?);
+    let profiler = story.end_profiling().unwrap();
+    assert!(story.get_profiler().is_none());
+
+    assert_eq!(2, profiler.get_num_continues());
+
+    let root = profiler.get_root_node();
+    let knot = root.get_descendant(&["knot"]).unwrap();
+    // Entered again when the lookahead for the end of the first line is rewound
+    assert_eq!(2, knot.get_call_count());
+    assert!(knot.get_self_step_count() > 0);
+
+    let add = root.get_descendant(&["knot", "add"]).unwrap();
+    assert_eq!(2, add.get_call_count());
+    assert!(add.get_self_step_count() > 0);
+    assert!(knot.get_total_step_count() > add.get_total_step_count());
+    assert!(knot.get_total_time() >= add.get_total_time());
+
+    let stitch = root.get_descendant(&["knot.stitch"]).unwrap();
+    assert!(stitch.get_call_count() > 0);
+
+    let report = profiler.report();
+    assert!(report.starts_with("2 CONTINUES / LINES:\n"), "{report}");
+    assert!(report.contains("\nknot: total "), "{report}");
+    assert!(report.contains("\n    add: total "), "{report}");
+
+    let folded = profiler.to_folded_stacks();
+    let stacks: Vec<&str> = folded
+        .lines()
+        .map(|l| l.rsplit_once(' ').unwrap().0)
+        .collect();
+    assert!(stacks.contains(&"knot"), "{folded}");
+    assert!(stacks.contains(&"knot;add"), "{folded}");
+    assert!(stacks.contains(&"knot.stitch"), "{folded}");
+
+    // Every step is also attributed to its pointer
+    let pointers = profiler.get_pointer_profiles();
+    assert_eq!(
+        root.get_total_step_count(),
+        pointers.iter().map(|p| p.get_step_count()).sum::<u32>()
+    );
+    assert!(pointers.iter().any(|p| p.get_path().starts_with("add.")));
+    assert!(
+        pointers
+            .iter()
+            .any(|p| p.get_path().starts_with("knot.stitch."))
+    );
+    assert!(report.contains("\nPOINTERS:\n    "), "{report}");
+
+    Ok(())
+}
+
+#[test]
+fn debugger_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+-> knot
+== knot
+~ temp x = 5
+Before {double(x)}.
+-> knot.stitch
+= stitch
+In stitch.
+-> END
+
+== function double(a)
+~ temp b = a * 2
+~ return b
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+
+    assert!(story.add_breakpoint("unknown_knot").is_err());
+    story.add_breakpoint("double")?;
+    story.add_breakpoint("knot.stitch")?;
+    assert_eq!(vec!["double", "knot.stitch"], story.get_breakpoints());
+
+    // Paused inside the function, with the text before the call in the output
+    assert_eq!(
+        DebugStop::Breakpoint("double".to_owned()),
+        story.debug_continue()?
+    );
+    assert!(story.is_debug_paused());
+    assert!(story.get_current_text().is_err());
+    assert!(story.debug_output_stream().contains(&"Before ".to_owned()));
+
+    let frames = story.debug_callstack();
+    assert_eq!(2, frames.len());
+    assert!(frames[0].element_type == PushPopType::Tunnel);
+    assert!(frames[1].element_type == PushPopType::Function);
+    assert_eq!(1, frames[0].temporary_variables.len());
+    assert_eq!("x", frames[0].temporary_variables[0].0);
+    assert_eq!(Some(5), frames[0].temporary_variables[0].1.get::<i32>());
+    // The argument is still in the evaluation stack
+    assert!(frames[1].temporary_variables.is_empty());
+    assert_eq!(vec!["5"], story.debug_evaluation_stack());
+
+    assert_eq!(DebugStop::Step, story.debug_step()?);
+    let frames = story.debug_callstack();
+    assert_eq!("a", frames[1].temporary_variables[0].0);
+    assert_eq!(Some(5), frames[1].temporary_variables[0].1.get::<i32>());
+
+    // Single step until the function result is in the evaluation stack
+    let mut steps = 0;
+    while !story.debug_evaluation_stack().contains(&"10".to_owned()) {
+        assert_eq!(DebugStop::Step, story.debug_step()?);
+        steps += 1;
+        assert!(steps < 20);
+    }
+
+    // The lookahead reaches the stitch before the end of the line
+    story.remove_breakpoint("double");
+    assert_eq!(
+        DebugStop::Breakpoint("knot.stitch".to_owned()),
+        story.debug_continue()?
+    );
+    assert_eq!(DebugStop::LineCompleted, story.debug_continue()?);
+    assert!(!story.is_debug_paused());
+    assert_eq!("Before 10.\n", story.get_current_text()?);
+
+    // Hit again when the stitch runs for real, cont() resumes ignoring breakpoints
+    assert_eq!(
+        DebugStop::Breakpoint("knot.stitch".to_owned()),
+        story.debug_continue()?
+    );
+    assert_eq!("In stitch.\n", story.cont()?);
+    assert!(!story.is_debug_paused());
+
+    // Without debug calls breakpoints are ignored
+    story.clear_breakpoints();
+    story.reset_state()?;
+    story.add_breakpoint("double")?;
+    assert_eq!("Before 10.\n", story.cont()?);
+
+    // Paths are normalized the same way when removing breakpoints
+    story.clear_breakpoints();
+    story.add_breakpoint(".knot.stitch")?;
+    assert_eq!(vec!["knot.stitch"], story.get_breakpoints());
+    assert!(story.remove_breakpoint(".knot.stitch"));
+    assert!(story.get_breakpoints().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn introspection_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+EXTERNAL play_sound(name)
+EXTERNAL unused()
+VAR score = 0
+-> intro
+== intro
+{play_sound("ding")} {add(1, 2)}
+* (first) Go -> ending(true)
+= details
+Details.
+-> DONE
+= more
+More.
+-> DONE
+
+== ending(good, ref points)
+{good: Good.}
+-> END
+
+== function add(a, b)
+~ return a + b
+
+== function say_hi()
+Hi.
+
+== function never_called()
+~ return 1
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let story = Story::new(&json)?;
+
+    assert_eq!(vec!["ending", "intro", "say_hi"], story.knots());
+    assert_eq!(vec!["add", "never_called"], story.functions());
+    assert!(story.has_function("add"));
+    assert!(story.has_function("say_hi"));
+    assert!(!story.has_function("unknown"));
+    assert!(!story.has_function("global decl"));
+
+    assert_eq!(
+        Some(vec!["details".to_owned(), "more".to_owned()]),
+        story.stitches_of("intro")
+    );
+    assert_eq!(Some(vec![]), story.stitches_of("ending"));
+    assert_eq!(None, story.stitches_of("unknown"));
+
+    assert_eq!(
+        Some(vec!["good".to_owned(), "points".to_owned()]),
+        story.parameters_of("ending")
+    );
+    assert_eq!(
+        Some(vec!["a".to_owned(), "b".to_owned()]),
+        story.parameters_of("add")
+    );
+    assert_eq!(Some(vec![]), story.parameters_of("intro.details"));
+    assert_eq!(None, story.parameters_of("intro.unknown"));
+
+    assert!(story.path_exists("intro"));
+    assert!(story.path_exists("intro.details"));
+    assert!(story.path_exists("intro.details.0"));
+    assert!(!story.path_exists("intro.unknown"));
+    assert!(!story.path_exists(""));
+
+    assert_eq!(vec!["play_sound"], story.required_externals());
+
+    Ok(())
+}
+
+struct RuntimeErrorRecorder {
+    errors: Vec<RuntimeError>,
+}
+
+impl ErrorHandler for RuntimeErrorRecorder {
+    fn error(&mut self, _message: &str, _error_type: ErrorType) {}
+
+    fn runtime_error(&mut self, error: &RuntimeError) {
+        self.errors.push(error.clone());
+    }
+}
+
+#[test]
+fn structured_runtime_error_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+-> knot
+== knot
+Hello.
+-> helper ->
+Unreachable.
+
+== helper
+In helper.
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+
+    // Without an error handler the errors are kept in the state
+    let mut story = Story::new(&json)?;
+    assert!(matches!(
+        story.continue_maximally(),
+        Err(StoryError::InvalidStoryState(_))
+    ));
+
+    let e = &story.get_current_runtime_errors()[0];
+    assert_eq!(RuntimeErrorKind::OutOfContent, e.kind);
+    assert!(e.error_type == ErrorType::Error);
+    assert_eq!(
+        "unexpectedly reached end of content. Do you need a '->->' to return from a tunnel?",
+        e.message
+    );
+    // The story already left the content
+    assert!(e.path.is_none());
+    // The tunnel to helper is still in the callstack
+    let trace = e.callstack_trace.as_ref().unwrap();
+    assert!(trace.contains("[TUNNEL] <SOMEWHERE IN knot>"), "{trace}");
+    assert_eq!(story.get_current_errors()[0], e.to_string());
+
+    // The error handler receives the same error
+    let mut story = Story::new(&json)?;
+    let recorder = Brc::new(BrCell::new(RuntimeErrorRecorder { errors: Vec::new() }));
+    story.set_error_handler(recorder.clone());
+    story.continue_maximally()?;
+
+    let errors = &recorder.borrow().errors;
+    assert_eq!(1, errors.len());
+    assert_eq!(RuntimeErrorKind::OutOfContent, errors[0].kind);
+    assert!(
+        errors[0]
+            .to_string()
+            .starts_with("RUNTIME ERROR: unexpectedly reached end of content")
+    );
+
+    // Failing external functions have their own kind
+    let ink = r#"
+EXTERNAL check(x)
+-> knot
+== knot
+{check(-1)}
+-> END
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+    story.bind_fn("check", |_: i32| -> Result<i32, String> {
+        Err("expected a positive number".to_owned())
+    })?;
+    let recorder = Brc::new(BrCell::new(RuntimeErrorRecorder { errors: Vec::new() }));
+    story.set_error_handler(recorder.clone());
+    story.continue_maximally()?;
+
+    let errors = &recorder.borrow().errors;
+    assert_eq!(1, errors.len());
+    assert_eq!(RuntimeErrorKind::ExternalFunction, errors[0].kind);
+    assert!(errors[0].path.as_ref().unwrap().starts_with("knot"));
+
+    // Functions that can't accept the arguments fail with a bad argument
+    // error, also with an error handler
+    let mut story = Story::new(&json)?;
+    story.bind_fn("check", |x: String| x)?;
+    let recorder = Brc::new(BrCell::new(RuntimeErrorRecorder { errors: Vec::new() }));
+    story.set_error_handler(recorder.clone());
+    assert!(matches!(
+        story.continue_maximally(),
+        Err(StoryError::BadArgument(msg)) if msg.contains("'check'")
+    ));
+
+    let errors = &recorder.borrow().errors;
+    assert_eq!(1, errors.len());
+    assert_eq!(RuntimeErrorKind::BadArgument, errors[0].kind);
+
+    Ok(())
+}
+
+/// Returns the seed and then consecutive numbers.
+struct CountingRng {
+    next: u32,
+}
+
+impl StoryRng for CountingRng {
+    fn seed(&mut self, seed: i32) {
+        self.next = seed as u32;
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        let n = self.next;
+        self.next = self.next.wrapping_add(1);
+        n
+    }
+}
+
+#[test]
+fn story_rng_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+LIST colors = red, green, blue
+{RANDOM(1, 100)} {RANDOM(1, 100)} {LIST_RANDOM(LIST_ALL(colors))}
+{~a|b|c} {~a|b|c}
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+
+    let mut story = Story::new(&json)?;
+    story.set_rng(Brc::new(BrCell::new(CountingRng { next: 0 })));
+    story.set_seed(10);
+    assert_eq!(10, story.get_seed());
+
+    // RANDOM(1, 100) = seed % 100 + 1, and the next one uses seed + 1
+    assert_eq!("11 12 blue\n", story.cont()?);
+
+    // The seed is saved
+    let json_state = story.save_state()?;
+    let binary_state = story.save_state_binary()?;
+    let line = story.cont()?;
+
+    let mut story2 = Story::new(&json)?;
+    story2.set_rng(Brc::new(BrCell::new(CountingRng { next: 0 })));
+    story2.load_state(&json_state)?;
+    assert_eq!(10, story2.get_seed());
+    assert_eq!(line, story2.cont()?);
+
+    let mut story3 = Story::new(&json)?;
+    story3.set_rng(Brc::new(BrCell::new(CountingRng { next: 0 })));
+    story3.load_state_binary(&binary_state)?;
+    assert_eq!(10, story3.get_seed());
+    assert_eq!(line, story3.cont()?);
+
+    // Same seed, same results with the default generator
+    let mut story1 = Story::new(&json)?;
+    let mut story2 = Story::new(&json)?;
+    story1.set_seed(42);
+    story2.set_seed(42);
+    assert_eq!(story1.continue_maximally()?, story2.continue_maximally()?);
+
+    Ok(())
+}
+
+#[test]
+fn history_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+VAR gold = 0
+-> shop
+== shop ==
+Gold: {gold}
+* [Sell]
+    ~ gold += 10
+* [Steal]
+    ~ gold += 100
+- Gold: {gold}
++ [Again] -> shop
++ [Leave] -> END
+
+== other ==
+Other
++ [Other choice] Done other
+-> END
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+
+    // Disabled by default
+    assert_eq!("Gold: 0\n", story.cont()?);
+    story.choose_choice_index(0)?;
+    assert_eq!(0, story.get_history_len());
+    assert!(story.rewind(1).is_err());
+
+    let mut story = Story::new(&json)?;
+    story.set_history_depth(2);
+
+    assert_eq!("Gold: 0\n", story.cont()?);
+    story.choose_choice_index(0)?;
+    assert_eq!("Gold: 10\n", story.continue_maximally()?);
+    story.choose_choice_index(0)?;
+    assert_eq!("Gold: 10\n", story.continue_maximally()?);
+    assert_eq!(1, story.get_current_choices().len());
+    story.choose_choice_index(0)?;
+    assert_eq!("Gold: 110\n", story.continue_maximally()?);
+    assert_eq!(2, story.get_history_len());
+
+    // Back to before choosing "Steal"
+    story.rewind(1)?;
+    assert_eq!(1, story.get_history_len());
+    assert_eq!(
+        10,
+        story.get_variable("gold").unwrap().get::<i32>().unwrap()
+    );
+    assert_eq!("Steal", story.get_current_choices()[0].text);
+
+    // The oldest choice was discarded
+    assert!(story.rewind(2).is_err());
+    story.rewind(1)?;
+    assert_eq!(0, story.get_history_len());
+    assert_eq!("Gold: 10\n", story.get_current_text()?);
+    assert_eq!(2, story.get_current_choices().len());
+
+    story.choose_choice_index(0)?;
+    assert_eq!("Gold: 10\n", story.continue_maximally()?);
+
+    // Rewinding restores all the flows
+    story.switch_flow("Other")?;
+    story.choose_path_string("other", true, None)?;
+    assert_eq!("Other\n", story.cont()?);
+    story.choose_choice_index(0)?;
+    assert_eq!("Done other\n", story.cont()?);
+    story.switch_flow("DEFAULT_FLOW")?;
+    story.choose_choice_index(0)?;
+    assert_eq!("Gold: 110\n", story.continue_maximally()?);
+
+    story.rewind(2)?;
+    assert_eq!("Other\n", story.get_current_text()?);
+    assert_eq!("Other choice", story.get_current_choices()[0].text);
+    story.switch_flow("DEFAULT_FLOW")?;
+    assert_eq!(
+        10,
+        story.get_variable("gold").unwrap().get::<i32>().unwrap()
+    );
+    assert_eq!("Steal", story.get_current_choices()[0].text);
+    story.choose_choice_index(0)?;
+    assert_eq!("Gold: 110\n", story.continue_maximally()?);
+
+    // Loading a state discards the history
+    let saved = story.save_state()?;
+    story.load_state(&saved)?;
+    assert_eq!(0, story.get_history_len());
+
+    Ok(())
+}
+
+/// Counts the notified variable changes.
+struct ChangeCounter {
+    count: Brc<BrCell<i32>>,
+}
+
+impl VariableObserver for ChangeCounter {
+    fn changed(&mut self, _variable_name: &str, _new_value: &ValueType) {
+        *self.count.borrow_mut() += 1;
+    }
+}
+
+#[test]
+fn preview_choice_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+VAR gold = 0
+VAR roll = 0
+Start
+* [Open the chest]
+    ~ gold += 50
+    ~ roll = RANDOM(1, 1000)
+    You find {gold} coins. #loot
+    The chest is empty now.
+    -> next
+* [Leave]
+    -> next
+== next ==
+{next > 1: Again.|First time.}
++ [Continue] -> next
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+
+    let count = Brc::new(BrCell::new(0));
+    story.observe_variable(
+        "gold",
+        Brc::new(BrCell::new(ChangeCounter {
+            count: count.clone(),
+        })),
+    )?;
+
+    assert_eq!("Start\n", story.cont()?);
+    assert_eq!(2, story.get_current_choices().len());
+    let saved = story.save_state()?;
+
+    let preview = story.preview_choice(0, 2)?;
+    assert_eq!(2, preview.lines.len());
+    assert_eq!("You find 50 coins.\n", preview.lines[0].text);
+    assert_eq!(vec!["loot"], preview.lines[0].tags);
+    assert_eq!("The chest is empty now.\n", preview.lines[1].text);
+    assert!(preview.choices.is_empty());
+
+    let changes: Vec<&str> = preview
+        .variable_changes
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(vec!["gold", "roll"], changes);
+    assert_eq!(50, preview.variable_changes[0].1.get::<i32>().unwrap());
+    let roll = preview.variable_changes[1].1.get::<i32>().unwrap();
+
+    // Until the next choices
+    let preview = story.preview_choice(1, 10)?;
+    assert_eq!(1, preview.lines.len());
+    assert_eq!("First time.\n", preview.lines[0].text);
+    assert_eq!(vec!["Continue"], preview.choices);
+    assert!(preview.variable_changes.is_empty());
+
+    // The real state is untouched
+    assert_eq!(0, *count.borrow());
+    assert_eq!(saved, story.save_state()?);
+    assert_eq!("Start\n", story.get_current_text()?);
+    assert!(story.preview_choice(2, 1).is_err());
+
+    // The same random numbers are produced when choosing for real
+    story.choose_choice_index(0)?;
+    assert_eq!("You find 50 coins.\n", story.cont()?);
+    assert_eq!(
+        roll,
+        story.get_variable("roll").unwrap().get::<i32>().unwrap()
+    );
+    assert_eq!(1, *count.borrow());
+
+    Ok(())
+}
+
+#[test]
+fn fork_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+EXTERNAL bonus()
+VAR gold = 0
+- (top) Gold: {gold}
+* [Sell]
+    ~ gold += bonus()
+    -> top
+* [Steal]
+    ~ gold += 100
+    -> top
+* -> END
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+    story.bind_fn("bonus", || 10)?;
+
+    assert_eq!("Gold: 0\n", story.cont()?);
+
+    let mut fork1 = story.fork()?;
+    let mut fork2 = story.fork()?;
+
+    // The external functions are shared, and can be bound again in a fork
+    fork1.unbind_external_function("bonus")?;
+    fork1.bind_fn("bonus", || 20)?;
+    fork1.choose_choice_index(0)?;
+    assert_eq!("Gold: 20\n", fork1.cont()?);
+    assert_eq!(1, fork1.get_current_choices().len());
+
+    fork2.choose_choice_index(1)?;
+    assert_eq!("Gold: 100\n", fork2.cont()?);
+
+    // A fork of a fork
+    let mut fork3 = fork2.fork()?;
+    fork3.choose_choice_index(0)?;
+    assert_eq!("Gold: 110\n", fork3.cont()?);
+    assert_eq!(
+        100,
+        fork2.get_variable("gold").unwrap().get::<i32>().unwrap()
+    );
+
+    // The original story is untouched
+    assert_eq!("Gold: 0\n", story.get_current_text()?);
+    assert_eq!(2, story.get_current_choices().len());
+    story.choose_choice_index(0)?;
+    assert_eq!("Gold: 10\n", story.cont()?);
+
+    Ok(())
+}
+
+#[test]
+fn explorer_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+-> hub
+== hub ==
+Where to?
+* [End] -> ending
+* [Stop] Stopping.
+    -> DONE
+* [Loop] -> loop
+* [Broken] -> broken
+
+== ending ==
+The end.
+-> END
+
+== loop ==
+Again.
+-> loop
+
+== broken ==
+-> tunnel ->
+-> END
+
+== tunnel ==
+In the tunnel.
+
+== never ==
+Never.
+-> END
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+    let saved = story.save_state()?;
+
+    let explorer = Explorer::with_options(ExplorerOptions {
+        max_lines_per_turn: 50,
+        ..Default::default()
+    });
+    let report = explorer.explore(&mut story)?;
+
+    assert!(report.has_problems());
+    assert!(report.complete);
+    assert_eq!(4, report.iterations);
+    assert_eq!(4, report.playthroughs);
+    assert_eq!(1, report.endings);
+
+    assert_eq!(1, report.dead_ends.len());
+    assert_eq!(vec![1], report.dead_ends[0].choices);
+
+    assert_eq!(1, report.infinite_loops.len());
+    assert_eq!(vec![2], report.infinite_loops[0].choices);
+    assert_eq!(Some("loop.2".to_owned()), report.infinite_loops[0].path);
+
+    assert_eq!(1, report.errors.len());
+    assert_eq!(vec![3], report.errors[0].choices);
+    assert!(report.errors[0].message.contains("'->->'"));
+
+    assert_eq!(vec!["never"], report.unreached);
+    let visits = |path: &str| {
+        report
+            .visit_counts
+            .iter()
+            .find(|(p, _)| p == path)
+            .map(|(_, count)| *count)
+    };
+    assert_eq!(Some(1), visits("hub"));
+    assert_eq!(Some(1), visits("tunnel"));
+    assert_eq!(Some(1), visits("loop"));
+    assert!(report.to_string().contains("DEAD ENDS:"));
+
+    // The state of the story is restored
+    assert_eq!(saved, story.save_state()?);
+    assert_eq!("Where to?\n", story.cont()?);
+
+    // Random exploration from the current state
+    let explorer = Explorer::with_options(ExplorerOptions {
+        strategy: ExploreStrategy::Random(7),
+        max_iterations: 10,
+        max_lines_per_turn: 50,
+        ..Default::default()
+    });
+    let report = explorer.explore(&mut story)?;
+    assert_eq!(10, report.iterations);
+    assert_eq!(10, report.playthroughs);
+    assert!(!report.complete);
+    assert!(report.has_problems());
+
+    // A loop that never produces a line
+    let ink = r#"
+Start
+* [Spin] -> spin
+* [Stop] -> END
+== spin ==
+~ temp x = 0
+- (top)
+~ x++
+-> top
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+    let explorer = Explorer::with_options(ExplorerOptions {
+        line_time_limit: Duration::from_millis(50),
+        ..Default::default()
+    });
+    let report = explorer.explore(&mut story)?;
+    assert_eq!(1, report.infinite_loops.len());
+    assert_eq!(vec![0], report.infinite_loops[0].choices);
+    assert_eq!(1, report.endings);
+    assert!(report.complete);
+    assert_eq!("Start\n", story.cont()?);
+
+    // Async external functions are not resolved and the turn is not a dead end
+    let ink = r#"
+EXTERNAL ask(x)
+Start
+* [Ask] -> asking
+* [Stop] -> END
+== asking ==
+~ temp answer = ask(1)
+Answered {answer}.
+-> END
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+    story.bind_async_external_function("ask")?;
+    let report = Explorer::new().explore(&mut story)?;
+    assert!(report.dead_ends.is_empty());
+    assert_eq!(1, report.pending_external_calls.len());
+    assert_eq!(vec![0], report.pending_external_calls[0].choices);
+    assert!(report.pending_external_calls[0].message.contains("'ask'"));
+    assert_eq!(1, report.endings);
+    assert!(!report.complete);
+    assert!(!report.has_problems());
+    assert!(report.to_string().contains("PENDING EXTERNAL CALLS:"));
+
+    Ok(())
+}
+
+#[test]
+fn continue_line_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+Hello #greeting
+World
+* [Left] Went left. #left #dir
+* [Right] Went right.
+- -> END
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+
+    assert_eq!(
+        Line {
+            text: "Hello\n".to_owned(),
+            tags: vec!["greeting".to_owned()],
+            is_end_of_turn: false,
+        },
+        story.continue_line()?
+    );
+
+    let events: Vec<TurnEvent> = story.lines().collect::<Result<_, _>>()?;
+    assert_eq!(2, events.len());
+    match &events[0] {
+        TurnEvent::Line(line) => {
+            assert_eq!("World\n", line.text);
+            assert!(line.tags.is_empty());
+            assert!(line.is_end_of_turn);
+        }
+        _ => panic!("expected a line"),
+    }
+    match &events[1] {
+        TurnEvent::Choices(choices) => {
+            assert_eq!(2, choices.len());
+            assert_eq!("Right", choices[1].text);
+        }
+        _ => panic!("expected choices"),
+    }
+
+    // At the end of the story there are no choices to return
+    story.choose_choice_index(0)?;
+    let lines: Vec<Line> = story
+        .lines()
+        .map(|event| match event {
+            Ok(TurnEvent::Line(line)) => line,
+            _ => panic!("expected a line"),
+        })
+        .collect();
+    assert_eq!(1, lines.len());
+    assert_eq!("Went left.\n", lines[0].text);
+    assert_eq!(vec!["left", "dir"], lines[0].tags);
+    assert!(lines[0].is_end_of_turn);
+    assert!(story.lines().next().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn async_external_function_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+EXTERNAL roll_dice(sides)
+EXTERNAL wait()
+Before the roll.
+~ temp result = roll_dice(6)
+You rolled {result}.
+~ wait()
+* [Again] -> END
+
+== elsewhere ==
+Elsewhere.
+-> END
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+    story.bind_async_external_function("roll_dice")?;
+    story.bind_async_external_function("wait")?;
+
+    assert_eq!("Before the roll.\n", story.cont()?);
+    assert!(story.get_pending_external_call().is_none());
+
+    // The call stops the story until it's resolved
+    assert_eq!("", story.cont()?);
+    assert!(!story.can_continue());
+    assert!(story.get_current_choices().is_empty());
+    let pending_call = story.get_pending_external_call().unwrap();
+    assert_eq!("roll_dice", pending_call.func_name);
+    assert_eq!(6, pending_call.args[0].get::<i32>().unwrap());
+    assert!(story.cont().is_err());
+
+    // The pending call is saved with the state
+    let save = story.save_state()?;
+    let binary_save = story.save_state_binary()?;
+
+    let mut story = Story::new(&json)?;
+    story.bind_async_external_function("roll_dice")?;
+    story.bind_async_external_function("wait")?;
+    story.load_state(&save)?;
+    assert_eq!(
+        "roll_dice",
+        story.get_pending_external_call().unwrap().func_name
+    );
+
+    story.resolve_external_call(Some(ValueType::Int(4)))?;
+    assert!(story.get_pending_external_call().is_none());
+    assert_eq!("You rolled 4.\n", story.cont()?);
+
+    // The choices are available once the function without a result is
+    // resolved
+    story.cont()?;
+    assert_eq!("wait", story.get_pending_external_call().unwrap().func_name);
+    assert!(story.get_current_choices().is_empty());
+    story.resolve_external_call(None)?;
+    story.continue_maximally()?;
+    assert_eq!(1, story.get_current_choices().len());
+    assert!(story.resolve_external_call(None).is_err());
+
+    story.load_state_binary(&binary_save)?;
+    story.resolve_external_call(Some(ValueType::Int(2)))?;
+    assert_eq!("You rolled 2.\n", story.cont()?);
+
+    // Jumping elsewhere abandons the pending call, unless the call stack is
+    // kept
+    story.load_state_binary(&binary_save)?;
+    assert!(story.choose_path_string("elsewhere", false, None).is_err());
+    assert!(story.get_pending_external_call().is_some());
+    story.choose_path_string("elsewhere", true, None)?;
+    assert!(story.get_pending_external_call().is_none());
+    assert_eq!("Elsewhere.\n", story.cont()?);
+    assert!(!story.can_continue());
+
+    Ok(())
+}
+
+#[test]
+fn load_state_with_migration_test() -> Result<(), Box<dyn Error>> {
+    let ink_v1 = r#"
+VAR gold = 0
+-> shop
+=== shop ===
+~ gold = 5
+In the shop.
+* [Buy] -> market
+=== market ===
+At the market.
+-> END
+"#;
+    let json_v1 = Compiler::new().compile(ink_v1).unwrap();
+    let mut story = Story::new(&json_v1)?;
+    story.continue_maximally()?;
+    let save = story.save_state()?;
+
+    // The shop knot has been renamed
+    let json_v2 = Compiler::new()
+        .compile(&ink_v1.replace("shop", "store"))
+        .unwrap();
+    let mut story = Story::new(&json_v2)?;
+
+    let report = story.check_state(&save, &SaveMigration::default())?;
+    assert!(report.has_lost_position());
+
+    let mut migration = SaveMigration::default();
+    migration
+        .renames
+        .insert("shop".to_owned(), "store".to_owned());
+    let report = story.load_state_with_migration(&save, &migration)?;
+    assert!(report.is_clean());
+    assert!(!report.restarted);
+    assert!(
+        report
+            .renamed
+            .contains(&("shop".to_owned(), "store".to_owned()))
+    );
+    assert_eq!(1, story.get_visit_count_at_path_string("store")?);
+
+    story.choose_choice_index(0)?;
+    assert_eq!("At the market.\n", story.cont()?);
+
+    // The shop knot has been removed
+    let ink_v3 = r#"
+VAR gold = 0
+-> village
+=== village ===
+In the village.
+-> END
+"#;
+    let json_v3 = Compiler::new().compile(ink_v3).unwrap();
+    let mut story = Story::new(&json_v3)?;
+
+    assert!(
+        story
+            .load_state_with_migration(&save, &SaveMigration::default())
+            .is_err()
+    );
+
+    let migration = SaveMigration {
+        fallback: MigrationFallback::RestartAt("village".to_owned()),
+        ..Default::default()
+    };
+    let report = story.load_state_with_migration(&save, &migration)?;
+    assert!(report.restarted);
+    assert!(
+        report
+            .unresolved
+            .iter()
+            .any(|p| p.kind == SavedPathKind::VisitCount && p.path == "shop")
+    );
+    assert!(
+        report
+            .unresolved
+            .iter()
+            .any(|p| p.kind == SavedPathKind::Choice)
+    );
+
+    assert_eq!("In the village.\n", story.cont()?);
+    assert_eq!(5, story.get_variable("gold").unwrap().get::<i32>().unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn save_state_view_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+VAR gold = 0
+Hello
+* [Shop] -> shop
+* [Leave] -> END
+=== shop ===
+~ gold = 5
+~ temp price = 3
+In the shop.
+* [Buy] -> END
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+    story.continue_maximally()?;
+    let before = SaveStateView::from_json(&story.save_state()?)?;
+
+    assert!(before.globals.is_empty());
+    let flow = &before.flows[&before.current_flow_name];
+    assert_eq!(2, flow.choices.len());
+    assert_eq!("Shop", flow.choices[0].text);
+
+    story.choose_choice_index(0)?;
+    story.continue_maximally()?;
+    let after = SaveStateView::from_json(&story.save_state()?)?;
+
+    assert_eq!(5, after.globals["gold"].get::<i32>().unwrap());
+    assert_eq!(Some(&1), after.visit_counts.get("shop"));
+    let element = &after.flows[&after.current_flow_name].threads[0].callstack[0];
+    assert_eq!(PushPopType::Tunnel, element.element_type);
+    assert_eq!(
+        3,
+        element.temporary_variables["price"].get::<i32>().unwrap()
+    );
+
+    let diff = save_state_view::diff(&before, &after);
+    assert_eq!(1, diff.variables.len());
+    assert_eq!("gold", diff.variables[0].name);
+    assert!(diff.variables[0].before.is_none());
+    assert!(diff.visits.iter().any(|v| v.path == "shop" && v.after == 1));
+    assert!(diff.flows.iter().any(|f| matches!(
+        f,
+        FlowChange::Choices { after, .. } if after == &vec!["Buy".to_owned()]
+    )));
+    assert!(diff.to_string().contains("gold: (default) -> 5"));
+    assert!(
+        format!("{:?}", diff.variables[0]).contains("after: Some(Int(5))"),
+        "{:?}",
+        diff
+    );
+
+    assert!(save_state_view::diff(&after, &after).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn global_variables_test() -> Result<(), Box<dyn Error>> {
+    let ink = r#"
+VAR gold = 10
+VAR name = "Ink"
+~ gold = 15
+Hello
+-> END
+"#;
+    let json = Compiler::new().compile(ink).unwrap();
+    let mut story = Story::new(&json)?;
+    story.continue_maximally()?;
+
+    let variables = story.global_variables();
+    assert_eq!(2, variables.len());
+    assert_eq!("gold", variables[0].name);
+    assert_eq!(15, variables[0].value.get::<i32>().unwrap());
+    assert_eq!(10, variables[0].default_value.get::<i32>().unwrap());
+    assert_eq!("name", variables[1].name);
+    assert_eq!("Ink", variables[1].value.get::<&str>().unwrap());
+    assert_eq!("Ink", variables[1].default_value.get::<&str>().unwrap());
+
+    let changes = Brc::new(BrCell::new(0));
+    let changes_clone = changes.clone();
+    let _subscription = story.observe_variable_fn("gold", move |_, _| {
+        *changes_clone.borrow_mut() += 1;
+    })?;
+
+    story.reset_variable("gold")?;
+    assert_eq!(
+        10,
+        story.get_variable("gold").unwrap().get::<i32>().unwrap()
+    );
+    assert_eq!(1, *changes.borrow());
+
+    assert!(story.reset_variable("silver").is_err());
+
+    Ok(())
+}
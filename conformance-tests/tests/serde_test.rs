@@ -0,0 +1,161 @@
+use std::error::Error;
+
+use bladeink::{story::Story, story_state_snapshot::StoryStateSnapshot, value_type::ValueType};
+use bladeink_compiler::Compiler;
+
+mod common;
+
+#[test]
+fn value_type_serde_test() -> Result<(), Box<dyn Error>> {
+    let ink_source = r#"
+LIST colors = red, (green), blue
+VAR s = "hello"
+VAR f = 1.5
+VAR d = -> knot
+VAR l = (colors.red, colors.blue)
+{s}
+== knot
+-> DONE
+"#;
+    let json_string = Compiler::new().compile(ink_source).unwrap();
+    let story = Story::new(&json_string)?;
+
+    for name in ["s", "f", "d", "l"] {
+        let value = story.get_variable(name).unwrap();
+        let serialized = serde_json::to_string(&value)?;
+        let deserialized: ValueType = serde_json::from_str(&serialized)?;
+
+        assert_eq!(serialized, serde_json::to_string(&deserialized)?);
+    }
+
+    assert_eq!(
+        r#"{"String":"hello"}"#,
+        serde_json::to_string(&story.get_variable("s").unwrap())?
+    );
+    assert_eq!(
+        r#"{"DivertTarget":"knot"}"#,
+        serde_json::to_string(&story.get_variable("d").unwrap())?
+    );
+
+    let s: ValueType = serde_json::from_str(r#"{"String":" "}"#)?;
+    match s {
+        ValueType::String(s) => assert!(!s.is_non_whitespace()),
+        _ => panic!("expected a string"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn story_state_snapshot_test() -> Result<(), Box<dyn Error>> {
+    let ink_source =
+        common::get_file_string("inkfiles/runtime/multiflow-saveloadthreads.ink").unwrap();
+    let json_string = Compiler::new().compile(&ink_source).unwrap();
+    let mut story = Story::new(&json_string)?;
+
+    assert_eq!("Default line 1\n", story.cont()?);
+
+    story.switch_flow("Blue Flow")?;
+    story.choose_path_string("blue", true, None)?;
+    assert_eq!("Hello I'm blue\n", story.cont()?);
+
+    story.switch_flow("Red Flow")?;
+    story.choose_path_string("red", true, None)?;
+    assert_eq!("Hello I'm red\n", story.cont()?);
+
+    story.switch_flow("Blue Flow")?;
+    assert_eq!("Thread 1 blue choice", story.get_current_choices()[0].text);
+    story.switch_flow("Red Flow")?;
+    assert_eq!("Thread 1 red choice", story.get_current_choices()[0].text);
+
+    let saved_json = story.save_state()?;
+    let snapshot = story.save_state_snapshot()?;
+
+    assert_eq!(3, snapshot.flows.len());
+    assert_eq!("Red Flow", snapshot.current_flow_name);
+
+    let serialized = serde_json::to_string(&snapshot)?;
+    let snapshot: StoryStateSnapshot = serde_json::from_str(&serialized)?;
+
+    let mut story = Story::new(&json_string)?;
+    story.load_state_snapshot(&snapshot)?;
+
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&saved_json)?,
+        serde_json::from_str::<serde_json::Value>(&story.save_state()?)?
+    );
+
+    story.choose_choice_index(1)?;
+    assert_eq!(
+        "Thread 2 red choice\nAfter thread 2 choice (red)\n",
+        story.continue_maximally()?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn story_state_snapshot_variables_test() -> Result<(), Box<dyn Error>> {
+    let ink_source = r#"
+VAR x = 1
+VAR name = "none"
+~ temp t = 3
+~ x = 2
+~ name = "Ink"
+Hi {name}, {x}.
+* [Go] {t}
+-> DONE
+"#;
+    let json_string = Compiler::new().compile(ink_source).unwrap();
+    let mut story = Story::new(&json_string)?;
+    story.continue_maximally()?;
+
+    let snapshot = story.save_state_snapshot()?;
+    assert_eq!(Some(2), snapshot.variables_state["x"].get::<i32>());
+    assert_eq!(Some("Ink"), snapshot.variables_state["name"].get::<&str>());
+
+    let mut story = Story::new(&json_string)?;
+    story.load_state_snapshot(&snapshot)?;
+
+    assert_eq!(2, story.get_variable("x").unwrap().get::<i32>().unwrap());
+    story.choose_choice_index(0)?;
+    assert_eq!("3\n", story.continue_maximally()?);
+
+    Ok(())
+}
+
+#[test]
+fn story_state_snapshot_pending_call_test() -> Result<(), Box<dyn Error>> {
+    let ink_source = r#"
+EXTERNAL roll_dice(sides)
+~ temp result = roll_dice(6)
+You rolled {result}.
+"#;
+    let json_string = Compiler::new().compile(ink_source).unwrap();
+    let mut story = Story::new(&json_string)?;
+    story.bind_async_external_function("roll_dice")?;
+    story.cont()?;
+
+    let snapshot = story.save_state_snapshot()?;
+    let serialized = serde_json::to_string(&snapshot)?;
+    let snapshot: StoryStateSnapshot = serde_json::from_str(&serialized)?;
+    assert_eq!(
+        "roll_dice",
+        snapshot.pending_external_call.as_ref().unwrap().func_name
+    );
+
+    let mut story = Story::new(&json_string)?;
+    story.bind_async_external_function("roll_dice")?;
+    story.load_state_snapshot(&snapshot)?;
+    assert_eq!(
+        6,
+        story.get_pending_external_call().unwrap().args[0]
+            .get::<i32>()
+            .unwrap()
+    );
+
+    story.resolve_external_call(Some(ValueType::Int(3)))?;
+    assert_eq!("You rolled 3.\n", story.cont()?);
+
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use std::error::Error;
+
 use bladeink::{story::Story, story_error::StoryError};
 use bladeink_compiler::Compiler;
 use rand::{RngExt, SeedableRng, rngs::StdRng};
@@ -90,6 +92,29 @@ fn the_intercept_runtime_choices_test() -> Result<(), StoryError> {
     Ok(())
 }
 
+#[test]
+fn the_intercept_from_reader_test() -> Result<(), Box<dyn Error>> {
+    let reference_json = common::get_json_string("inkfiles/TheIntercept.ink.json")?;
+    let mut reference_story = Story::new(&reference_json)?;
+    let mut story = Story::from_reader(common::open_file("inkfiles/TheIntercept.ink.json")?)?;
+
+    for choice in [0, 1, 0] {
+        assert_eq!(
+            reference_story.continue_maximally()?,
+            story.continue_maximally()?
+        );
+        assert_eq!(
+            reference_story.get_current_choices().len(),
+            story.get_current_choices().len()
+        );
+
+        reference_story.choose_choice_index(choice)?;
+        story.choose_choice_index(choice)?;
+    }
+
+    Ok(())
+}
+
 #[test]
 fn the_intercept_random_playthrough_test() {
     let ink_source = common::get_file_string("inkfiles/TheIntercept.ink").unwrap();
@@ -1,4 +1,4 @@
-//! Compilation logic: `.ink` → `.ink.json`.
+//! Compilation logic: `.ink` → `.ink.json` (or binary `.inkb`).
 
 use std::cell::RefCell;
 use std::path::PathBuf;
@@ -11,6 +11,7 @@ use crate::Options;
 ///
 /// - If `opts.stats` is set: print stats and return (no JSON written, no play).
 /// - Otherwise: compile to JSON, write to `opts.output_file`, then optionally play.
+/// - In explore mode: compile and explore, no JSON written.
 pub fn compile(
     source: &str,
     filename: &str,
@@ -124,11 +125,17 @@ pub fn compile(
     }
 
     // ------------------------------------------------------------------
-    // Write output JSON file (unless in play-only mode with no output needed).
+    // Write output JSON (or binary) file (unless in play-only mode with no
+    // output needed).
     // ------------------------------------------------------------------
-    if !opts.play_mode {
+    if !opts.play_mode && !opts.explore_mode {
         let output_path = opts.output_file.as_ref().unwrap();
-        std::fs::write(output_path, &json_string).map_err(|e| {
+        let output = if opts.binary_output {
+            bladeink_compiler::json_to_binary(&json_string)?
+        } else {
+            json_string.clone().into_bytes()
+        };
+        std::fs::write(output_path, output).map_err(|e| {
             anyhow::anyhow!("Could not write to output file '{}': {}", output_path, e)
         })?;
         if opts.json_output {
@@ -139,7 +146,9 @@ pub fn compile(
     // ------------------------------------------------------------------
     // Play mode: run the story interactively.
     // ------------------------------------------------------------------
-    if opts.play_mode {
+    if opts.explore_mode {
+        crate::explorer_tool::explore_from_json(&json_string, opts)?;
+    } else if opts.play_mode {
         crate::player::play_from_json(&json_string, opts)?;
     }
 
@@ -0,0 +1,56 @@
+//! Diff mode (`-d`): compares two states saved by a story and prints the
+//! variables, visit counts and flows that changed between them.
+
+use std::path::Path;
+
+use bladeink::save_state_view::{self, SaveStateView};
+use serde_json::json;
+
+use crate::Options;
+
+/// Compare the state saved in `before_file` with the one in `after_file`.
+pub fn diff(before_file: &str, after_file: &Path, opts: &Options) -> anyhow::Result<()> {
+    let before = read_state(Path::new(before_file))?;
+    let after = read_state(after_file)?;
+
+    let diff = save_state_view::diff(&before, &after);
+
+    if opts.json_output {
+        let value = |v: &Option<bladeink::value_type::ValueType>| v.as_ref().map(|v| v.to_string());
+
+        let variables: Vec<_> = diff
+            .variables
+            .iter()
+            .map(|c| json!({"name": c.name, "before": value(&c.before), "after": value(&c.after)}))
+            .collect();
+        let visits: Vec<_> = diff
+            .visits
+            .iter()
+            .map(|c| json!({"path": c.path, "before": c.before, "after": c.after}))
+            .collect();
+        let flows: Vec<String> = diff.flows.iter().map(|c| c.to_string()).collect();
+
+        println!(
+            "{}",
+            json!({
+                "diff": {
+                    "variables": variables,
+                    "visits": visits,
+                    "flows": flows,
+                }
+            })
+        );
+    } else {
+        print!("{diff}");
+    }
+
+    Ok(())
+}
+
+fn read_state(file: &Path) -> anyhow::Result<SaveStateView> {
+    let json_state = std::fs::read_to_string(file)
+        .map_err(|e| anyhow::anyhow!("Could not open file '{}': {e}", file.display()))?;
+
+    SaveStateView::from_json(&json_state)
+        .map_err(|e| anyhow::anyhow!("Failed to read save state '{}': {e}", file.display()))
+}
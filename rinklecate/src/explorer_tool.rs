@@ -0,0 +1,74 @@
+//! Explore mode (`-e`): plays every branch of the story and reports the
+//! problems found, failing if there are errors, dead ends or infinite loops.
+
+use bladeink::{
+    explorer::{ExploreStrategy, Explorer, ExplorerOptions, Issue},
+    story::Story,
+};
+use serde_json::json;
+
+use crate::Options;
+
+/// Build a Story from compiled JSON and explore it.
+pub fn explore_from_json(json_string: &str, opts: &Options) -> anyhow::Result<()> {
+    let story =
+        Story::new(json_string).map_err(|e| anyhow::anyhow!("Failed to load story: {e}"))?;
+    explore(story, opts)
+}
+
+/// Explore an already-constructed story and print the report.
+pub fn explore(mut story: Story, opts: &Options) -> anyhow::Result<()> {
+    story.set_allow_external_function_fallbacks(true);
+
+    let mut options = ExplorerOptions::default();
+    if let Some(seed) = opts.explore_seed {
+        options.strategy = ExploreStrategy::Random(seed);
+    }
+    if let Some(max_depth) = opts.explore_max_depth {
+        options.max_depth = max_depth;
+    }
+    if let Some(max_iterations) = opts.explore_max_iterations {
+        options.max_iterations = max_iterations;
+    }
+
+    let report = Explorer::with_options(options)
+        .explore(&mut story)
+        .map_err(|e| anyhow::anyhow!("Failed to explore story: {e}"))?;
+
+    if opts.json_output {
+        let issues =
+            |issues: &[Issue]| -> Vec<String> { issues.iter().map(|i| i.to_string()).collect() };
+        let visits: serde_json::Map<String, serde_json::Value> = report
+            .visit_counts
+            .iter()
+            .map(|(path, count)| (path.clone(), json!(count)))
+            .collect();
+
+        println!(
+            "{}",
+            json!({
+                "exploration": {
+                    "choices": report.iterations,
+                    "playthroughs": report.playthroughs,
+                    "endings": report.endings,
+                    "complete": report.complete,
+                    "errors": issues(&report.errors),
+                    "warnings": issues(&report.warnings),
+                    "dead-ends": issues(&report.dead_ends),
+                    "infinite-loops": issues(&report.infinite_loops),
+                    "pending-external-calls": issues(&report.pending_external_calls),
+                    "unreached": report.unreached,
+                    "visits": visits,
+                }
+            })
+        );
+    } else {
+        print!("{report}");
+    }
+
+    if report.has_problems() {
+        anyhow::bail!("Exploration found problems in the story");
+    }
+
+    Ok(())
+}
@@ -7,16 +7,25 @@
 //!    -o <filename>   Output file name
 //!    -c              Count all visits to knots, stitches and weave points
 //!    -p              Play mode
+//!    -e              Explore mode — play every branch and report problems
+//!    -r <seed>       Explore choosing random choices with the given seed instead of every branch
+//!    -m <depth>      Maximum number of choices of a playthrough in explore mode
+//!    -n <choices>    Maximum number of choices of the whole exploration in explore mode
+//!    -d <save file>  Diff mode — compare the given save state with the input save state
 //!    -j              JSON output mode (for communication with tools like Inky)
+//!    -b              Binary output, write the compiled story in the binary format (.inkb)
 //!    -s              Print stats about story (word count, knots, etc.)
 //!    -v              Verbose mode — print compilation timings
 //!    -k              Keep rinklecate running in play mode after story is complete
 //!    -x <directory>  Import plugins (accepted but ignored — not supported in this implementation)
 
 mod compiler_tool;
+mod diff_tool;
+mod explorer_tool;
 mod player;
 
 use std::process;
+use std::str::FromStr;
 use std::time::Instant;
 
 pub const EXIT_CODE_ERROR: i32 = 1;
@@ -25,10 +34,18 @@ pub const EXIT_CODE_ERROR: i32 = 1;
 pub struct Options {
     pub verbose: bool,
     pub play_mode: bool,
+    pub explore_mode: bool,
+    /// Seed of a random exploration, systematic if `None`.
+    pub explore_seed: Option<u64>,
+    pub explore_max_depth: Option<usize>,
+    pub explore_max_iterations: Option<usize>,
     pub stats: bool,
     pub json_output: bool,
+    pub binary_output: bool,
     pub input_file: Option<String>,
     pub output_file: Option<String>,
+    /// Save state to compare with the input file, in diff mode.
+    pub diff_file: Option<String>,
     pub count_all_visits: bool,
     pub keep_open_after_story_finish: bool,
     /// Plugin directories — accepted for interface compatibility but ignored.
@@ -40,10 +57,16 @@ impl Default for Options {
         Self {
             verbose: false,
             play_mode: false,
+            explore_mode: false,
+            explore_seed: None,
+            explore_max_depth: None,
+            explore_max_iterations: None,
             stats: false,
             json_output: false,
+            binary_output: false,
             input_file: None,
             output_file: None,
+            diff_file: None,
             // Match inklecate: always count visits by default.
             count_all_visits: true,
             keep_open_after_story_finish: false,
@@ -94,6 +117,10 @@ fn run(mut opts: Options) -> anyhow::Result<()> {
         anyhow::bail!("Could not open file '{}'", input_file);
     }
 
+    if let Some(diff_file) = &opts.diff_file {
+        return diff_tool::diff(diff_file, &full_input, &opts);
+    }
+
     let input_base_dir = full_input.parent().map(|p| p.to_path_buf());
     let filename_only = full_input
         .file_name()
@@ -103,10 +130,15 @@ fn run(mut opts: Options) -> anyhow::Result<()> {
 
     // Resolve output path
     if opts.output_file.is_none() {
+        let extension = if opts.binary_output {
+            ".inkb"
+        } else {
+            ".ink.json"
+        };
         let out = input_base_dir
             .as_deref()
             .unwrap_or(&working_dir)
-            .join(change_extension(&filename_only, ".ink.json"));
+            .join(change_extension(&filename_only, extension));
         opts.output_file = Some(out.to_string_lossy().to_string());
     } else {
         // If output was given as relative, resolve it relative to input dir
@@ -120,6 +152,18 @@ fn run(mut opts: Options) -> anyhow::Result<()> {
         }
     }
 
+    if filename_only.to_lowercase().ends_with(".inkb") {
+        // Play directly from a binary story — force play mode
+        opts.play_mode = true;
+        let data = std::fs::read(&full_input)?;
+        let story = bladeink::story::Story::from_binary(&data)
+            .map_err(|e| anyhow::anyhow!("Failed to load story: {e}"))?;
+        if opts.explore_mode {
+            return explorer_tool::explore(story, &opts);
+        }
+        return player::play(story, &opts);
+    }
+
     let input_string = std::fs::read_to_string(&full_input)?;
     // Strip UTF-8 BOM if present
     let input_string = input_string
@@ -151,7 +195,11 @@ fn run(mut opts: Options) -> anyhow::Result<()> {
                 t0.elapsed().as_secs_f64() * 1000.0
             );
         }
-        player::play(story, &opts)?;
+        if opts.explore_mode {
+            explorer_tool::explore(story, &opts)?;
+        } else {
+            player::play(story, &opts)?;
+        }
     } else {
         // Compile .ink
         let t0 = Instant::now();
@@ -177,6 +225,10 @@ fn parse_arguments(args: &[String]) -> Option<Options> {
     let mut i = 0;
     let mut next_is_output = false;
     let mut next_is_plugin_dir = false;
+    let mut next_is_diff = false;
+    let mut next_is_seed = false;
+    let mut next_is_max_depth = false;
+    let mut next_is_max_iterations = false;
 
     while i < args.len() {
         let arg = &args[i];
@@ -188,6 +240,34 @@ fn parse_arguments(args: &[String]) -> Option<Options> {
             continue;
         }
 
+        if next_is_diff {
+            opts.diff_file = Some(arg.clone());
+            next_is_diff = false;
+            i += 1;
+            continue;
+        }
+
+        if next_is_seed {
+            opts.explore_seed = Some(parse_number(arg, 'r')?);
+            next_is_seed = false;
+            i += 1;
+            continue;
+        }
+
+        if next_is_max_depth {
+            opts.explore_max_depth = Some(parse_number(arg, 'm')?);
+            next_is_max_depth = false;
+            i += 1;
+            continue;
+        }
+
+        if next_is_max_iterations {
+            opts.explore_max_iterations = Some(parse_number(arg, 'n')?);
+            next_is_max_iterations = false;
+            i += 1;
+            continue;
+        }
+
         if next_is_plugin_dir {
             opts.plugin_directories.push(arg.clone());
             next_is_plugin_dir = false;
@@ -199,12 +279,18 @@ fn parse_arguments(args: &[String]) -> Option<Options> {
             for ch in arg.chars().skip(1) {
                 match ch {
                     'p' => opts.play_mode = true,
+                    'e' => opts.explore_mode = true,
                     'j' => opts.json_output = true,
+                    'b' => opts.binary_output = true,
                     'v' => opts.verbose = true,
                     's' => opts.stats = true,
                     'c' => opts.count_all_visits = true,
                     'k' => opts.keep_open_after_story_finish = true,
                     'o' => next_is_output = true,
+                    'd' => next_is_diff = true,
+                    'r' => next_is_seed = true,
+                    'm' => next_is_max_depth = true,
+                    'n' => next_is_max_iterations = true,
                     'x' => next_is_plugin_dir = true,
                     other => eprintln!("Warning: unsupported argument '-{other}' ignored"),
                 }
@@ -220,6 +306,16 @@ fn parse_arguments(args: &[String]) -> Option<Options> {
     Some(opts)
 }
 
+fn parse_number<T: FromStr>(arg: &str, flag: char) -> Option<T> {
+    match arg.parse() {
+        Ok(n) => Some(n),
+        Err(_) => {
+            eprintln!("Invalid number '{arg}' for -{flag}");
+            None
+        }
+    }
+}
+
 fn print_usage() {
     eprintln!(
         "Usage: rinklecate <options> <ink file>
@@ -227,7 +323,16 @@ fn print_usage() {
    -c              Count all visits to knots, stitches and weave points, not
                    just those referenced by TURNS_SINCE and read counts.
    -p              Play mode
+   -e              Explore mode - play every branch of the story and report errors,
+                   dead ends, infinite loops and unreached knots
+   -r <seed>       Explore choosing random choices with the given seed, instead of
+                   trying every branch
+   -m <depth>      Maximum number of choices of a single playthrough in explore mode
+   -n <choices>    Maximum number of choices of the whole exploration in explore mode
+   -d <save file>  Diff mode - compare the given save state with the input save state
+                   and print the changed variables, visits and flows
    -j              Output in JSON format (for communication with tools like Inky)
+   -b              Write the compiled story in the binary format (.inkb) instead of JSON
    -s              Print stats about story including word count
    -v              Verbose mode - print compilation timings
    -k              Keep rinklecate running in play mode even after story is complete
@@ -102,3 +102,164 @@ fn json_issues_escape_backslashes_test() -> Result<(), Box<dyn std::error::Error
     fs::remove_dir_all(&temp_dir)?;
     Ok(())
 }
+
+/// Compile to the binary format with -b and play the resulting .inkb file.
+#[test]
+fn binary_compile_and_play_test() -> Result<(), Box<dyn std::error::Error>> {
+    let unique = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let temp_dir = std::env::temp_dir().join(format!("blade-ink-rs-{unique}"));
+    fs::create_dir_all(&temp_dir)?;
+    let source_path = temp_dir.join("test1.ink");
+    fs::copy(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../conformance-tests/inkfiles/test1.ink"),
+        &source_path,
+    )?;
+
+    Command::cargo_bin("rinklecate")?
+        .args(["-b", source_path.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let binary_path = temp_dir.join("test1.inkb");
+    assert!(binary_path.exists());
+
+    let mut cmd = Command::cargo_bin("rinklecate")?;
+    cmd.arg(&binary_path);
+    cmd.stdin(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+
+    let mut child = cmd.spawn().unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+
+    stdin.write_all(b"1\n").unwrap();
+
+    let output = child.wait_with_output()?;
+    let output_str = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert!(output_str.contains("Test conditional choices"));
+    assert!(output_str.ends_with("one\n"));
+
+    fs::remove_dir_all(&temp_dir)?;
+    Ok(())
+}
+
+/// Explore mode succeeds for a correct story and fails when problems are found.
+#[test]
+fn explore_mode_test() -> Result<(), Box<dyn std::error::Error>> {
+    let unique = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let temp_dir = std::env::temp_dir().join(format!("blade-ink-rs-{unique}"));
+    fs::create_dir_all(&temp_dir)?;
+
+    let good_path = temp_dir.join("good.ink");
+    fs::write(&good_path, "Hello\n* [Left] -> END\n* [Right] -> END\n")?;
+
+    Command::cargo_bin("rinklecate")?
+        .args(["-e", good_path.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2 CHOICES / 2 PLAYTHROUGHS / 2 ENDINGS",
+        ));
+
+    // Nothing is written in explore mode
+    assert!(!temp_dir.join("good.ink.json").exists());
+
+    // Random exploration with a budget
+    Command::cargo_bin("rinklecate")?
+        .args(["-e", "-r", "3", "-m", "10", "-n", "1"])
+        .arg(good_path.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1 CHOICES / 1 PLAYTHROUGHS / 1 ENDINGS (incomplete)",
+        ));
+
+    Command::cargo_bin("rinklecate")?
+        .args(["-e", "-n", "many", good_path.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid number 'many' for -n"));
+
+    let bad_path = temp_dir.join("bad.ink");
+    fs::write(
+        &bad_path,
+        "Hello\n* [Left] -> END\n* [Right] -> right\n== right ==\nStuck.\n-> DONE\n== unused ==\nNever.\n-> END\n",
+    )?;
+
+    Command::cargo_bin("rinklecate")?
+        .args(["-e", bad_path.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("DEAD ENDS:"))
+        .stdout(predicate::str::contains("UNREACHED:\n    unused"))
+        .stderr(predicate::str::contains("Exploration found problems"));
+
+    // JSON output, with the visit statistics
+    let output = Command::cargo_bin("rinklecate")?
+        .args(["-e", "-j", bad_path.to_str().unwrap()])
+        .output()?;
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    let line = stdout
+        .lines()
+        .find(|line| line.contains("\"exploration\""))
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_str(line)?;
+    let exploration = &report["exploration"];
+    assert_eq!(1, exploration["dead-ends"].as_array().unwrap().len());
+    assert_eq!(1, exploration["visits"]["right"]);
+    assert_eq!(0, exploration["visits"]["unused"]);
+
+    fs::remove_dir_all(&temp_dir)?;
+    Ok(())
+}
+
+/// Diff mode prints what changed between two save states.
+#[test]
+fn diff_mode_test() -> Result<(), Box<dyn std::error::Error>> {
+    let unique = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let temp_dir = std::env::temp_dir().join(format!("blade-ink-rs-diff-{unique}"));
+    fs::create_dir_all(&temp_dir)?;
+
+    let json = bladeink_compiler::Compiler::new()
+        .compile(
+            "VAR gold = 0\nHello\n* [Shop] -> shop\n== shop ==\n~ gold = 5\nIn the shop.\n-> END\n",
+        )
+        .unwrap();
+    let mut story = bladeink::story::Story::new(&json)?;
+    story.continue_maximally()?;
+    let before_path = temp_dir.join("before.json");
+    fs::write(&before_path, story.save_state()?)?;
+
+    story.choose_choice_index(0)?;
+    story.continue_maximally()?;
+    let after_path = temp_dir.join("after.json");
+    fs::write(&after_path, story.save_state()?)?;
+
+    Command::cargo_bin("rinklecate")?
+        .args([
+            "-d",
+            before_path.to_str().unwrap(),
+            after_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "VARIABLES:\n    gold: (default) -> 5",
+        ))
+        .stdout(predicate::str::contains("shop: 0 -> 1"));
+
+    Command::cargo_bin("rinklecate")?
+        .args([
+            "-d",
+            before_path.to_str().unwrap(),
+            before_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("NO DIFFERENCES"));
+
+    fs::remove_dir_all(&temp_dir)?;
+    Ok(())
+}
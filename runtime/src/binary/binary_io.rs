@@ -0,0 +1,336 @@
+//! Low level encoding shared by the binary story and save state formats.
+//!
+//! A binary file is made of a header (a 4 bytes magic number and the format
+//! version), a table with all the strings used in the file and the body.
+//! Integers are encoded as LEB128 varints (signed ones zigzag encoded) and
+//! strings are written as an index in the string table, so repeated names and
+//! paths are stored only once.
+use std::collections::HashMap;
+
+use crate::story_error::StoryError;
+
+/// Maximum nesting of containers, to not overflow the stack with crafted data.
+const MAX_CONTAINER_DEPTH: usize = 256;
+
+pub(crate) struct BinaryWriter {
+    header: Vec<u8>,
+    strings: Vec<String>,
+    string_indices: HashMap<String, u32>,
+    body: Vec<u8>,
+}
+
+impl BinaryWriter {
+    pub(crate) fn new(magic: &[u8; 4], format_version: u32) -> Self {
+        let mut header = magic.to_vec();
+        write_varint(&mut header, format_version);
+
+        Self {
+            header,
+            strings: Vec::new(),
+            string_indices: HashMap::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub(crate) fn write_u8(&mut self, v: u8) {
+        self.body.push(v);
+    }
+
+    pub(crate) fn write_bool(&mut self, v: bool) {
+        self.body.push(v as u8);
+    }
+
+    pub(crate) fn write_uint(&mut self, v: u32) {
+        write_varint(&mut self.body, v);
+    }
+
+    pub(crate) fn write_len(&mut self, len: usize) {
+        self.write_uint(len as u32);
+    }
+
+    pub(crate) fn write_int(&mut self, v: i32) {
+        self.write_uint(((v << 1) ^ (v >> 31)) as u32);
+    }
+
+    pub(crate) fn write_float(&mut self, v: f32) {
+        self.body.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(crate) fn write_str(&mut self, s: &str) {
+        let index = match self.string_indices.get(s) {
+            Some(index) => *index,
+            None => {
+                let index = self.strings.len() as u32;
+                self.strings.push(s.to_owned());
+                self.string_indices.insert(s.to_owned(), index);
+                index
+            }
+        };
+
+        self.write_uint(index);
+    }
+
+    /// A flag byte followed by the string, if any.
+    pub(crate) fn write_opt_str(&mut self, s: Option<&str>) {
+        match s {
+            Some(s) => {
+                self.write_u8(1);
+                self.write_str(s);
+            }
+            None => self.write_u8(0),
+        }
+    }
+
+    pub(crate) fn finish(self) -> Vec<u8> {
+        let mut out = self.header;
+        write_varint(&mut out, self.strings.len() as u32);
+
+        for s in &self.strings {
+            write_varint(&mut out, s.len() as u32);
+            out.extend_from_slice(s.as_bytes());
+        }
+
+        out.extend_from_slice(&self.body);
+
+        out
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut v: u32) {
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+
+        if v == 0 {
+            out.push(byte);
+            break;
+        }
+
+        out.push(byte | 0x80);
+    }
+}
+
+pub(crate) struct BinaryReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    strings: Vec<String>,
+    format_version: u32,
+    container_depth: usize,
+}
+
+impl<'a> BinaryReader<'a> {
+    /// Checks the header and reads the string table.
+    pub(crate) fn new(
+        data: &'a [u8],
+        magic: &[u8; 4],
+        current_format_version: u32,
+    ) -> Result<Self, StoryError> {
+        if data.len() < magic.len() || &data[..magic.len()] != magic {
+            return Err(StoryError::BadBinary(
+                "Unknown file type. Are you sure it's a valid binary ink file?".to_owned(),
+            ));
+        }
+
+        let mut reader = Self {
+            data,
+            pos: magic.len(),
+            strings: Vec::new(),
+            format_version: 0,
+            container_depth: 0,
+        };
+
+        let format_version = reader.read_uint()?;
+
+        if format_version > current_format_version {
+            return Err(StoryError::BadBinary(format!(
+                "Binary format version {} is newer than the version supported by this engine ({})",
+                format_version, current_format_version
+            )));
+        }
+
+        let num_strings = reader.read_count()?;
+        let mut strings = Vec::with_capacity(num_strings);
+
+        for _ in 0..num_strings {
+            let len = reader.read_len()?;
+            let bytes = reader.read_bytes(len)?;
+            let s = std::str::from_utf8(bytes)
+                .map_err(|_| StoryError::BadBinary("Invalid UTF-8 string".to_owned()))?;
+            strings.push(s.to_owned());
+        }
+
+        reader.strings = strings;
+        reader.format_version = format_version;
+
+        Ok(reader)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], StoryError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| StoryError::BadBinary("Unexpected end of data".to_owned()))?;
+
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+
+        Ok(bytes)
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8, StoryError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub(crate) fn read_bool(&mut self) -> Result<bool, StoryError> {
+        Ok(self.read_u8()? != 0)
+    }
+
+    pub(crate) fn read_uint(&mut self) -> Result<u32, StoryError> {
+        let mut result: u32 = 0;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.read_u8()?;
+
+            if shift >= 32 {
+                return Err(StoryError::BadBinary("Invalid varint".to_owned()));
+            }
+
+            result |= ((byte & 0x7f) as u32) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+
+            shift += 7;
+        }
+    }
+
+    pub(crate) fn read_len(&mut self) -> Result<usize, StoryError> {
+        Ok(self.read_uint()? as usize)
+    }
+
+    /// Reads the number of elements that follow. Every element takes at
+    /// least one byte, so a count larger than the data left is an error. This
+    /// keeps the allocations made for the elements bounded by the data size.
+    pub(crate) fn read_count(&mut self) -> Result<usize, StoryError> {
+        let count = self.read_len()?;
+
+        if count > self.data.len() - self.pos {
+            return Err(StoryError::BadBinary(format!(
+                "Element count out of range: {}",
+                count
+            )));
+        }
+
+        Ok(count)
+    }
+
+    pub(crate) fn read_int(&mut self) -> Result<i32, StoryError> {
+        let v = self.read_uint()?;
+        Ok(((v >> 1) as i32) ^ -((v & 1) as i32))
+    }
+
+    pub(crate) fn read_float(&mut self) -> Result<f32, StoryError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub(crate) fn read_str(&mut self) -> Result<&str, StoryError> {
+        let index = self.read_len()?;
+
+        match self.strings.get(index) {
+            Some(s) => Ok(s.as_str()),
+            None => Err(StoryError::BadBinary(format!(
+                "String index out of range: {}",
+                index
+            ))),
+        }
+    }
+
+    pub(crate) fn read_string(&mut self) -> Result<String, StoryError> {
+        self.read_str().map(str::to_owned)
+    }
+
+    pub(crate) fn read_opt_string(&mut self) -> Result<Option<String>, StoryError> {
+        if self.read_bool()? {
+            Ok(Some(self.read_string()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Called before reading the content of a container, paired with
+    /// [`exit_container`](Self::exit_container).
+    pub(crate) fn enter_container(&mut self) -> Result<(), StoryError> {
+        if self.container_depth >= MAX_CONTAINER_DEPTH {
+            return Err(StoryError::BadBinary(
+                "Containers are nested too deeply".to_owned(),
+            ));
+        }
+
+        self.container_depth += 1;
+
+        Ok(())
+    }
+
+    pub(crate) fn exit_container(&mut self) {
+        self.container_depth -= 1;
+    }
+
+    /// The format version of the data, to read older layouts.
+    pub(crate) fn get_format_version(&self) -> u32 {
+        self.format_version
+    }
+
+    pub(crate) fn is_at_end(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_test() {
+        let mut w = BinaryWriter::new(b"TEST", 1);
+        w.write_int(-1);
+        w.write_int(i32::MIN);
+        w.write_int(i32::MAX);
+        w.write_uint(300);
+        w.write_float(1.5);
+        w.write_str("hello");
+        w.write_opt_str(None);
+        w.write_opt_str(Some("hello"));
+        let data = w.finish();
+
+        let mut r = BinaryReader::new(&data, b"TEST", 1).unwrap();
+        assert_eq!(-1, r.read_int().unwrap());
+        assert_eq!(i32::MIN, r.read_int().unwrap());
+        assert_eq!(i32::MAX, r.read_int().unwrap());
+        assert_eq!(300, r.read_uint().unwrap());
+        assert_eq!(1.5, r.read_float().unwrap());
+        assert_eq!("hello", r.read_str().unwrap());
+        assert_eq!(None, r.read_opt_string().unwrap());
+        assert_eq!(Some("hello".to_owned()), r.read_opt_string().unwrap());
+        assert!(r.is_at_end());
+
+        assert!(BinaryReader::new(&data, b"TEST", 0).is_err());
+        assert!(BinaryReader::new(&data, b"NOPE", 1).is_err());
+    }
+
+    #[test]
+    fn count_out_of_range_test() {
+        assert!(BinaryReader::new(b"TEST\x01\xff\xff\xff\xff\x0f", b"TEST", 1).is_err());
+
+        let mut w = BinaryWriter::new(b"TEST", 1);
+        w.write_len(2);
+        w.write_u8(0);
+        let data = w.finish();
+
+        let mut r = BinaryReader::new(&data, b"TEST", 1).unwrap();
+        assert!(r.read_count().is_err());
+    }
+}
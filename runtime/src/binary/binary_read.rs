@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+
+use super::{binary_io::BinaryReader, *};
+use crate::{
+    choice::Choice,
+    choice_point::ChoicePoint,
+    container::Container,
+    control_command::ControlCommand,
+    divert::Divert,
+    glue::Glue,
+    ink_list::InkList,
+    ink_list_item::InkListItem,
+    list_definition::ListDefinition,
+    list_definitions_origin::ListDefinitionsOrigin,
+    native_function_call::NativeFunctionCall,
+    object::RTObject,
+    path::Path,
+    push_pop::PushPopType,
+    story::{INK_VERSION_CURRENT, INK_VERSION_MINIMUM_COMPATIBLE},
+    story_error::StoryError,
+    tag::Tag,
+    threadsafe::Brc,
+    value::Value,
+    value_type::ValueType,
+    variable_assigment::VariableAssignment,
+    variable_reference::VariableReference,
+    void::Void,
+};
+
+pub fn load_from_bytes(
+    data: &[u8],
+) -> Result<(i32, Brc<Container>, Brc<ListDefinitionsOrigin>), StoryError> {
+    let mut r = BinaryReader::new(data, STORY_MAGIC, STORY_FORMAT_VERSION)?;
+
+    let version = r.read_int()?;
+
+    if version > INK_VERSION_CURRENT {
+        return Err(StoryError::BadBinary(
+            "Version of ink used to build story was newer than the current version of the engine"
+                .to_owned(),
+        ));
+    } else if version < INK_VERSION_MINIMUM_COMPATIBLE {
+        return Err(StoryError::BadBinary("Version of ink used to build story is too old to be loaded by this version of the engine".to_owned()));
+    }
+
+    let list_definitions = Brc::new(read_list_definitions(&mut r)?);
+    let main_content_container = read_container(&mut r)?;
+
+    if !r.is_at_end() {
+        return Err(StoryError::BadBinary(
+            "Unexpected data after the story content".to_owned(),
+        ));
+    }
+
+    Ok((version, main_content_container, list_definitions))
+}
+
+fn read_list_definitions(r: &mut BinaryReader) -> Result<ListDefinitionsOrigin, StoryError> {
+    let num_lists = r.read_count()?;
+    let mut all_defs: Vec<ListDefinition> = Vec::with_capacity(num_lists);
+
+    for _ in 0..num_lists {
+        let name = r.read_string()?;
+        let num_items = r.read_count()?;
+        let mut items: HashMap<String, i32> = HashMap::with_capacity(num_items);
+
+        for _ in 0..num_items {
+            let item_name = r.read_string()?;
+            items.insert(item_name, r.read_int()?);
+        }
+
+        all_defs.push(ListDefinition::new(name, items));
+    }
+
+    Ok(ListDefinitionsOrigin::new(&mut all_defs))
+}
+
+pub(crate) fn read_rtobject(r: &mut BinaryReader) -> Result<Brc<dyn RTObject>, StoryError> {
+    let obj_type = r.read_u8()?;
+
+    let obj: Brc<dyn RTObject> = match obj_type {
+        OBJ_CONTAINER => read_container(r)?,
+        OBJ_GLUE => Brc::new(Glue::new()),
+        OBJ_VOID => Brc::new(Void::new()),
+        OBJ_CONTROL_COMMAND => {
+            let name = r.read_str()?;
+            match ControlCommand::new_from_name(name) {
+                Some(cc) => Brc::new(cc),
+                None => {
+                    return Err(StoryError::BadBinary(format!(
+                        "Unknown control command: {}",
+                        name
+                    )));
+                }
+            }
+        }
+        OBJ_NATIVE_FUNCTION_CALL => {
+            let name = r.read_str()?;
+            match NativeFunctionCall::new_from_name(name) {
+                Some(f) => Brc::new(f),
+                None => {
+                    return Err(StoryError::BadBinary(format!(
+                        "Unknown native function: {}",
+                        name
+                    )));
+                }
+            }
+        }
+        OBJ_DIVERT => {
+            let flags = r.read_u8()?;
+            let target = r.read_string()?;
+            let external = flags & DIVERT_EXTERNAL != 0;
+            let external_args = if external { r.read_len()? } else { 0 };
+
+            let (var_divert_name, target_path) = if flags & DIVERT_VARIABLE_TARGET != 0 {
+                (Some(target), None)
+            } else {
+                (None, Some(target))
+            };
+
+            let push_type = if flags & DIVERT_TUNNEL != 0 {
+                PushPopType::Tunnel
+            } else {
+                PushPopType::Function
+            };
+
+            Brc::new(Divert::new(
+                flags & DIVERT_PUSHES_TO_STACK != 0,
+                push_type,
+                external,
+                external_args,
+                flags & DIVERT_CONDITIONAL != 0,
+                var_divert_name,
+                target_path.as_deref(),
+            ))
+        }
+        OBJ_CHOICE_POINT => {
+            let path_string_on_choice = r.read_string()?;
+            let flags = r.read_int()?;
+            Brc::new(ChoicePoint::new(flags, &path_string_on_choice))
+        }
+        OBJ_VARIABLE_REFERENCE => Brc::new(VariableReference::new(r.read_str()?)),
+        OBJ_READ_COUNT => Brc::new(VariableReference::from_path_for_count(r.read_str()?)),
+        OBJ_VARIABLE_ASSIGNMENT => {
+            let flags = r.read_u8()?;
+            Brc::new(VariableAssignment::new(
+                r.read_str()?,
+                flags & VAR_ASS_NEW_DECLARATION != 0,
+                flags & VAR_ASS_GLOBAL != 0,
+            ))
+        }
+        OBJ_TAG => Brc::new(Tag::new(r.read_str()?)),
+        _ => Brc::new(Value::new_value_type(read_value_of_type(r, obj_type)?)),
+    };
+
+    Ok(obj)
+}
+
+fn read_container(r: &mut BinaryReader) -> Result<Brc<Container>, StoryError> {
+    r.enter_container()?;
+    let container = read_container_content(r);
+    r.exit_container();
+
+    container
+}
+
+fn read_container_content(r: &mut BinaryReader) -> Result<Brc<Container>, StoryError> {
+    let name = r.read_opt_string()?;
+    let flags = r.read_int()?;
+
+    let num_content = r.read_count()?;
+    let mut content: Vec<Brc<dyn RTObject>> = Vec::with_capacity(num_content);
+
+    for _ in 0..num_content {
+        content.push(read_rtobject(r)?);
+    }
+
+    let num_named = r.read_count()?;
+    let mut named_only_content: HashMap<String, Brc<Container>> = HashMap::with_capacity(num_named);
+
+    for _ in 0..num_named {
+        let key = r.read_string()?;
+        named_only_content.insert(key, read_container(r)?);
+    }
+
+    Ok(Container::new(name, flags, content, named_only_content))
+}
+
+fn read_value_of_type(r: &mut BinaryReader, obj_type: u8) -> Result<ValueType, StoryError> {
+    let value = match obj_type {
+        OBJ_BOOL => ValueType::Bool(r.read_bool()?),
+        OBJ_INT => ValueType::Int(r.read_int()?),
+        OBJ_FLOAT => ValueType::Float(r.read_float()?),
+        OBJ_STRING => ValueType::from(r.read_str()?),
+        OBJ_LIST => ValueType::List(read_ink_list(r)?),
+        OBJ_DIVERT_TARGET => {
+            ValueType::DivertTarget(Path::new_with_components_string(Some(r.read_str()?)))
+        }
+        OBJ_VARIABLE_POINTER => {
+            let variable_name = r.read_string()?;
+            let context_index = r.read_int()?;
+            Value::new_variable_pointer(&variable_name, context_index).value
+        }
+        _ => {
+            return Err(StoryError::BadBinary(format!(
+                "Unknown object type: {}",
+                obj_type
+            )));
+        }
+    };
+
+    Ok(value)
+}
+
+pub(crate) fn read_ink_list(r: &mut BinaryReader) -> Result<InkList, StoryError> {
+    let mut list = InkList::new();
+
+    let num_items = r.read_count()?;
+
+    for _ in 0..num_items {
+        let origin_name = r.read_opt_string()?;
+        let item_name = r.read_string()?;
+        let value = r.read_int()?;
+        list.items
+            .insert(InkListItem::new(origin_name, item_name), value);
+    }
+
+    let num_origins = r.read_count()?;
+
+    if num_origins > 0 {
+        let mut origin_names = Vec::with_capacity(num_origins);
+
+        for _ in 0..num_origins {
+            origin_names.push(r.read_string()?);
+        }
+
+        list.set_initial_origin_names(origin_names);
+    }
+
+    Ok(list)
+}
+
+pub(crate) fn read_list_rt_objs(
+    r: &mut BinaryReader,
+) -> Result<Vec<Brc<dyn RTObject>>, StoryError> {
+    let len = r.read_count()?;
+    let mut list: Vec<Brc<dyn RTObject>> = Vec::with_capacity(len);
+
+    for _ in 0..len {
+        list.push(read_rtobject(r)?);
+    }
+
+    Ok(list)
+}
+
+pub(crate) fn read_dictionary_values(
+    r: &mut BinaryReader,
+) -> Result<HashMap<String, Brc<Value>>, StoryError> {
+    let len = r.read_count()?;
+    let mut dict: HashMap<String, Brc<Value>> = HashMap::with_capacity(len);
+
+    for _ in 0..len {
+        let name = r.read_string()?;
+        let obj_type = r.read_u8()?;
+        dict.insert(
+            name,
+            Brc::new(Value::new_value_type(read_value_of_type(r, obj_type)?)),
+        );
+    }
+
+    Ok(dict)
+}
+
+pub(crate) fn read_int_dictionary(
+    r: &mut BinaryReader,
+) -> Result<HashMap<String, i32>, StoryError> {
+    let len = r.read_count()?;
+    let mut dict: HashMap<String, i32> = HashMap::with_capacity(len);
+
+    for _ in 0..len {
+        let key = r.read_string()?;
+        dict.insert(key, r.read_int()?);
+    }
+
+    Ok(dict)
+}
+
+pub(crate) fn read_choice(r: &mut BinaryReader) -> Result<Choice, StoryError> {
+    let text = r.read_string()?;
+    let index = r.read_len()?;
+    let source_path = r.read_string()?;
+    let original_thread_index = r.read_len()?;
+    let path_string_on_choice = r.read_string()?;
+
+    let num_tags = r.read_count()?;
+    let mut tags = Vec::with_capacity(num_tags);
+
+    for _ in 0..num_tags {
+        tags.push(r.read_string()?);
+    }
+
+    Ok(Choice::new_from_json(
+        &path_string_on_choice,
+        source_path,
+        &text,
+        index,
+        original_thread_index,
+        tags,
+    ))
+}
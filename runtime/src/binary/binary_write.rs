@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+
+use super::{binary_io::BinaryWriter, *};
+use crate::{
+    choice::Choice, choice_point::ChoicePoint, container::Container,
+    control_command::ControlCommand, divert::Divert, glue::Glue, ink_list::InkList,
+    list_definitions_origin::ListDefinitionsOrigin, native_function_call::NativeFunctionCall,
+    object::RTObject, path::Path, push_pop::PushPopType, story_error::StoryError, tag::Tag,
+    threadsafe::Brc, value::Value, value_type::ValueType, variable_assigment::VariableAssignment,
+    variable_reference::VariableReference, void::Void,
+};
+
+pub fn write_story(
+    version: i32,
+    main_content_container: &Container,
+    list_definitions: &ListDefinitionsOrigin,
+) -> Result<Vec<u8>, StoryError> {
+    let mut w = BinaryWriter::new(STORY_MAGIC, STORY_FORMAT_VERSION);
+
+    w.write_int(version);
+    write_list_definitions(&mut w, list_definitions);
+    write_container(&mut w, main_content_container)?;
+
+    Ok(w.finish())
+}
+
+/// The components string of the path, built from its components. The string
+/// cached in paths loaded from a string drops the leading "." of relative
+/// paths.
+fn path_string(path: &Path) -> String {
+    let components: Vec<String> = (0..path.len())
+        .filter_map(|i| path.get_component(i))
+        .map(|c| c.to_string())
+        .collect();
+
+    if path.is_relative() {
+        format!(".{}", components.join("."))
+    } else {
+        components.join(".")
+    }
+}
+
+fn write_list_definitions(w: &mut BinaryWriter, list_definitions: &ListDefinitionsOrigin) {
+    let mut lists: Vec<_> = list_definitions.get_lists().collect();
+    lists.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+
+    w.write_len(lists.len());
+
+    for list in lists {
+        w.write_str(list.get_name());
+
+        let mut items: Vec<_> = list.get_item_name_to_values().iter().collect();
+        items.sort_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)));
+
+        w.write_len(items.len());
+
+        for (name, value) in items {
+            w.write_str(name);
+            w.write_int(*value);
+        }
+    }
+}
+
+pub(crate) fn write_rtobject(
+    w: &mut BinaryWriter,
+    o: &Brc<dyn RTObject>,
+) -> Result<(), StoryError> {
+    if let Some(c) = o.as_any().downcast_ref::<Container>() {
+        w.write_u8(OBJ_CONTAINER);
+        return write_container(w, c);
+    }
+
+    if let Some(v) = o.as_any().downcast_ref::<Value>() {
+        write_value(w, &v.value);
+        return Ok(());
+    }
+
+    // Paths are written as they were loaded. Resolving them is not needed and
+    // would change relative paths into global ones.
+    if let Some(divert) = o.as_any().downcast_ref::<Divert>() {
+        let mut flags = 0;
+
+        if divert.pushes_to_stack {
+            flags |= DIVERT_PUSHES_TO_STACK;
+        }
+
+        if divert.stack_push_type == PushPopType::Tunnel {
+            flags |= DIVERT_TUNNEL;
+        }
+
+        if divert.is_external {
+            flags |= DIVERT_EXTERNAL;
+        }
+
+        if divert.is_conditional {
+            flags |= DIVERT_CONDITIONAL;
+        }
+
+        let target_str = if divert.has_variable_target() {
+            flags |= DIVERT_VARIABLE_TARGET;
+            divert.variable_divert_name.clone().unwrap()
+        } else {
+            path_string(&divert.get_raw_target_path().unwrap())
+        };
+
+        w.write_u8(OBJ_DIVERT);
+        w.write_u8(flags);
+        w.write_str(&target_str);
+
+        if divert.is_external {
+            w.write_len(divert.external_args);
+        }
+
+        return Ok(());
+    }
+
+    if let Some(cp) = o.as_any().downcast_ref::<ChoicePoint>() {
+        w.write_u8(OBJ_CHOICE_POINT);
+        w.write_str(&path_string(&cp.get_raw_path_on_choice()));
+        w.write_int(cp.get_flags());
+        return Ok(());
+    }
+
+    if o.as_any().is::<Glue>() {
+        w.write_u8(OBJ_GLUE);
+        return Ok(());
+    }
+
+    if let Some(cc) = o.as_any().downcast_ref::<ControlCommand>() {
+        w.write_u8(OBJ_CONTROL_COMMAND);
+        w.write_str(&ControlCommand::get_name(cc.command_type));
+        return Ok(());
+    }
+
+    if let Some(f) = o.as_any().downcast_ref::<NativeFunctionCall>() {
+        w.write_u8(OBJ_NATIVE_FUNCTION_CALL);
+        w.write_str(&NativeFunctionCall::get_name(f.op));
+        return Ok(());
+    }
+
+    if let Some(var_ref) = o.as_any().downcast_ref::<VariableReference>() {
+        match &var_ref.path_for_count {
+            Some(read_count_path) => {
+                w.write_u8(OBJ_READ_COUNT);
+                w.write_str(&path_string(read_count_path));
+            }
+            None => {
+                w.write_u8(OBJ_VARIABLE_REFERENCE);
+                w.write_str(&var_ref.name);
+            }
+        }
+
+        return Ok(());
+    }
+
+    if let Some(var_ass) = o.as_any().downcast_ref::<VariableAssignment>() {
+        let mut flags = 0;
+
+        if var_ass.is_global {
+            flags |= VAR_ASS_GLOBAL;
+        }
+
+        if var_ass.is_new_declaration {
+            flags |= VAR_ASS_NEW_DECLARATION;
+        }
+
+        w.write_u8(OBJ_VARIABLE_ASSIGNMENT);
+        w.write_u8(flags);
+        w.write_str(&var_ass.variable_name);
+        return Ok(());
+    }
+
+    if o.as_any().is::<Void>() {
+        w.write_u8(OBJ_VOID);
+        return Ok(());
+    }
+
+    if let Some(tag) = o.as_any().downcast_ref::<Tag>() {
+        w.write_u8(OBJ_TAG);
+        w.write_str(tag.get_text());
+        return Ok(());
+    }
+
+    Err(StoryError::BadBinary(format!(
+        "Failed to write runtime object to binary: {}",
+        o
+    )))
+}
+
+fn write_container(w: &mut BinaryWriter, container: &Container) -> Result<(), StoryError> {
+    w.write_opt_str(container.name.as_deref());
+    w.write_int(container.get_count_flags());
+
+    w.write_len(container.content.len());
+
+    for o in &container.content {
+        write_rtobject(w, o)?;
+    }
+
+    let mut named_only_content: Vec<_> = container.get_named_only_content().into_iter().collect();
+    named_only_content.sort_by(|a, b| a.0.cmp(&b.0));
+
+    w.write_len(named_only_content.len());
+
+    for (name, c) in named_only_content {
+        w.write_str(&name);
+        write_container(w, &c)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn write_value(w: &mut BinaryWriter, value: &ValueType) {
+    match value {
+        ValueType::Bool(v) => {
+            w.write_u8(OBJ_BOOL);
+            w.write_bool(*v);
+        }
+        ValueType::Int(v) => {
+            w.write_u8(OBJ_INT);
+            w.write_int(*v);
+        }
+        ValueType::Float(v) => {
+            w.write_u8(OBJ_FLOAT);
+            w.write_float(*v);
+        }
+        ValueType::String(v) => {
+            w.write_u8(OBJ_STRING);
+            w.write_str(&v.string);
+        }
+        ValueType::List(v) => {
+            w.write_u8(OBJ_LIST);
+            write_ink_list(w, v);
+        }
+        ValueType::DivertTarget(v) => {
+            w.write_u8(OBJ_DIVERT_TARGET);
+            w.write_str(&path_string(v));
+        }
+        ValueType::VariablePointer(v) => {
+            w.write_u8(OBJ_VARIABLE_POINTER);
+            w.write_str(&v.variable_name);
+            w.write_int(v.context_index);
+        }
+    }
+}
+
+pub(crate) fn write_ink_list(w: &mut BinaryWriter, list: &InkList) {
+    let mut items: Vec<_> = list.items.iter().collect();
+    items.sort_by(|a, b| {
+        a.1.cmp(b.1)
+            .then_with(|| a.0.get_full_name().cmp(&b.0.get_full_name()))
+    });
+
+    w.write_len(items.len());
+
+    for (item, value) in items {
+        w.write_opt_str(item.get_origin_name().map(|s| s.as_str()));
+        w.write_str(item.get_item_name());
+        w.write_int(*value);
+    }
+
+    let origin_names = list.get_initial_origin_names();
+    w.write_len(origin_names.len());
+
+    for name in &origin_names {
+        w.write_str(name);
+    }
+}
+
+pub(crate) fn write_list_rt_objs(
+    w: &mut BinaryWriter,
+    objs: &[Brc<dyn RTObject>],
+) -> Result<(), StoryError> {
+    w.write_len(objs.len());
+
+    for o in objs {
+        write_rtobject(w, o)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn write_dictionary_values(w: &mut BinaryWriter, objs: &HashMap<String, Brc<Value>>) {
+    let mut entries: Vec<_> = objs.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    w.write_len(entries.len());
+
+    for (name, value) in entries {
+        w.write_str(name);
+        write_value(w, &value.value);
+    }
+}
+
+pub(crate) fn write_int_dictionary(w: &mut BinaryWriter, map: &HashMap<String, i32>) {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    w.write_len(entries.len());
+
+    for (key, value) in entries {
+        w.write_str(key);
+        w.write_int(*value);
+    }
+}
+
+pub(crate) fn write_choice(w: &mut BinaryWriter, choice: &Choice) {
+    w.write_str(&choice.text);
+    w.write_len(*choice.index.borrow());
+    w.write_str(&choice.source_path);
+    w.write_len(*choice.original_thread_index.borrow());
+    w.write_str(&path_string(&choice.target_path));
+
+    w.write_len(choice.tags.len());
+
+    for tag in &choice.tags {
+        w.write_str(tag);
+    }
+}
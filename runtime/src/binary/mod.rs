@@ -0,0 +1,45 @@
+pub mod binary_io;
+pub mod binary_read;
+pub mod binary_write;
+
+/// Magic number at the start of a binary story.
+pub(crate) const STORY_MAGIC: &[u8; 4] = b"INKB";
+/// Version of the binary story format. Increment when the layout changes.
+pub(crate) const STORY_FORMAT_VERSION: u32 = 1;
+
+/// Magic number at the start of a binary save state.
+pub(crate) const SAVE_MAGIC: &[u8; 4] = b"INKS";
+/// Version of the binary save state format. Increment when the layout
+/// changes.
+pub(crate) const SAVE_FORMAT_VERSION: u32 = 2;
+
+// Runtime object type tags.
+const OBJ_CONTAINER: u8 = 0;
+const OBJ_BOOL: u8 = 1;
+const OBJ_INT: u8 = 2;
+const OBJ_FLOAT: u8 = 3;
+const OBJ_STRING: u8 = 4;
+const OBJ_LIST: u8 = 5;
+const OBJ_DIVERT_TARGET: u8 = 6;
+const OBJ_VARIABLE_POINTER: u8 = 7;
+const OBJ_GLUE: u8 = 8;
+const OBJ_CONTROL_COMMAND: u8 = 9;
+const OBJ_NATIVE_FUNCTION_CALL: u8 = 10;
+const OBJ_DIVERT: u8 = 11;
+const OBJ_CHOICE_POINT: u8 = 12;
+const OBJ_VARIABLE_REFERENCE: u8 = 13;
+const OBJ_READ_COUNT: u8 = 14;
+const OBJ_VARIABLE_ASSIGNMENT: u8 = 15;
+const OBJ_VOID: u8 = 16;
+const OBJ_TAG: u8 = 17;
+
+// Divert flags.
+const DIVERT_PUSHES_TO_STACK: u8 = 1;
+const DIVERT_TUNNEL: u8 = 2;
+const DIVERT_EXTERNAL: u8 = 4;
+const DIVERT_CONDITIONAL: u8 = 8;
+const DIVERT_VARIABLE_TARGET: u8 = 16;
+
+// Variable assignment flags.
+const VAR_ASS_GLOBAL: u8 = 1;
+const VAR_ASS_NEW_DECLARATION: u8 = 2;
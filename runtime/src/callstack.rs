@@ -1,8 +1,12 @@
-use std::{collections::HashMap, rc::Rc};
+use std::collections::HashMap;
 
 use serde_json::{Map, json};
 
 use crate::{
+    binary::{
+        binary_io::{BinaryReader, BinaryWriter},
+        binary_read, binary_write,
+    },
     container::Container,
     json::{json_read, json_write},
     object::Object,
@@ -11,6 +15,7 @@ use crate::{
     push_pop::PushPopType,
     story::Story,
     story_error::StoryError,
+    threadsafe::Brc,
     value::Value,
 };
 
@@ -18,7 +23,7 @@ use crate::{
 pub struct Element {
     pub current_pointer: Pointer,
     pub in_expression_evaluation: bool,
-    pub temporary_variables: HashMap<String, Rc<Value>>,
+    pub temporary_variables: HashMap<String, Brc<Value>>,
     pub push_pop_type: PushPopType,
     pub evaluation_stack_height_when_pushed: usize,
     pub function_start_in_output_stream: i32,
@@ -58,7 +63,7 @@ impl Thread {
     }
 
     pub fn from_json(
-        main_content_container: &Rc<Container>,
+        main_content_container: &Brc<Container>,
         j_obj: &Map<String, serde_json::Value>,
     ) -> Result<Thread, StoryError> {
         let mut thread = Thread::new();
@@ -182,6 +187,77 @@ impl Thread {
 
         Ok(serde_json::Value::Object(thread))
     }
+
+    pub(crate) fn from_binary(
+        main_content_container: &Brc<Container>,
+        r: &mut BinaryReader,
+    ) -> Result<Thread, StoryError> {
+        let mut thread = Thread::new();
+
+        let num_elements = r.read_count()?;
+
+        for _ in 0..num_elements {
+            let mut pointer = pointer::NULL.clone();
+
+            if let Some(current_container_path_str) = r.read_opt_string()? {
+                let thread_pointer_result = main_content_container.content_at_path(
+                    &Path::new_with_components_string(Some(&current_container_path_str)),
+                    0,
+                    -1,
+                );
+
+                pointer.container = thread_pointer_result.container();
+                pointer.index = r.read_int()?;
+            }
+
+            let in_expression_evaluation = r.read_bool()?;
+            let push_pop_type = PushPopType::from_value(r.read_u8()? as usize)?;
+
+            let mut el = Element::new(push_pop_type, pointer, in_expression_evaluation);
+            el.temporary_variables = binary_read::read_dictionary_values(r)?;
+
+            thread.callstack.push(el);
+        }
+
+        thread.thread_index = r.read_len()?;
+
+        if let Some(prev_content_obj_path) = r.read_opt_string()? {
+            let prev_path = Path::new_with_components_string(Some(&prev_content_obj_path));
+            thread.previous_pointer = Story::pointer_at_path(main_content_container, &prev_path)?;
+        }
+
+        Ok(thread)
+    }
+
+    pub(crate) fn write_binary(&self, w: &mut BinaryWriter) {
+        w.write_len(self.callstack.len());
+
+        for el in self.callstack.iter() {
+            if el.current_pointer.is_null() {
+                w.write_opt_str(None);
+            } else {
+                w.write_opt_str(Some(
+                    &Object::get_path(el.current_pointer.container.as_ref().unwrap().as_ref())
+                        .get_components_string(),
+                ));
+                w.write_int(el.current_pointer.index);
+            }
+
+            w.write_bool(el.in_expression_evaluation);
+            w.write_u8(el.push_pop_type as u8);
+            binary_write::write_dictionary_values(w, &el.temporary_variables);
+        }
+
+        w.write_len(self.thread_index);
+
+        if self.previous_pointer.is_null() {
+            w.write_opt_str(None);
+        } else {
+            w.write_opt_str(Some(
+                &Object::get_path(self.previous_pointer.resolve().unwrap().as_ref()).to_string(),
+            ));
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -192,7 +268,7 @@ pub struct CallStack {
 }
 
 impl CallStack {
-    pub fn new(main_content_container: Rc<Container>) -> CallStack {
+    pub fn new(main_content_container: Brc<Container>) -> CallStack {
         let mut cs = CallStack {
             thread_counter: 0,
             start_of_root: Pointer::start_of(main_content_container),
@@ -320,7 +396,7 @@ impl CallStack {
     pub fn set_temporary_variable(
         &mut self,
         name: String,
-        value: Rc<Value>,
+        value: Brc<Value>,
         declare_new: bool,
         mut context_index: i32,
     ) -> Result<(), StoryError> {
@@ -370,7 +446,7 @@ impl CallStack {
         &self,
         name: &str,
         context_index: i32,
-    ) -> Option<Rc<Value>> {
+    ) -> Option<Brc<Value>> {
         let mut context_index = context_index;
         // contextIndex 0 means global, so index is actually 1-based
         if context_index == -1 {
@@ -422,7 +498,7 @@ impl CallStack {
 
     pub fn load_json(
         &mut self,
-        main_content_container: &Rc<Container>,
+        main_content_container: &Brc<Container>,
         j_obj: &Map<String, serde_json::Value>,
     ) -> Result<(), StoryError> {
         self.threads.clear();
@@ -441,6 +517,36 @@ impl CallStack {
         Ok(())
     }
 
+    pub(crate) fn write_binary(&self, w: &mut BinaryWriter) {
+        w.write_len(self.threads.len());
+
+        for thread in &self.threads {
+            thread.write_binary(w);
+        }
+
+        w.write_len(self.thread_counter);
+    }
+
+    pub(crate) fn load_binary(
+        &mut self,
+        main_content_container: &Brc<Container>,
+        r: &mut BinaryReader,
+    ) -> Result<(), StoryError> {
+        self.threads.clear();
+
+        let num_threads = r.read_count()?;
+
+        for _ in 0..num_threads {
+            let thread = Thread::from_binary(main_content_container, r)?;
+            self.threads.push(thread);
+        }
+
+        self.thread_counter = r.read_len()?;
+        self.start_of_root = Pointer::start_of(main_content_container.clone());
+
+        Ok(())
+    }
+
     pub fn get_callstack_trace(&self) -> String {
         let mut sb = String::new();
 
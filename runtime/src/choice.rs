@@ -1,19 +1,19 @@
 //! A generated [`Choice`] from the story.
 use core::fmt;
-use std::cell::RefCell;
 
 use crate::{
     callstack::Thread,
     object::{Object, RTObject},
     path::Path,
+    threadsafe::BrCell,
 };
 
 /// Represents a choice generated by a [`Story`](crate::story::Story).
 #[derive(Clone)]
 pub struct Choice {
     obj: Object,
-    thread_at_generation: RefCell<Option<Thread>>,
-    pub(crate) original_thread_index: RefCell<usize>,
+    thread_at_generation: BrCell<Option<Thread>>,
+    pub(crate) original_thread_index: BrCell<usize>,
     /// Get the path to the original choice point - where was this choice defined in the story?
     pub(crate) source_path: String,
     pub(crate) target_path: Path,
@@ -22,7 +22,7 @@ pub struct Choice {
     pub tags: Vec<String>,
     /// The original index into `currentChoices` list on the [`Story`](crate::story::Story) when
     /// this `Choice` was generated, for convenience.
-    pub index: RefCell<usize>,
+    pub index: BrCell<usize>,
     /// The main text to present to the player for this `Choice`.
     pub text: String,
 }
@@ -41,10 +41,10 @@ impl Choice {
             target_path,
             is_invisible_default,
             tags,
-            index: RefCell::new(0),
-            original_thread_index: RefCell::new(0),
+            index: BrCell::new(0),
+            original_thread_index: BrCell::new(0),
             text,
-            thread_at_generation: RefCell::new(Some(thread_at_generation)),
+            thread_at_generation: BrCell::new(Some(thread_at_generation)),
             source_path,
         }
     }
@@ -62,10 +62,10 @@ impl Choice {
             target_path: Path::new_with_components_string(Some(path_string_on_choice)),
             is_invisible_default: false,
             tags: choice_tags,
-            index: RefCell::new(index),
-            original_thread_index: RefCell::new(original_thread_index),
+            index: BrCell::new(index),
+            original_thread_index: BrCell::new(original_thread_index),
             text: text.to_string(),
-            thread_at_generation: RefCell::new(None),
+            thread_at_generation: BrCell::new(None),
             source_path,
         }
     }
@@ -0,0 +1,101 @@
+//! [`CompiledStory`] holds the parsed, immutable content of an Ink story so it
+//! can be shared by many [`Story`](crate::story::Story) instances.
+use std::io::Read;
+
+use crate::{
+    binary::{binary_read, binary_write},
+    container::Container,
+    json::{json_read, json_read_stream},
+    list_definitions_origin::ListDefinitionsOrigin,
+    story_error::StoryError,
+    threadsafe::Brc,
+};
+
+/// The parsed content of a `.ink.json` file: the container tree and the list
+/// definitions.
+///
+/// Parsing is done once in [`CompiledStory::new`]. Then, any number of
+/// stories can be created with
+/// [`Story::from_compiled`](crate::story::Story::from_compiled), each one
+/// only allocating its own state. Cloning a `CompiledStory` is cheap, the
+/// content is reference counted.
+///
+/// ```
+/// # use bladeink::{compiled_story::CompiledStory, story::Story, story_error::StoryError};
+/// # fn main() -> Result<(), StoryError> {
+/// # let json_string = r##"{"inkVersion":21, "root":["done",null],"listDefs":{}}"##;
+/// let compiled = CompiledStory::new(json_string)?;
+///
+/// let mut player1 = Story::from_compiled(&compiled)?;
+/// let mut player2 = Story::from_compiled(&compiled)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct CompiledStory {
+    pub(crate) version: i32,
+    pub(crate) main_content_container: Brc<Container>,
+    pub(crate) list_definitions: Brc<ListDefinitionsOrigin>,
+}
+
+impl CompiledStory {
+    /// Parses a JSON string that was compiled with `inklecate`.
+    pub fn new(json_string: &str) -> Result<Self, StoryError> {
+        let (version, main_content_container, list_definitions) =
+            if cfg!(feature = "stream-json-parser") {
+                json_read_stream::load_from_string(json_string)?
+            } else {
+                json_read::load_from_string(json_string)?
+            };
+
+        Ok(CompiledStory {
+            version,
+            main_content_container,
+            list_definitions,
+        })
+    }
+
+    /// Parses a compiled JSON story from a reader using the streamed parser.
+    ///
+    /// The JSON is tokenized incrementally, so the story can be loaded from
+    /// a file or flash memory without holding the full text in RAM.
+    pub fn from_reader(reader: impl Read) -> Result<Self, StoryError> {
+        let (version, main_content_container, list_definitions) =
+            json_read_stream::load_from_reader(reader)?;
+
+        Ok(CompiledStory {
+            version,
+            main_content_container,
+            list_definitions,
+        })
+    }
+
+    /// Loads a story from the binary format written by
+    /// [`to_binary`](CompiledStory::to_binary).
+    pub fn from_binary(data: &[u8]) -> Result<Self, StoryError> {
+        let (version, main_content_container, list_definitions) =
+            binary_read::load_from_bytes(data)?;
+
+        Ok(CompiledStory {
+            version,
+            main_content_container,
+            list_definitions,
+        })
+    }
+
+    /// Serializes the story content to a compact, versioned binary format
+    /// that loads faster than the JSON. The JSON remains the interchange
+    /// format, the binary is only meant to be loaded by this runtime.
+    pub fn to_binary(&self) -> Result<Vec<u8>, StoryError> {
+        binary_write::write_story(
+            self.version,
+            &self.main_content_container,
+            &self.list_definitions,
+        )
+    }
+
+    /// The ink version used to build the story.
+    pub fn get_ink_version(&self) -> i32 {
+        self.version
+    }
+}
@@ -0,0 +1,518 @@
+//! Automated [`Explorer`] that plays a story choosing its choices, to find
+//! runtime errors, dead ends, infinite loops and content that is never
+//! reached.
+use std::{fmt, time::Duration};
+
+use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+use crate::{
+    story::{
+        Story,
+        errors::{ErrorHandler, ErrorType},
+    },
+    story_error::{RuntimeError, StoryError},
+    threadsafe::{BrCell, Brc},
+};
+
+/// How the [`Explorer`] chooses the choices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExploreStrategy {
+    /// Tries every choice, depth first, until the budget is spent.
+    Systematic,
+    /// Plays the story from the start choosing random choices, with the
+    /// given seed, until the budget is spent.
+    Random(u64),
+}
+
+/// Limits and strategy of the exploration.
+#[derive(Debug, Clone)]
+pub struct ExplorerOptions {
+    pub strategy: ExploreStrategy,
+    /// Maximum number of choices made in a single playthrough.
+    pub max_depth: usize,
+    /// Maximum number of choices made in the whole exploration.
+    pub max_iterations: usize,
+    /// Lines produced without reaching a choice or an ending that are
+    /// reported as an infinite loop.
+    pub max_lines_per_turn: usize,
+    /// Time spent in a single line that is reported as an infinite loop.
+    pub line_time_limit: Duration,
+}
+
+impl Default for ExplorerOptions {
+    fn default() -> Self {
+        Self {
+            strategy: ExploreStrategy::Systematic,
+            max_depth: 50,
+            max_iterations: 10_000,
+            max_lines_per_turn: 1000,
+            line_time_limit: Duration::from_secs(1),
+        }
+    }
+}
+
+/// A problem found while exploring the story.
+#[derive(Debug, Clone)]
+pub struct Issue {
+    /// The description of the problem.
+    pub message: String,
+    /// Path of the ink content where the problem was found, if known.
+    pub path: Option<String>,
+    /// Indices of the choices chosen from the start of the exploration to
+    /// reproduce the problem, the first time it was found.
+    pub choices: Vec<usize>,
+    /// Number of times the problem was found.
+    pub occurrences: usize,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "({}): {}", path, self.message)?,
+            None => write!(f, "{}", self.message)?,
+        }
+
+        write!(
+            f,
+            " [choices: {:?}, found {} times]",
+            self.choices, self.occurrences
+        )
+    }
+}
+
+/// The results of an exploration.
+#[derive(Debug, Clone, Default)]
+pub struct ExplorationReport {
+    /// Number of choices made.
+    pub iterations: usize,
+    /// Number of playthroughs that reached an ending, an error or the
+    /// maximum depth.
+    pub playthroughs: usize,
+    /// Number of playthroughs that reached an `-> END`.
+    pub endings: usize,
+    /// `true` if every choice was tried within the maximum depth and no
+    /// playthrough stopped waiting for an async external function. Only
+    /// systematic explorations can be complete.
+    pub complete: bool,
+    pub errors: Vec<Issue>,
+    pub warnings: Vec<Issue>,
+    /// Places where the story stopped without choices and without reaching
+    /// an `-> END`, e.g. with a `-> DONE` when there are no choices left.
+    pub dead_ends: Vec<Issue>,
+    pub infinite_loops: Vec<Issue>,
+    /// Places where the story stopped waiting for the result of an async
+    /// external function. The explorer can't resolve the call, so the
+    /// content after it is not explored.
+    pub pending_external_calls: Vec<Issue>,
+    /// Knots and stitches that were never visited, sorted. Visits are only
+    /// counted if the story was compiled counting all the visits.
+    pub unreached: Vec<String>,
+    /// Number of visits to every knot and stitch, sorted by path.
+    pub visit_counts: Vec<(String, u32)>,
+}
+
+impl ExplorationReport {
+    /// `true` if errors, dead ends or infinite loops were found. Warnings,
+    /// pending external calls and unreached content are not considered
+    /// problems, since content can be unreached because of the limits of
+    /// the exploration.
+    pub fn has_problems(&self) -> bool {
+        !self.errors.is_empty() || !self.dead_ends.is_empty() || !self.infinite_loops.is_empty()
+    }
+}
+
+impl fmt::Display for ExplorationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} CHOICES / {} PLAYTHROUGHS / {} ENDINGS{}",
+            self.iterations,
+            self.playthroughs,
+            self.endings,
+            if self.complete { "" } else { " (incomplete)" }
+        )?;
+
+        let sections = [
+            ("ERRORS", &self.errors),
+            ("WARNINGS", &self.warnings),
+            ("DEAD ENDS", &self.dead_ends),
+            ("INFINITE LOOPS", &self.infinite_loops),
+            ("PENDING EXTERNAL CALLS", &self.pending_external_calls),
+        ];
+
+        for (title, issues) in sections {
+            if !issues.is_empty() {
+                writeln!(f, "{}:", title)?;
+                for issue in issues {
+                    writeln!(f, "    {}", issue)?;
+                }
+            }
+        }
+
+        if !self.unreached.is_empty() {
+            writeln!(f, "UNREACHED:")?;
+            for path in self.unreached.iter() {
+                writeln!(f, "    {}", path)?;
+            }
+        }
+
+        writeln!(f, "VISITS:")?;
+        for (path, count) in self.visit_counts.iter() {
+            writeln!(f, "    {}: {}", path, count)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Plays a story choosing its choices, systematically or randomly, and
+/// reports the problems found. It can be used as a test of the story, for
+/// example in continuous integration.
+///
+/// The exploration starts from the current state of the story, and branches
+/// by saving and loading the state. External functions must be bound, or
+/// fallbacks allowed, before exploring. Async external functions are never
+/// resolved, the playthroughs that call them stop there. Variable observers,
+/// event listeners, the error handler, the profiler and the history are not
+/// called while exploring, and the state of the story is restored at the
+/// end.
+#[derive(Debug, Default, Clone)]
+pub struct Explorer {
+    options: ExplorerOptions,
+}
+
+impl Explorer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: ExplorerOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn get_options(&self) -> &ExplorerOptions {
+        &self.options
+    }
+
+    /// Explores the story. Errors of the story are part of the report, an
+    /// `Err` is only returned if the state of the story can't be saved or
+    /// loaded.
+    pub fn explore(&self, story: &mut Story) -> Result<ExplorationReport, StoryError> {
+        story.if_async_we_cant("explore the story")?;
+
+        let start_state = story.save_state()?;
+
+        let collector = Brc::new(BrCell::new(IssueCollector::default()));
+        let hooks = story.suspend_hooks();
+        story.set_error_handler(collector.clone());
+
+        let mut exploration = Exploration::new(&self.options, story, collector);
+        let result = match self.options.strategy {
+            ExploreStrategy::Systematic => exploration.systematic(story, &start_state),
+            ExploreStrategy::Random(seed) => exploration.random(story, &start_state, seed),
+        };
+
+        let restored = story.load_state(&start_state);
+        story.resume_hooks(hooks);
+        result?;
+        restored?;
+
+        Ok(exploration.finish())
+    }
+}
+
+/// How a turn, the content run after a choice, finished.
+#[derive(PartialEq)]
+enum TurnEnd {
+    Choices(usize),
+    Ended,
+    Failed,
+    /// Stopped waiting for an async external function.
+    Waiting,
+}
+
+struct Exploration<'a> {
+    options: &'a ExplorerOptions,
+    collector: Brc<BrCell<IssueCollector>>,
+    report: ExplorationReport,
+    paths: Vec<String>,
+    visits: Vec<u32>,
+    choices: Vec<usize>,
+}
+
+impl<'a> Exploration<'a> {
+    fn new(
+        options: &'a ExplorerOptions,
+        story: &Story,
+        collector: Brc<BrCell<IssueCollector>>,
+    ) -> Self {
+        let mut paths = Vec::new();
+        for knot in story.knots() {
+            for stitch in story.stitches_of(&knot).unwrap_or_default() {
+                paths.push(format!("{knot}.{stitch}"));
+            }
+            paths.push(knot);
+        }
+        paths.sort();
+
+        Self {
+            options,
+            collector,
+            report: ExplorationReport::default(),
+            visits: vec![0; paths.len()],
+            paths,
+            choices: Vec::new(),
+        }
+    }
+
+    fn systematic(&mut self, story: &mut Story, start_state: &str) -> Result<(), StoryError> {
+        story.load_state(start_state)?;
+
+        self.report.complete = true;
+
+        match self.run_turn(story)? {
+            TurnEnd::Choices(num_choices) => {
+                let state = story.save_state()?;
+                self.explore_choices(story, &state, num_choices)
+            }
+            _ => {
+                self.report.playthroughs += 1;
+                Ok(())
+            }
+        }
+    }
+
+    /// Tries every choice of the state, depth first.
+    fn explore_choices(
+        &mut self,
+        story: &mut Story,
+        state: &str,
+        num_choices: usize,
+    ) -> Result<(), StoryError> {
+        for i in 0..num_choices {
+            if self.report.iterations >= self.options.max_iterations {
+                self.report.complete = false;
+                return Ok(());
+            }
+
+            story.load_state(state)?;
+            story.choose_choice_index(i)?;
+            self.report.iterations += 1;
+            self.choices.push(i);
+
+            match self.run_turn(story)? {
+                TurnEnd::Choices(num_choices) if self.choices.len() < self.options.max_depth => {
+                    let state = story.save_state()?;
+                    self.explore_choices(story, &state, num_choices)?;
+                }
+                TurnEnd::Choices(_) => {
+                    self.report.complete = false;
+                    self.report.playthroughs += 1;
+                }
+                _ => self.report.playthroughs += 1,
+            }
+
+            self.choices.pop();
+        }
+
+        Ok(())
+    }
+
+    fn random(
+        &mut self,
+        story: &mut Story,
+        start_state: &str,
+        seed: u64,
+    ) -> Result<(), StoryError> {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        loop {
+            story.load_state(start_state)?;
+            self.choices.clear();
+
+            let mut turn_end = self.run_turn(story)?;
+
+            while let TurnEnd::Choices(num_choices) = turn_end {
+                if self.choices.len() >= self.options.max_depth
+                    || self.report.iterations >= self.options.max_iterations
+                {
+                    break;
+                }
+
+                let i = rng.random_range(0..num_choices);
+                story.choose_choice_index(i)?;
+                self.report.iterations += 1;
+                self.choices.push(i);
+
+                turn_end = self.run_turn(story)?;
+            }
+
+            self.report.playthroughs += 1;
+
+            // Without choices every playthrough is the same
+            if self.choices.is_empty() || self.report.iterations >= self.options.max_iterations {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Continues the story until the next choices or the end, collecting the
+    /// problems found.
+    fn run_turn(&mut self, story: &mut Story) -> Result<TurnEnd, StoryError> {
+        let before = self.visit_counts(story);
+        let mut lines = 0;
+        let mut failed = false;
+
+        while story.can_continue() {
+            if lines == self.options.max_lines_per_turn {
+                let message = format!(
+                    "{} lines without reaching a choice or an ending",
+                    self.options.max_lines_per_turn
+                );
+                let path = story.get_current_path();
+                add_issue(
+                    &mut self.report.infinite_loops,
+                    message,
+                    path,
+                    &self.choices,
+                );
+                failed = true;
+                break;
+            }
+
+            let time_limit = self.options.line_time_limit.as_secs_f32() * 1000.0;
+            if let Err(e) = story.continue_async(time_limit) {
+                let path = story.get_current_path();
+                add_issue(&mut self.report.errors, e.to_string(), path, &self.choices);
+                failed = true;
+                break;
+            }
+
+            if story.is_continue_in_progress() {
+                let message = format!(
+                    "a line took more than {} ms",
+                    self.options.line_time_limit.as_millis()
+                );
+                let path = story.get_current_path();
+                story.abort_continue();
+                add_issue(
+                    &mut self.report.infinite_loops,
+                    message,
+                    path,
+                    &self.choices,
+                );
+                failed = true;
+                break;
+            }
+
+            lines += 1;
+        }
+
+        let collected = std::mem::take(&mut self.collector.borrow_mut().issues);
+        for error in collected {
+            let issues = match error.error_type {
+                ErrorType::Error => {
+                    failed = true;
+                    &mut self.report.errors
+                }
+                ErrorType::Warning => &mut self.report.warnings,
+            };
+
+            add_issue(issues, error.message, error.path, &self.choices);
+        }
+
+        let after = self.visit_counts(story);
+        for (visits, (b, a)) in self.visits.iter_mut().zip(before.iter().zip(after.iter())) {
+            *visits += a.saturating_sub(*b) as u32;
+        }
+
+        if failed {
+            return Ok(TurnEnd::Failed);
+        }
+
+        if let Some(call) = story.get_pending_external_call() {
+            let message = format!(
+                "waiting for the async external function '{}'",
+                call.func_name
+            );
+            let path = story.get_current_path();
+            add_issue(
+                &mut self.report.pending_external_calls,
+                message,
+                path,
+                &self.choices,
+            );
+            self.report.complete = false;
+            return Ok(TurnEnd::Waiting);
+        }
+
+        let num_choices = story.get_current_choices().len();
+        if num_choices > 0 {
+            return Ok(TurnEnd::Choices(num_choices));
+        }
+
+        // `-> END` clears the previous pointer, `-> DONE` keeps it
+        match story.get_state().previous_path_string() {
+            Some(path) => {
+                let message = "the story stopped without choices".to_owned();
+                add_issue(
+                    &mut self.report.dead_ends,
+                    message,
+                    Some(path),
+                    &self.choices,
+                );
+            }
+            None => self.report.endings += 1,
+        }
+
+        Ok(TurnEnd::Ended)
+    }
+
+    fn visit_counts(&self, story: &Story) -> Vec<i32> {
+        self.paths
+            .iter()
+            .map(|path| story.get_visit_count_at_path_string(path).unwrap_or(0))
+            .collect()
+    }
+
+    fn finish(mut self) -> ExplorationReport {
+        for (path, visits) in self.paths.into_iter().zip(self.visits) {
+            if visits == 0 {
+                self.report.unreached.push(path.clone());
+            }
+
+            self.report.visit_counts.push((path, visits));
+        }
+
+        self.report
+    }
+}
+
+fn add_issue(issues: &mut Vec<Issue>, message: String, path: Option<String>, choices: &[usize]) {
+    match issues
+        .iter_mut()
+        .find(|i| i.message == message && i.path == path)
+    {
+        Some(issue) => issue.occurrences += 1,
+        None => issues.push(Issue {
+            message,
+            path,
+            choices: choices.to_vec(),
+            occurrences: 1,
+        }),
+    }
+}
+
+#[derive(Default)]
+struct IssueCollector {
+    issues: Vec<RuntimeError>,
+}
+
+impl ErrorHandler for IssueCollector {
+    // Not called, `runtime_error` is overridden
+    fn error(&mut self, _message: &str, _error_type: ErrorType) {}
+
+    fn runtime_error(&mut self, error: &RuntimeError) {
+        self.issues.push(error.clone());
+    }
+}
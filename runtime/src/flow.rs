@@ -1,29 +1,32 @@
-use std::{cell::RefCell, rc::Rc};
-
 use serde_json::Map;
 
 use crate::{
+    binary::{
+        binary_io::{BinaryReader, BinaryWriter},
+        binary_read, binary_write,
+    },
     callstack::{CallStack, Thread},
     choice::Choice,
     container::Container,
     json::{json_read, json_write},
     object::RTObject,
     story_error::StoryError,
+    threadsafe::{BrCell, Brc},
 };
 
 #[derive(Clone)]
 pub(crate) struct Flow {
     pub name: String,
-    pub callstack: Rc<RefCell<CallStack>>,
-    pub output_stream: Vec<Rc<dyn RTObject>>,
-    pub current_choices: Vec<Rc<Choice>>,
+    pub callstack: Brc<BrCell<CallStack>>,
+    pub output_stream: Vec<Brc<dyn RTObject>>,
+    pub current_choices: Vec<Brc<Choice>>,
 }
 
 impl Flow {
-    pub fn new(name: &str, main_content_container: Rc<Container>) -> Flow {
+    pub fn new(name: &str, main_content_container: Brc<Container>) -> Flow {
         Flow {
             name: name.to_string(),
-            callstack: Rc::new(RefCell::new(CallStack::new(main_content_container))),
+            callstack: Brc::new(BrCell::new(CallStack::new(main_content_container))),
             output_stream: Vec::new(),
             current_choices: Vec::new(),
         }
@@ -31,12 +34,12 @@ impl Flow {
 
     pub fn from_json(
         name: &str,
-        main_content_container: Rc<Container>,
+        main_content_container: Brc<Container>,
         j_obj: &Map<String, serde_json::Value>,
     ) -> Result<Flow, StoryError> {
         let mut flow = Self {
             name: name.to_string(),
-            callstack: Rc::new(RefCell::new(CallStack::new(main_content_container.clone()))),
+            callstack: Brc::new(BrCell::new(CallStack::new(main_content_container.clone()))),
             output_stream: json_read::jarray_to_runtime_obj_list(
                 j_obj
                     .get("outputStream")
@@ -55,7 +58,7 @@ impl Flow {
             )?
             .iter()
             .map(|o| o.clone().into_any().downcast::<Choice>().unwrap())
-            .collect::<Vec<Rc<Choice>>>(),
+            .collect::<Vec<Brc<Choice>>>(),
         };
 
         flow.callstack.borrow_mut().load_json(
@@ -131,7 +134,7 @@ impl Flow {
     pub fn load_flow_choice_threads(
         &mut self,
         j_choice_threads: Option<&serde_json::Value>,
-        main_content_container: Rc<Container>,
+        main_content_container: Brc<Container>,
     ) -> Result<(), StoryError> {
         for choice in self.current_choices.iter_mut() {
             self.callstack
@@ -156,4 +159,68 @@ impl Flow {
 
         Ok(())
     }
+
+    pub(crate) fn from_binary(
+        name: &str,
+        main_content_container: Brc<Container>,
+        r: &mut BinaryReader,
+    ) -> Result<Flow, StoryError> {
+        let mut flow = Flow::new(name, main_content_container.clone());
+
+        flow.callstack
+            .borrow_mut()
+            .load_binary(&main_content_container, r)?;
+        flow.output_stream = binary_read::read_list_rt_objs(r)?;
+
+        let num_choices = r.read_count()?;
+
+        for _ in 0..num_choices {
+            let choice = binary_read::read_choice(r)?;
+
+            let thread = if r.read_bool()? {
+                Thread::from_binary(&main_content_container, r)?
+            } else {
+                flow.callstack
+                    .borrow()
+                    .get_thread_with_index(*choice.original_thread_index.borrow())
+                    .ok_or(StoryError::BadBinary("loading choice threads".to_owned()))?
+                    .clone()
+            };
+
+            choice.set_thread_at_generation(thread);
+            flow.current_choices.push(Brc::new(choice));
+        }
+
+        Ok(flow)
+    }
+
+    pub(crate) fn write_binary(&self, w: &mut BinaryWriter) -> Result<(), StoryError> {
+        self.callstack.borrow().write_binary(w);
+        binary_write::write_list_rt_objs(w, &self.output_stream)?;
+
+        // Each choice is followed by its thread, unless the thread is still
+        // in the callstack
+        w.write_len(self.current_choices.len());
+
+        for c in self.current_choices.iter() {
+            let thread = c.get_thread_at_generation().unwrap();
+            c.original_thread_index.replace(thread.thread_index);
+
+            binary_write::write_choice(w, c);
+
+            let in_callstack = self
+                .callstack
+                .borrow()
+                .get_thread_with_index(thread.thread_index)
+                .is_some();
+
+            w.write_bool(!in_callstack);
+
+            if !in_callstack {
+                thread.write_binary(w);
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -1,36 +1,49 @@
+//! [`InkList`], the value of an Ink list variable.
 use core::fmt;
-use std::{cell::RefCell, collections::HashMap};
+use std::collections::HashMap;
 
 use crate::{
     ink_list_item::InkListItem, list_definition::ListDefinition,
-    list_definitions_origin::ListDefinitionsOrigin, story_error::StoryError, value_type::ValueType,
+    list_definitions_origin::ListDefinitionsOrigin, story_error::StoryError, threadsafe::BrCell,
+    value_type::ValueType,
 };
 
+/// A set of list items, each one with its value.
+///
+/// Build one from code with
+/// [`Story::list_from_items`](crate::story::Story::list_from_items), get
+/// every item of its lists with
+/// [`Story::list_all`](crate::story::Story::list_all), and assign it with [`Story::set_variable`](crate::story::Story::set_variable).
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "SerdeInkList", into = "SerdeInkList")
+)]
 pub struct InkList {
-    pub items: HashMap<InkListItem, i32>,
-    pub origins: RefCell<Vec<ListDefinition>>,
+    pub(crate) items: HashMap<InkListItem, i32>,
+    pub(crate) origins: BrCell<Vec<ListDefinition>>,
     // we need an origin when we only have the definition (the list has not elemetns)
-    initial_origin_names: RefCell<Vec<String>>,
+    initial_origin_names: BrCell<Vec<String>>,
 }
 
 impl InkList {
     pub fn new() -> Self {
         Self {
             items: HashMap::new(),
-            origins: RefCell::new(Vec::with_capacity(0)),
-            initial_origin_names: RefCell::new(Vec::with_capacity(0)),
+            origins: BrCell::new(Vec::with_capacity(0)),
+            initial_origin_names: BrCell::new(Vec::with_capacity(0)),
         }
     }
 
-    pub fn from_single_element(single_element: (InkListItem, i32)) -> Self {
+    pub(crate) fn from_single_element(single_element: (InkListItem, i32)) -> Self {
         let mut l = Self::new();
         l.items.insert(single_element.0, single_element.1);
 
         l
     }
 
-    pub fn from_single_origin(
+    pub(crate) fn from_single_origin(
         single_origin: String,
         list_definitions: &ListDefinitionsOrigin,
     ) -> Result<Self, StoryError> {
@@ -52,6 +65,60 @@ impl InkList {
         Ok(l)
     }
 
+    /// Creates a list with items of the list definition `list_name`. Items
+    /// can be given by name (`red`) or by full name (`colors.red`).
+    ///
+    /// Returns [`StoryError::BadArgument`] if the list or any of the items
+    /// are not defined.
+    pub(crate) fn from_items(
+        list_name: &str,
+        item_names: &[&str],
+        list_definitions: &ListDefinitionsOrigin,
+    ) -> Result<Self, StoryError> {
+        let def = list_definitions
+            .get_list_definition(list_name)
+            .ok_or_else(|| {
+                StoryError::BadArgument(format!("List '{}' is not defined in the story", list_name))
+            })?;
+
+        let mut l = Self::new();
+        l.set_initial_origin_names(vec![list_name.to_string()]);
+        l.origins.borrow_mut().push(def.clone());
+
+        for item_name in item_names {
+            let item = InkListItem::from_full_name(item_name);
+
+            if item
+                .get_origin_name()
+                .is_some_and(|origin| origin != list_name)
+            {
+                return Err(StoryError::BadArgument(format!(
+                    "Item '{}' doesn't belong to list '{}'",
+                    item_name, list_name
+                )));
+            }
+
+            let item = InkListItem::new(
+                Some(list_name.to_string()),
+                item.get_item_name().to_string(),
+            );
+
+            match def.get_value_for_item(&item) {
+                Some(value) => {
+                    l.items.insert(item, *value);
+                }
+                None => {
+                    return Err(StoryError::BadArgument(format!(
+                        "Item '{}' is not defined in list '{}'",
+                        item_name, list_name
+                    )));
+                }
+            }
+        }
+
+        Ok(l)
+    }
+
     fn from_other_list(other_list: &InkList) -> Self {
         let mut ink_list = InkList::new();
 
@@ -78,6 +145,63 @@ impl InkList {
         ordered
     }
 
+    /// Iterates the items with their values, ordered by value.
+    pub fn iter(&self) -> impl Iterator<Item = (&InkListItem, i32)> {
+        self.get_ordered_items()
+            .into_iter()
+            .map(|(item, value)| (item, *value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn contains_item(&self, item: &InkListItem) -> bool {
+        self.items.contains_key(item)
+    }
+
+    /// Checks for an item by name (`red`) or by full name (`colors.red`).
+    pub fn contains_item_named(&self, item_name: &str) -> bool {
+        let item = InkListItem::from_full_name(item_name);
+
+        self.items.keys().any(|k| {
+            k.get_item_name() == item.get_item_name()
+                && (item.get_origin_name().is_none()
+                    || k.get_origin_name() == item.get_origin_name())
+        })
+    }
+
+    /// Every item of the lists that this list's items belong to, like
+    /// `LIST_ALL` in ink.
+    pub(crate) fn all(&self, list_definitions: &ListDefinitionsOrigin) -> InkList {
+        let mut origin_names = self.get_origin_names();
+        origin_names.sort();
+        origin_names.dedup();
+
+        let mut list = InkList::new();
+
+        for name in &origin_names {
+            if let Some(def) = list_definitions.get_list_definition(name) {
+                for (item_name, value) in def.get_item_name_to_values() {
+                    list.items.insert(
+                        InkListItem::new(Some(name.clone()), item_name.clone()),
+                        *value,
+                    );
+                }
+
+                list.origins.borrow_mut().push(def.clone());
+            }
+        }
+
+        list.set_initial_origin_names(origin_names);
+
+        list
+    }
+
     pub fn get_max_item(&self) -> Option<(&InkListItem, i32)> {
         let mut max: Option<(&InkListItem, i32)> = None;
 
@@ -102,10 +226,14 @@ impl InkList {
         min
     }
 
-    pub fn set_initial_origin_names(&self, initial_origin_names: Vec<String>) {
+    pub(crate) fn set_initial_origin_names(&self, initial_origin_names: Vec<String>) {
         self.initial_origin_names.replace(initial_origin_names);
     }
 
+    pub(crate) fn get_initial_origin_names(&self) -> Vec<String> {
+        self.initial_origin_names.borrow().clone()
+    }
+
     pub fn get_origin_names(&self) -> Vec<String> {
         if !self.items.is_empty() {
             let mut names = Vec::new();
@@ -328,6 +456,40 @@ impl InkList {
     }
 }
 
+/// Like in the JSON, only the item values and the names of the origins are
+/// serialized. Origin definitions are resolved again by the story.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerdeInkList {
+    items: Vec<(InkListItem, i32)>,
+    origins: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl From<InkList> for SerdeInkList {
+    fn from(list: InkList) -> Self {
+        let origins = list.get_initial_origin_names();
+        let mut items: Vec<(InkListItem, i32)> = list.items.into_iter().collect();
+        items.sort_by(|a, b| {
+            a.1.cmp(&b.1)
+                .then_with(|| a.0.get_full_name().cmp(&b.0.get_full_name()))
+        });
+
+        SerdeInkList { items, origins }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<SerdeInkList> for InkList {
+    fn from(serde_list: SerdeInkList) -> Self {
+        let mut list = InkList::new();
+        list.set_initial_origin_names(serde_list.origins);
+        list.items.extend(serde_list.items);
+
+        list
+    }
+}
+
 impl Default for InkList {
     fn default() -> Self {
         Self::new()
@@ -1,4 +1,8 @@
+//! [`InkListItem`], an item of an Ink list.
+/// An item of an Ink list: the name of the list it belongs to (its origin)
+/// and the item name.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InkListItem {
     origin_name: Option<String>,
     item_name: String,
@@ -2,7 +2,7 @@
 //! This is useful for large JSON files that don't fit in memory hence the JSON is not loaded all at once as Serde does.
 //! This parser has been used to load 'The Intercept' example story in an ESP32-s2 microcontroller with an external RAM of 2MB. With the Serde based parser, it is impossible, it does not have enogh memory to load the story.
 
-use std::{collections::HashMap, rc::Rc};
+use std::{collections::HashMap, io::Read};
 
 use crate::{
     choice_point::ChoicePoint,
@@ -21,6 +21,7 @@ use crate::{
     story::{INK_VERSION_CURRENT, INK_VERSION_MINIMUM_COMPATIBLE},
     story_error::StoryError,
     tag::Tag,
+    threadsafe::Brc,
     value::Value,
     variable_assigment::VariableAssignment,
     variable_reference::VariableReference,
@@ -31,15 +32,25 @@ use super::json_tokenizer::{JsonTokenizer, JsonValue};
 
 pub fn load_from_string(
     s: &str,
-) -> Result<(i32, Rc<Container>, Rc<ListDefinitionsOrigin>), StoryError> {
+) -> Result<(i32, Brc<Container>, Brc<ListDefinitionsOrigin>), StoryError> {
     let mut tok = JsonTokenizer::new_from_str(s);
 
     parse(&mut tok)
 }
 
+/// Loads the story incrementally from a reader, the JSON text is never held
+/// in memory as a whole.
+pub fn load_from_reader(
+    reader: impl Read,
+) -> Result<(i32, Brc<Container>, Brc<ListDefinitionsOrigin>), StoryError> {
+    let mut tok = JsonTokenizer::new_from_reader(reader);
+
+    parse(&mut tok)
+}
+
 fn parse(
     tok: &mut JsonTokenizer,
-) -> Result<(i32, Rc<Container>, Rc<ListDefinitionsOrigin>), StoryError> {
+) -> Result<(i32, Brc<Container>, Brc<ListDefinitionsOrigin>), StoryError> {
     tok.expect('{')?;
 
     let version_key = tok.read_obj_key()?;
@@ -50,7 +61,7 @@ fn parse(
         ));
     }
 
-    let version: i32 = tok.read_number().unwrap().as_integer().unwrap();
+    let version: i32 = as_integer(&tok.read_value()?)?;
 
     if version > INK_VERSION_CURRENT {
         return Err(StoryError::BadJson(
@@ -103,20 +114,32 @@ fn parse(
         ));
     }
 
-    let list_defs = Rc::new(jtoken_to_list_definitions(tok)?);
+    let list_defs = Brc::new(jtoken_to_list_definitions(tok)?);
 
     tok.expect('}')?;
 
     Ok((version, main_content_container, list_defs))
 }
 
+fn as_str(value: &JsonValue) -> Result<&str, StoryError> {
+    value
+        .as_str()
+        .ok_or_else(|| StoryError::BadJson(format!("Expected a string, found {:?}", value)))
+}
+
+fn as_integer(value: &JsonValue) -> Result<i32, StoryError> {
+    value
+        .as_integer()
+        .ok_or_else(|| StoryError::BadJson(format!("Expected a number, found {:?}", value)))
+}
+
 enum ArrayElement {
-    RTObject(Rc<dyn RTObject>),
-    LastElement(i32, Option<String>, HashMap<String, Rc<Container>>),
+    RTObject(Brc<dyn RTObject>),
+    LastElement(i32, Option<String>, HashMap<String, Brc<Container>>),
     NullElement,
 }
 
-type RuntimeObjectList = Vec<Rc<dyn RTObject>>;
+type RuntimeObjectList = Vec<Brc<dyn RTObject>>;
 type RuntimeObjectListResult = Result<(RuntimeObjectList, Option<ArrayElement>), StoryError>;
 
 fn jtoken_to_runtime_object(
@@ -126,36 +149,38 @@ fn jtoken_to_runtime_object(
 ) -> Result<ArrayElement, StoryError> {
     match value {
         JsonValue::Null => Ok(ArrayElement::NullElement),
-        JsonValue::Boolean(value) => Ok(ArrayElement::RTObject(Rc::new(Value::new::<bool>(value)))),
+        JsonValue::Boolean(value) => {
+            Ok(ArrayElement::RTObject(Brc::new(Value::new::<bool>(value))))
+        }
         JsonValue::Number(value) => {
             if value.is_integer() {
-                let val: i32 = value.as_integer().unwrap();
-                Ok(ArrayElement::RTObject(Rc::new(Value::new::<i32>(val))))
+                let val: i32 = value.as_integer().unwrap(); // unwrap: always a number
+                Ok(ArrayElement::RTObject(Brc::new(Value::new::<i32>(val))))
             } else {
-                let val: f32 = value.as_float().unwrap();
-                Ok(ArrayElement::RTObject(Rc::new(Value::new::<f32>(val))))
+                let val: f32 = value.as_float().unwrap(); // unwrap: always a number
+                Ok(ArrayElement::RTObject(Brc::new(Value::new::<f32>(val))))
             }
         }
         JsonValue::String(value) => {
             let str = value.as_str();
 
             // String value
-            let first_char = str.chars().next().unwrap();
-            if first_char == '^' {
-                return Ok(ArrayElement::RTObject(Rc::new(Value::new::<&str>(
+            let first_char = str.chars().next();
+            if first_char == Some('^') {
+                return Ok(ArrayElement::RTObject(Brc::new(Value::new::<&str>(
                     &str[1..],
                 ))));
-            } else if first_char == '\n' && str.len() == 1 {
-                return Ok(ArrayElement::RTObject(Rc::new(Value::new::<&str>("\n"))));
+            } else if first_char == Some('\n') && str.len() == 1 {
+                return Ok(ArrayElement::RTObject(Brc::new(Value::new::<&str>("\n"))));
             }
 
             // Glue
             if "<>".eq(str) {
-                return Ok(ArrayElement::RTObject(Rc::new(Glue::new())));
+                return Ok(ArrayElement::RTObject(Brc::new(Glue::new())));
             }
 
             if let Some(control_command) = ControlCommand::new_from_name(str) {
-                return Ok(ArrayElement::RTObject(Rc::new(control_command)));
+                return Ok(ArrayElement::RTObject(Brc::new(control_command)));
             }
 
             // Native functions
@@ -167,12 +192,12 @@ fn jtoken_to_runtime_object(
                 call_str = "^";
             }
             if let Some(native_function_call) = NativeFunctionCall::new_from_name(call_str) {
-                return Ok(ArrayElement::RTObject(Rc::new(native_function_call)));
+                return Ok(ArrayElement::RTObject(Brc::new(native_function_call)));
             }
 
             // Void
             if "void".eq(str) {
-                return Ok(ArrayElement::RTObject(Rc::new(Void::new())));
+                return Ok(ArrayElement::RTObject(Brc::new(Void::new())));
             }
 
             Err(StoryError::BadJson(format!(
@@ -188,23 +213,23 @@ fn jtoken_to_runtime_object(
             // Divert target value to path
             if prop == "^->" {
                 tok.expect('}')?;
-                return Ok(ArrayElement::RTObject(Rc::new(Value::new::<Path>(
+                return Ok(ArrayElement::RTObject(Brc::new(Value::new::<Path>(
                     Path::new_with_components_string(prop_value.as_str()),
                 ))));
             }
 
             // // VariablePointerValue
             if prop == "^var" {
-                let variable_name = prop_value.as_str().unwrap();
+                let variable_name = as_str(&prop_value)?;
                 let mut contex_index = -1;
 
                 if tok.peek()? == ',' {
                     tok.expect(',')?;
                     tok.expect_obj_key("ci")?;
-                    contex_index = tok.read_number().unwrap().as_integer().unwrap();
+                    contex_index = as_integer(&tok.read_value()?)?;
                 }
 
-                let var_ptr = Rc::new(Value::new_variable_pointer(variable_name, contex_index));
+                let var_ptr = Brc::new(Value::new_variable_pointer(variable_name, contex_index));
                 tok.expect('}')?;
                 return Ok(ArrayElement::RTObject(var_ptr));
             }
@@ -233,7 +258,7 @@ fn jtoken_to_runtime_object(
             }
 
             if is_divert {
-                let target = prop_value.as_str().unwrap().to_string();
+                let target = as_str(&prop_value)?.to_string();
 
                 let mut var_divert_name: Option<String> = None;
                 let mut target_path: Option<String> = None;
@@ -252,7 +277,7 @@ fn jtoken_to_runtime_object(
                     } else if prop == "c" {
                         conditional = true;
                     } else if prop == "exArgs" {
-                        external_args = prop_value.as_integer().unwrap() as usize;
+                        external_args = as_integer(&prop_value)? as usize;
                     }
                 }
 
@@ -261,7 +286,7 @@ fn jtoken_to_runtime_object(
                 }
 
                 tok.expect('}')?;
-                return Ok(ArrayElement::RTObject(Rc::new(Divert::new(
+                return Ok(ArrayElement::RTObject(Brc::new(Divert::new(
                     pushes_to_stack,
                     div_push_type,
                     external,
@@ -275,16 +300,16 @@ fn jtoken_to_runtime_object(
             // Choice
             if prop == "*" {
                 let mut flags = 0;
-                let path_string_on_choice = prop_value.as_str().unwrap();
+                let path_string_on_choice = as_str(&prop_value)?;
 
                 if tok.peek()? == ',' {
                     tok.expect(',')?;
                     tok.expect_obj_key("flg")?;
-                    flags = tok.read_number().unwrap().as_integer().unwrap();
+                    flags = as_integer(&tok.read_value()?)?;
                 }
 
                 tok.expect('}')?;
-                return Ok(ArrayElement::RTObject(Rc::new(ChoicePoint::new(
+                return Ok(ArrayElement::RTObject(Brc::new(ChoicePoint::new(
                     flags,
                     path_string_on_choice,
                 ))));
@@ -293,15 +318,15 @@ fn jtoken_to_runtime_object(
             // Variable reference
             if prop == "VAR?" {
                 tok.expect('}')?;
-                return Ok(ArrayElement::RTObject(Rc::new(VariableReference::new(
-                    prop_value.as_str().unwrap(),
+                return Ok(ArrayElement::RTObject(Brc::new(VariableReference::new(
+                    as_str(&prop_value)?,
                 ))));
             }
 
             if prop == "CNT?" {
                 tok.expect('}')?;
-                return Ok(ArrayElement::RTObject(Rc::new(
-                    VariableReference::from_path_for_count(prop_value.as_str().unwrap()),
+                return Ok(ArrayElement::RTObject(Brc::new(
+                    VariableReference::from_path_for_count(as_str(&prop_value)?),
                 )));
             }
 
@@ -318,7 +343,7 @@ fn jtoken_to_runtime_object(
             }
 
             if is_var_ass {
-                let var_name = prop_value.as_str().unwrap();
+                let var_name = as_str(&prop_value)?;
                 let mut is_new_decl = true;
 
                 if tok.peek()? == ',' {
@@ -328,7 +353,7 @@ fn jtoken_to_runtime_object(
                     is_new_decl = false;
                 }
 
-                let var_ass = Rc::new(VariableAssignment::new(
+                let var_ass = Brc::new(VariableAssignment::new(
                     var_name,
                     is_new_decl,
                     is_global_var,
@@ -340,9 +365,9 @@ fn jtoken_to_runtime_object(
             // // Legacy Tag
             if prop == "#" {
                 tok.expect('}')?;
-                return Ok(ArrayElement::RTObject(Rc::new(Tag::new(
-                    prop_value.as_str().unwrap(),
-                ))));
+                return Ok(ArrayElement::RTObject(Brc::new(Tag::new(as_str(
+                    &prop_value,
+                )?))));
             }
 
             // List value
@@ -378,30 +403,31 @@ fn jtoken_to_runtime_object(
                 }
 
                 tok.expect('}')?;
-                return Ok(ArrayElement::RTObject(Rc::new(Value::new::<InkList>(
+                return Ok(ArrayElement::RTObject(Brc::new(Value::new::<InkList>(
                     raw_list,
                 ))));
             }
 
             // Used when serialising save state only
             if prop == "originalChoicePath" {
-                todo!("originalChoicePath");
-                // return jobject_to_choice(obj); // TODO
+                return Err(StoryError::BadJson(
+                    "Choices are only expected in save states".to_owned(),
+                ));
             }
 
             // Last Element
             let mut flags = 0;
             let mut name: Option<String> = None;
-            let mut named_only_content: HashMap<String, Rc<Container>> = HashMap::new();
+            let mut named_only_content: HashMap<String, Brc<Container>> = HashMap::new();
 
             let mut p = prop.clone();
             let mut pv = prop_value;
 
             loop {
                 if p == "#f" {
-                    flags = pv.as_integer().unwrap();
+                    flags = as_integer(&pv)?;
                 } else if p == "#n" {
-                    name = Some(pv.as_str().unwrap().to_string());
+                    name = Some(as_str(&pv)?.to_string());
                 } else {
                     let named_content_item = jtoken_to_runtime_object(tok, pv, Some(p.clone()))?;
 
@@ -417,7 +443,9 @@ fn jtoken_to_runtime_object(
                     let named_sub_container = named_content_item
                         .into_any()
                         .downcast::<Container>()
-                        .unwrap();
+                        .map_err(|_| {
+                            StoryError::BadJson("Named content is not a container".to_owned())
+                        })?;
 
                     named_only_content.insert(p, named_sub_container);
                 }
@@ -447,7 +475,7 @@ fn parse_list(tok: &mut JsonTokenizer) -> Result<HashMap<String, i32>, StoryErro
 
     while tok.peek()? != '}' {
         let key = tok.read_obj_key()?;
-        let value = tok.read_number().unwrap().as_integer().unwrap();
+        let value = as_integer(&tok.read_value()?)?;
         list_content.insert(key, value);
 
         if tok.peek()? != '}' {
@@ -463,7 +491,7 @@ fn parse_list(tok: &mut JsonTokenizer) -> Result<HashMap<String, i32>, StoryErro
 fn jarray_to_container(
     tok: &mut JsonTokenizer,
     name: Option<String>,
-) -> Result<Rc<dyn RTObject>, StoryError> {
+) -> Result<Brc<dyn RTObject>, StoryError> {
     let (content, named) = jarray_to_runtime_obj_list(tok)?;
 
     // Final object in the array is always a combination of
@@ -474,7 +502,7 @@ fn jarray_to_container(
     // let terminating_obj = jarray[jarray.len() - 1].as_object();
     let mut name: Option<String> = name;
     let mut flags = 0;
-    let mut named_only_content: HashMap<String, Rc<Container>> = HashMap::new();
+    let mut named_only_content: HashMap<String, Brc<Container>> = HashMap::new();
 
     if let Some(ArrayElement::LastElement(f, n, named_content)) = named {
         flags = f;
@@ -559,6 +587,27 @@ mod tests {
         let _ = load_from_string(s).unwrap();
     }
 
+    #[test]
+    fn simple_load_from_reader() {
+        let s = r##"{"inkVersion":21,"root":[["^Line.","\n",["done",{"#n":"g-0"}],null],"done",null],"listDefs":{}}"##;
+        let (version, container, _) = load_from_reader(s.as_bytes()).unwrap();
+        assert_eq!(21, version);
+        assert_eq!(2, container.content.len());
+    }
+
+    #[test]
+    fn truncated_load_from_reader() {
+        let s = r##"{"inkVersion":21,"root":[["^Line.","\n","ev",{"VAR?":"x"},"out","/ev",{"*":"0.c-0","flg":20},["done",{"#f":5,"#n":"g-0"}],null],"done",{"global decl":["ev",{"list":{"a.A":1},"origins":["a"]},{"VAR=":"x"},"/ev","end",null]}],"listDefs":{"a":{"A":1}}}"##;
+        assert!(load_from_reader(s.as_bytes()).is_ok());
+
+        for len in 0..s.len() {
+            assert!(matches!(
+                load_from_reader(&s.as_bytes()[..len]),
+                Err(StoryError::BadJson(_))
+            ));
+        }
+    }
+
     #[test]
     fn load_list() {
         let s = r##"
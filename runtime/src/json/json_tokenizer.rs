@@ -1,5 +1,5 @@
 //! Tokenizer for the streamed JSON parser.
-use std::io::{self, Read};
+use std::io::{self, BufReader, Read};
 
 #[derive(Debug)]
 pub(super) enum Number {
@@ -56,8 +56,28 @@ impl JsonValue {
     }
 }
 
+/// Size of the buffer used when reading from a `Read` source. Kept small, the
+/// streamed parser is used in low memory devices.
+const READER_BUFFER_SIZE: usize = 512;
+
+/// Where the tokenizer reads the JSON from. Strings are read directly from
+/// the slice, avoiding a dynamic call per byte.
+enum JsonSource<'a> {
+    Slice(&'a [u8]),
+    Reader(Box<dyn Read + 'a>),
+}
+
+impl JsonSource<'_> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        match self {
+            JsonSource::Slice(slice) => slice.read_exact(buf),
+            JsonSource::Reader(reader) => reader.read_exact(buf),
+        }
+    }
+}
+
 pub(super) struct JsonTokenizer<'a> {
-    json: &'a [u8],
+    json: JsonSource<'a>,
     lookahead: Option<char>,
     skip_whitespaces: bool,
 }
@@ -65,7 +85,18 @@ pub(super) struct JsonTokenizer<'a> {
 impl<'a> JsonTokenizer<'a> {
     pub(super) fn new_from_str(s: &'a str) -> JsonTokenizer<'a> {
         JsonTokenizer {
-            json: s.as_bytes(),
+            json: JsonSource::Slice(s.as_bytes()),
+            lookahead: None,
+            skip_whitespaces: true,
+        }
+    }
+
+    pub(super) fn new_from_reader<R: Read + 'a>(reader: R) -> JsonTokenizer<'a> {
+        JsonTokenizer {
+            json: JsonSource::Reader(Box::new(BufReader::with_capacity(
+                READER_BUFFER_SIZE,
+                reader,
+            ))),
             lookahead: None,
             skip_whitespaces: true,
         }
@@ -183,7 +214,9 @@ impl<'a> JsonTokenizer<'a> {
         self.expect('"')?;
         self.skip_whitespaces = false;
 
-        while let Ok(c) = self.read() {
+        loop {
+            let c = self.read()?;
+
             if escape {
                 // Handle escape sequences
                 match c {
@@ -207,14 +240,7 @@ impl<'a> JsonTokenizer<'a> {
             }
         }
 
-        if !escape {
-            Ok(result)
-        } else {
-            Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Unterminated string",
-            ))
-        }
+        Ok(result)
     }
 
     fn read_until_separator(&mut self) -> io::Result<String> {
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, rc::Rc};
+use std::collections::HashMap;
 
 use serde_json::{Map, json};
 
@@ -16,6 +16,7 @@ use crate::{
     push_pop::PushPopType,
     story_error::StoryError,
     tag::Tag,
+    threadsafe::Brc,
     value::Value,
     value_type::{StringValue, VariablePointerValue},
     variable_assigment::VariableAssignment,
@@ -24,7 +25,7 @@ use crate::{
 };
 
 pub fn write_dictionary_values(
-    objs: &HashMap<String, Rc<Value>>,
+    objs: &HashMap<String, Brc<Value>>,
 ) -> Result<serde_json::Value, StoryError> {
     let mut jobjs: Map<String, serde_json::Value> = Map::new();
 
@@ -35,7 +36,7 @@ pub fn write_dictionary_values(
     Ok(serde_json::Value::Object(jobjs))
 }
 
-pub fn write_rtobject(o: Rc<dyn RTObject>) -> Result<serde_json::Value, StoryError> {
+pub fn write_rtobject(o: Brc<dyn RTObject>) -> Result<serde_json::Value, StoryError> {
     if let Some(c) = o.as_any().downcast_ref::<Container>() {
         return write_rt_container(c, false);
     }
@@ -277,7 +278,7 @@ pub fn write_choice(choice: &Choice) -> serde_json::Value {
     jobj.insert("originalChoicePath".to_owned(), json!(choice.source_path));
     jobj.insert(
         "originalThreadIndex".to_owned(),
-        json!(choice.original_thread_index),
+        json!(*choice.original_thread_index.borrow()),
     );
     jobj.insert(
         "targetPath".to_owned(),
@@ -299,7 +300,7 @@ fn write_choice_tags(choice: &Choice) -> serde_json::Value {
 }
 
 pub(crate) fn write_list_rt_objs(
-    objs: &[Rc<dyn RTObject>],
+    objs: &[Brc<dyn RTObject>],
 ) -> Result<serde_json::Value, StoryError> {
     let mut c_array: Vec<serde_json::Value> = Vec::new();
 
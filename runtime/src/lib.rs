@@ -44,17 +44,28 @@
 //! features will be added to this documentation in the future, but meanwhile,
 //! all the examples can be found in the `runtime/tests` folder in the source code
 //! of this crate.
+//!
+//! By default a [`Story`](story::Story) can only be used from the thread that
+//! created it. Enable the `threadsafe` feature to make it `Send + Sync`, see
+//! the [`threadsafe`] module.
+//!
+//! The `serde` feature implements `Serialize` and `Deserialize` for
+//! [`ValueType`](value_type::ValueType) and adds a structured save state,
+//! see the `story_state_snapshot` module.
 
+mod binary;
 mod callstack;
 pub mod choice;
 mod choice_point;
+pub mod compiled_story;
 mod container;
 mod control_command;
 mod divert;
+pub mod explorer;
 mod flow;
 mod glue;
-mod ink_list;
-mod ink_list_item;
+pub mod ink_list;
+pub mod ink_list_item;
 mod json;
 mod list_definition;
 mod list_definitions_origin;
@@ -62,13 +73,18 @@ mod native_function_call;
 mod object;
 mod path;
 mod pointer;
-mod push_pop;
+pub mod profiler;
+pub mod push_pop;
+pub mod save_state_view;
 mod search_result;
 mod state_patch;
 pub mod story;
 pub mod story_error;
 mod story_state;
+#[cfg(feature = "serde")]
+pub mod story_state_snapshot;
 mod tag;
+pub mod threadsafe;
 mod value;
 pub mod value_type;
 mod variable_assigment;
@@ -1,7 +1,9 @@
+//! [`ListDefinition`], a `LIST` declared in the story.
 use std::collections::HashMap;
 
 use crate::ink_list_item::InkListItem;
 
+/// A list declared in the story with `LIST`, with the values of its items.
 #[derive(Clone)]
 pub struct ListDefinition {
     name: String,
@@ -10,7 +12,7 @@ pub struct ListDefinition {
 }
 
 impl ListDefinition {
-    pub fn new(name: String, items: HashMap<String, i32>) -> Self {
+    pub(crate) fn new(name: String, items: HashMap<String, i32>) -> Self {
         Self {
             name,
             items: None,
@@ -18,7 +20,7 @@ impl ListDefinition {
         }
     }
 
-    pub fn get_items(&mut self) -> &HashMap<InkListItem, i32> {
+    pub(crate) fn get_items(&mut self) -> &HashMap<InkListItem, i32> {
         if self.items.is_none() {
             let mut new_items = HashMap::new();
             for (item_name, value) in &self.item_name_to_values {
@@ -31,6 +33,10 @@ impl ListDefinition {
         self.items.as_ref().unwrap()
     }
 
+    pub fn get_item_name_to_values(&self) -> &HashMap<String, i32> {
+        &self.item_name_to_values
+    }
+
     pub fn get_name(&self) -> &str {
         &self.name
     }
@@ -39,15 +45,6 @@ impl ListDefinition {
         self.item_name_to_values.get(item.get_item_name())
     }
 
-    pub fn contains_item(&self, item: &InkListItem) -> bool {
-        item.get_origin_name() == Some(&self.name)
-            && self.item_name_to_values.contains_key(item.get_item_name())
-    }
-
-    pub fn contains_item_with_name(&self, item_name: &str) -> bool {
-        self.item_name_to_values.contains_key(item_name)
-    }
-
     pub fn get_item_with_value(&self, val: i32) -> Option<InkListItem> {
         for (item_name, value) in &self.item_name_to_values {
             if *value == val {
@@ -1,15 +1,17 @@
-use std::{collections::HashMap, rc::Rc};
+//! [`ListDefinitionsOrigin`], all the lists declared in a story.
+use std::collections::HashMap;
 
-use crate::{ink_list::InkList, list_definition::ListDefinition, value::Value};
+use crate::{ink_list::InkList, list_definition::ListDefinition, threadsafe::Brc, value::Value};
 
+/// All the list definitions of a story.
 #[derive(Clone)]
-pub struct ListDefinitionsOrigin {
+pub(crate) struct ListDefinitionsOrigin {
     lists: HashMap<String, ListDefinition>,
-    all_unambiguous_list_value_cache: HashMap<String, Rc<Value>>,
+    all_unambiguous_list_value_cache: HashMap<String, Brc<Value>>,
 }
 
 impl ListDefinitionsOrigin {
-    pub fn new(lists: &mut Vec<ListDefinition>) -> Self {
+    pub(crate) fn new(lists: &mut Vec<ListDefinition>) -> Self {
         let mut list_definitions_origin = ListDefinitionsOrigin {
             lists: HashMap::new(),
             all_unambiguous_list_value_cache: HashMap::new(),
@@ -24,7 +26,7 @@ impl ListDefinitionsOrigin {
                 let mut l = InkList::new();
                 l.items.insert(key.clone(), *val);
 
-                let list_value = Rc::new(Value::new::<InkList>(l));
+                let list_value = Brc::new(Value::new::<InkList>(l));
 
                 list_definitions_origin
                     .all_unambiguous_list_value_cache
@@ -38,11 +40,15 @@ impl ListDefinitionsOrigin {
         list_definitions_origin
     }
 
+    pub fn get_lists(&self) -> impl Iterator<Item = &ListDefinition> {
+        self.lists.values()
+    }
+
     pub fn get_list_definition(&self, name: &str) -> Option<&ListDefinition> {
         self.lists.get(name)
     }
 
-    pub fn find_single_item_list_with_name(&self, name: &str) -> Option<&Rc<Value>> {
+    pub(crate) fn find_single_item_list_with_name(&self, name: &str) -> Option<&Brc<Value>> {
         if name.trim().is_empty() {
             return None;
         }
@@ -1,9 +1,10 @@
-use std::{fmt, rc::Rc};
+use std::fmt;
 
 use crate::{
     ink_list::InkList,
     object::{Object, RTObject},
     story_error::StoryError,
+    threadsafe::Brc,
     value::Value,
     value_type::ValueType,
     void::Void,
@@ -206,8 +207,8 @@ impl NativeFunctionCall {
 
     pub(crate) fn call(
         &self,
-        params: Vec<Rc<dyn RTObject>>,
-    ) -> Result<Rc<dyn RTObject>, StoryError> {
+        params: Vec<Brc<dyn RTObject>>,
+    ) -> Result<Brc<dyn RTObject>, StoryError> {
         if self.get_number_of_parameters() != params.len() {
             return Err(StoryError::InvalidStoryState(
                 "Unexpected number of parameters".to_owned(),
@@ -242,8 +243,8 @@ impl NativeFunctionCall {
 
     fn call_binary_list_operation(
         &self,
-        params: &[Rc<dyn RTObject>],
-    ) -> Result<Rc<dyn RTObject>, StoryError> {
+        params: &[Brc<dyn RTObject>],
+    ) -> Result<Brc<dyn RTObject>, StoryError> {
         // List-Int addition/subtraction returns a List (e.g., "alpha" + 1 = "beta")
         if (self.op == Op::Add || self.op == Op::Subtract)
             && Value::get_value::<&InkList>(params[0].as_ref()).is_some()
@@ -268,7 +269,7 @@ impl NativeFunctionCall {
                 }
             };
 
-            return Ok(Rc::new(Value::new::<bool>(result)));
+            return Ok(Brc::new(Value::new::<bool>(result)));
         }
 
         // Normal (list • list) operation
@@ -288,7 +289,7 @@ impl NativeFunctionCall {
         )))
     }
 
-    fn call_list_increment_operation(&self, list_int_params: &[Rc<dyn RTObject>]) -> Rc<Value> {
+    fn call_list_increment_operation(&self, list_int_params: &[Brc<dyn RTObject>]) -> Brc<Value> {
         let list_val = Value::get_value::<&InkList>(list_int_params[0].as_ref()).unwrap();
         let int_val = Value::get_value::<i32>(list_int_params[1].as_ref()).unwrap();
 
@@ -318,10 +319,10 @@ impl NativeFunctionCall {
             }
         }
 
-        Rc::new(Value::new::<InkList>(result_raw_list))
+        Brc::new(Value::new::<InkList>(result_raw_list))
     }
 
-    fn call_type(&self, coerced_params: Vec<Rc<Value>>) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn call_type(&self, coerced_params: Vec<Brc<Value>>) -> Result<Brc<dyn RTObject>, StoryError> {
         match self.op {
             Op::Add => self.add_op(&coerced_params),
             Op::Subtract => self.subtract_op(&coerced_params),
@@ -359,10 +360,10 @@ impl NativeFunctionCall {
 
     fn coerce_values_to_single_type(
         &self,
-        params: Vec<Rc<dyn RTObject>>,
-    ) -> Result<Vec<Rc<Value>>, StoryError> {
+        params: Vec<Brc<dyn RTObject>>,
+    ) -> Result<Vec<Brc<Value>>, StoryError> {
         let mut dest_type = 1; // Int
-        let mut result: Vec<Rc<Value>> = Vec::new();
+        let mut result: Vec<Brc<Value>> = Vec::new();
 
         for obj in params.iter() {
             // Find out what the output type is
@@ -379,7 +380,7 @@ impl NativeFunctionCall {
         for obj in params.iter() {
             if let Some(v) = obj.as_ref().as_any().downcast_ref::<Value>() {
                 match v.cast(dest_type)? {
-                    Some(casted_value) => result.push(Rc::new(casted_value)),
+                    Some(casted_value) => result.push(Brc::new(casted_value)),
                     None => {
                         if let Ok(obj) = obj.clone().into_any().downcast::<Value>() {
                             result.push(obj);
@@ -397,28 +398,30 @@ impl NativeFunctionCall {
         Ok(result)
     }
 
-    fn and_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn and_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::Bool(op1) => match params[1].value {
-                ValueType::Bool(op2) => Ok(Rc::new(Value::new::<bool>(*op1 && op2))),
+                ValueType::Bool(op2) => Ok(Brc::new(Value::new::<bool>(*op1 && op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<bool>(*op1 != 0 && op2 != 0))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<bool>(*op1 != 0 && op2 != 0))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<bool>(*op1 != 0.0 && op2 != 0.0))),
+                ValueType::Float(op2) => {
+                    Ok(Brc::new(Value::new::<bool>(*op1 != 0.0 && op2 != 0.0)))
+                }
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::List(op1) => match &params[1].value {
-                ValueType::List(op2) => Ok(Rc::new(Value::new::<bool>(
+                ValueType::List(op2) => Ok(Brc::new(Value::new::<bool>(
                     !op1.items.is_empty() && !op2.items.is_empty(),
                 ))),
                 _ => Err(StoryError::InvalidStoryState(
@@ -431,22 +434,22 @@ impl NativeFunctionCall {
         }
     }
 
-    fn greater_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn greater_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<bool>(*op1 > op2))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<bool>(*op1 > op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<bool>(*op1 > op2))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<bool>(*op1 > op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::List(op1) => match &params[1].value {
-                ValueType::List(op2) => Ok(Rc::new(Value::new::<bool>(op1.greater_than(op2)))),
+                ValueType::List(op2) => Ok(Brc::new(Value::new::<bool>(op1.greater_than(op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -457,22 +460,22 @@ impl NativeFunctionCall {
         }
     }
 
-    fn less_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn less_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<bool>(*op1 < op2))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<bool>(*op1 < op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<bool>(*op1 < op2))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<bool>(*op1 < op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::List(op1) => match &params[1].value {
-                ValueType::List(op2) => Ok(Rc::new(Value::new::<bool>(op1.less_than(op2)))),
+                ValueType::List(op2) => Ok(Brc::new(Value::new::<bool>(op1.less_than(op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -485,25 +488,25 @@ impl NativeFunctionCall {
 
     fn greater_than_or_equals_op(
         &self,
-        params: &[Rc<Value>],
-    ) -> Result<Rc<dyn RTObject>, StoryError> {
+        params: &[Brc<Value>],
+    ) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<bool>(*op1 >= op2))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<bool>(*op1 >= op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<bool>(*op1 >= op2))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<bool>(*op1 >= op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::List(op1) => match &params[1].value {
-                ValueType::List(op2) => {
-                    Ok(Rc::new(Value::new::<bool>(op1.greater_than_or_equals(op2))))
-                }
+                ValueType::List(op2) => Ok(Brc::new(Value::new::<bool>(
+                    op1.greater_than_or_equals(op2),
+                ))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -514,23 +517,26 @@ impl NativeFunctionCall {
         }
     }
 
-    fn less_than_or_equals_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn less_than_or_equals_op(
+        &self,
+        params: &[Brc<Value>],
+    ) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<bool>(*op1 <= op2))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<bool>(*op1 <= op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<bool>(*op1 <= op2))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<bool>(*op1 <= op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::List(op1) => match &params[1].value {
                 ValueType::List(op2) => {
-                    Ok(Rc::new(Value::new::<bool>(op1.less_than_or_equals(op2))))
+                    Ok(Brc::new(Value::new::<bool>(op1.less_than_or_equals(op2))))
                 }
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
@@ -542,22 +548,22 @@ impl NativeFunctionCall {
         }
     }
 
-    fn subtract_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn subtract_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<i32>(*op1 - op2))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<i32>(*op1 - op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<f32>(*op1 - op2))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<f32>(*op1 - op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::List(op1) => match &params[1].value {
-                ValueType::List(op2) => Ok(Rc::new(Value::new::<InkList>(op1.without(op2)))),
+                ValueType::List(op2) => Ok(Brc::new(Value::new::<InkList>(op1.without(op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -568,16 +574,16 @@ impl NativeFunctionCall {
         }
     }
 
-    fn add_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn add_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<i32>(op1 + op2))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<i32>(op1 + op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<f32>(op1 + op2))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<f32>(op1 + op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -587,14 +593,14 @@ impl NativeFunctionCall {
                     let mut sb = String::new();
                     sb.push_str(&op1.string);
                     sb.push_str(&op2.string);
-                    Ok(Rc::new(Value::new::<&str>(&sb)))
+                    Ok(Brc::new(Value::new::<&str>(&sb)))
                 }
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::List(op1) => match &params[1].value {
-                ValueType::List(op2) => Ok(Rc::new(Value::new::<InkList>(op1.union(op2)))),
+                ValueType::List(op2) => Ok(Brc::new(Value::new::<InkList>(op1.union(op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -605,16 +611,16 @@ impl NativeFunctionCall {
         }
     }
 
-    fn divide_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn divide_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match params[0].value {
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<i32>(op1 / op2))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<i32>(op1 / op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<f32>(op1 / op2))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<f32>(op1 / op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -625,18 +631,18 @@ impl NativeFunctionCall {
         }
     }
 
-    fn pow_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn pow_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match params[0].value {
             ValueType::Int(op1) => match params[1].value {
                 ValueType::Int(op2) => {
-                    Ok(Rc::new(Value::new::<f32>((op1 as f32).powf(op2 as f32))))
+                    Ok(Brc::new(Value::new::<f32>((op1 as f32).powf(op2 as f32))))
                 }
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<f32>(op1.powf(op2)))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<f32>(op1.powf(op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -647,16 +653,16 @@ impl NativeFunctionCall {
         }
     }
 
-    fn multiply_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn multiply_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match params[0].value {
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<i32>(op1 * op2))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<i32>(op1 * op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<f32>(op1 * op2))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<f32>(op1 * op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -667,28 +673,30 @@ impl NativeFunctionCall {
         }
     }
 
-    fn or_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn or_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::Bool(op1) => match params[1].value {
-                ValueType::Bool(op2) => Ok(Rc::new(Value::new::<bool>(*op1 || op2))),
+                ValueType::Bool(op2) => Ok(Brc::new(Value::new::<bool>(*op1 || op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<bool>(*op1 != 0 || op2 != 0))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<bool>(*op1 != 0 || op2 != 0))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<bool>(*op1 != 0.0 || op2 != 0.0))),
+                ValueType::Float(op2) => {
+                    Ok(Brc::new(Value::new::<bool>(*op1 != 0.0 || op2 != 0.0)))
+                }
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::List(op1) => match &params[1].value {
-                ValueType::List(op2) => Ok(Rc::new(Value::new::<bool>(
+                ValueType::List(op2) => Ok(Brc::new(Value::new::<bool>(
                     !op1.items.is_empty() || !op2.items.is_empty(),
                 ))),
                 _ => Err(StoryError::InvalidStoryState(
@@ -701,11 +709,11 @@ impl NativeFunctionCall {
         }
     }
 
-    fn not_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn not_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
-            ValueType::Int(op1) => Ok(Rc::new(Value::new::<bool>(*op1 == 0))),
-            ValueType::Float(op1) => Ok(Rc::new(Value::new::<bool>(*op1 == 0.0))),
-            ValueType::List(op1) => Ok(Rc::new(Value::new::<i32>(match op1.items.is_empty() {
+            ValueType::Int(op1) => Ok(Brc::new(Value::new::<bool>(*op1 == 0))),
+            ValueType::Float(op1) => Ok(Brc::new(Value::new::<bool>(*op1 == 0.0))),
+            ValueType::List(op1) => Ok(Brc::new(Value::new::<i32>(match op1.items.is_empty() {
                 true => 1,
                 false => 0,
             }))),
@@ -715,16 +723,16 @@ impl NativeFunctionCall {
         }
     }
 
-    fn min_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn min_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<i32>(i32::min(*op1, op2)))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<i32>(i32::min(*op1, op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<f32>(f32::min(*op1, op2)))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<f32>(f32::min(*op1, op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -735,16 +743,16 @@ impl NativeFunctionCall {
         }
     }
 
-    fn max_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn max_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<i32>(i32::max(*op1, op2)))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<i32>(i32::max(*op1, op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<f32>(f32::max(*op1, op2)))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<f32>(f32::max(*op1, op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -755,42 +763,42 @@ impl NativeFunctionCall {
         }
     }
 
-    fn equal_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn equal_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::Bool(op1) => match params[1].value {
-                ValueType::Bool(op2) => Ok(Rc::new(Value::new::<bool>(*op1 == op2))),
+                ValueType::Bool(op2) => Ok(Brc::new(Value::new::<bool>(*op1 == op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<bool>(*op1 == op2))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<bool>(*op1 == op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<bool>(*op1 == op2))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<bool>(*op1 == op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::String(op1) => match &params[1].value {
                 ValueType::String(op2) => {
-                    Ok(Rc::new(Value::new::<bool>(op1.string.eq(&op2.string))))
+                    Ok(Brc::new(Value::new::<bool>(op1.string.eq(&op2.string))))
                 }
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::List(op1) => match &params[1].value {
-                ValueType::List(op2) => Ok(Rc::new(Value::new::<bool>(op1.eq(op2)))),
+                ValueType::List(op2) => Ok(Brc::new(Value::new::<bool>(op1.eq(op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::DivertTarget(op1) => match &params[1].value {
-                ValueType::DivertTarget(op2) => Ok(Rc::new(Value::new::<bool>(op1.eq(op2)))),
+                ValueType::DivertTarget(op2) => Ok(Brc::new(Value::new::<bool>(op1.eq(op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -801,42 +809,42 @@ impl NativeFunctionCall {
         }
     }
 
-    fn not_equals_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn not_equals_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::Bool(op1) => match params[1].value {
-                ValueType::Bool(op2) => Ok(Rc::new(Value::new::<bool>(*op1 != op2))),
+                ValueType::Bool(op2) => Ok(Brc::new(Value::new::<bool>(*op1 != op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<bool>(*op1 != op2))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<bool>(*op1 != op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<bool>(*op1 != op2))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<bool>(*op1 != op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::String(op1) => match &params[1].value {
                 ValueType::String(op2) => {
-                    Ok(Rc::new(Value::new::<bool>(!op1.string.eq(&op2.string))))
+                    Ok(Brc::new(Value::new::<bool>(!op1.string.eq(&op2.string))))
                 }
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::List(op1) => match &params[1].value {
-                ValueType::List(op2) => Ok(Rc::new(Value::new::<bool>(!op1.eq(op2)))),
+                ValueType::List(op2) => Ok(Brc::new(Value::new::<bool>(!op1.eq(op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::DivertTarget(op1) => match &params[1].value {
-                ValueType::DivertTarget(op2) => Ok(Rc::new(Value::new::<bool>(!op1.eq(op2)))),
+                ValueType::DivertTarget(op2) => Ok(Brc::new(Value::new::<bool>(!op1.eq(op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -847,16 +855,16 @@ impl NativeFunctionCall {
         }
     }
 
-    fn mod_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn mod_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match params[0].value {
             ValueType::Int(op1) => match params[1].value {
-                ValueType::Int(op2) => Ok(Rc::new(Value::new::<i32>(op1 % op2))),
+                ValueType::Int(op2) => Ok(Brc::new(Value::new::<i32>(op1 % op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
             },
             ValueType::Float(op1) => match params[1].value {
-                ValueType::Float(op2) => Ok(Rc::new(Value::new::<f32>(op1 % op2))),
+                ValueType::Float(op2) => Ok(Brc::new(Value::new::<f32>(op1 % op2))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -867,10 +875,10 @@ impl NativeFunctionCall {
         }
     }
 
-    fn intersect_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn intersect_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::List(op1) => match &params[1].value {
-                ValueType::List(op2) => Ok(Rc::new(Value::new::<InkList>(op1.intersect(op2)))),
+                ValueType::List(op2) => Ok(Brc::new(Value::new::<InkList>(op1.intersect(op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -881,10 +889,10 @@ impl NativeFunctionCall {
         }
     }
 
-    fn has(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn has(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::String(op1) => match &params[1].value {
-                ValueType::String(op2) => Ok(Rc::new(Value::new::<bool>(
+                ValueType::String(op2) => Ok(Brc::new(Value::new::<bool>(
                     op1.string.contains(&op2.string),
                 ))),
                 _ => Err(StoryError::InvalidStoryState(
@@ -892,7 +900,7 @@ impl NativeFunctionCall {
                 )),
             },
             ValueType::List(op1) => match &params[1].value {
-                ValueType::List(op2) => Ok(Rc::new(Value::new::<bool>(op1.contains(op2)))),
+                ValueType::List(op2) => Ok(Brc::new(Value::new::<bool>(op1.contains(op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -903,10 +911,10 @@ impl NativeFunctionCall {
         }
     }
 
-    fn hasnt(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn hasnt(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::String(op1) => match &params[1].value {
-                ValueType::String(op2) => Ok(Rc::new(Value::new::<bool>(
+                ValueType::String(op2) => Ok(Brc::new(Value::new::<bool>(
                     !op1.string.contains(&op2.string),
                 ))),
                 _ => Err(StoryError::InvalidStoryState(
@@ -914,7 +922,7 @@ impl NativeFunctionCall {
                 )),
             },
             ValueType::List(op1) => match &params[1].value {
-                ValueType::List(op2) => Ok(Rc::new(Value::new::<bool>(!op1.contains(op2)))),
+                ValueType::List(op2) => Ok(Brc::new(Value::new::<bool>(!op1.contains(op2)))),
                 _ => Err(StoryError::InvalidStoryState(
                     "Operation not available for type.".to_owned(),
                 )),
@@ -925,11 +933,11 @@ impl NativeFunctionCall {
         }
     }
 
-    fn value_of_list_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn value_of_list_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
             ValueType::List(op1) => match op1.get_max_item() {
-                Some(i) => Ok(Rc::new(Value::new::<i32>(i.1))),
-                None => Ok(Rc::new(Value::new::<i32>(0))),
+                Some(i) => Ok(Brc::new(Value::new::<i32>(i.1))),
+                None => Ok(Brc::new(Value::new::<i32>(0))),
             },
             _ => Err(StoryError::InvalidStoryState(
                 "Operation not available for type.".to_owned(),
@@ -937,95 +945,95 @@ impl NativeFunctionCall {
         }
     }
 
-    fn all_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn all_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
-            ValueType::List(op1) => Ok(Rc::new(Value::new::<InkList>(op1.get_all()))),
+            ValueType::List(op1) => Ok(Brc::new(Value::new::<InkList>(op1.get_all()))),
             _ => Err(StoryError::InvalidStoryState(
                 "Operation not available for type.".to_owned(),
             )),
         }
     }
 
-    fn inverse_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn inverse_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
-            ValueType::List(op1) => Ok(Rc::new(Value::new::<InkList>(op1.inverse()))),
+            ValueType::List(op1) => Ok(Brc::new(Value::new::<InkList>(op1.inverse()))),
             _ => Err(StoryError::InvalidStoryState(
                 "Operation not available for type.".to_owned(),
             )),
         }
     }
 
-    fn count_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn count_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
-            ValueType::List(op1) => Ok(Rc::new(Value::new::<i32>(op1.items.len() as i32))),
+            ValueType::List(op1) => Ok(Brc::new(Value::new::<i32>(op1.items.len() as i32))),
             _ => Err(StoryError::InvalidStoryState(
                 "Operation not available for type.".to_owned(),
             )),
         }
     }
 
-    fn list_max_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn list_max_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
-            ValueType::List(op1) => Ok(Rc::new(Value::new::<InkList>(op1.max_as_list()))),
+            ValueType::List(op1) => Ok(Brc::new(Value::new::<InkList>(op1.max_as_list()))),
             _ => Err(StoryError::InvalidStoryState(
                 "Operation not available for type.".to_owned(),
             )),
         }
     }
 
-    fn list_min_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn list_min_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
-            ValueType::List(op1) => Ok(Rc::new(Value::new::<InkList>(op1.min_as_list()))),
+            ValueType::List(op1) => Ok(Brc::new(Value::new::<InkList>(op1.min_as_list()))),
             _ => Err(StoryError::InvalidStoryState(
                 "Operation not available for type.".to_owned(),
             )),
         }
     }
 
-    fn negate_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn negate_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
-            ValueType::Int(op1) => Ok(Rc::new(Value::new::<i32>(-op1))),
-            ValueType::Float(op1) => Ok(Rc::new(Value::new::<f32>(-op1))),
+            ValueType::Int(op1) => Ok(Brc::new(Value::new::<i32>(-op1))),
+            ValueType::Float(op1) => Ok(Brc::new(Value::new::<f32>(-op1))),
             _ => Err(StoryError::InvalidStoryState(
                 "Operation not available for type.".to_owned(),
             )),
         }
     }
 
-    fn floor_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn floor_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
-            ValueType::Int(op1) => Ok(Rc::new(Value::new::<i32>(*op1))),
-            ValueType::Float(op1) => Ok(Rc::new(Value::new::<f32>(op1.floor()))),
+            ValueType::Int(op1) => Ok(Brc::new(Value::new::<i32>(*op1))),
+            ValueType::Float(op1) => Ok(Brc::new(Value::new::<f32>(op1.floor()))),
             _ => Err(StoryError::InvalidStoryState(
                 "Operation not available for type.".to_owned(),
             )),
         }
     }
 
-    fn ceiling_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn ceiling_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
-            ValueType::Int(op1) => Ok(Rc::new(Value::new::<i32>(*op1))),
-            ValueType::Float(op1) => Ok(Rc::new(Value::new::<f32>(op1.ceil()))),
+            ValueType::Int(op1) => Ok(Brc::new(Value::new::<i32>(*op1))),
+            ValueType::Float(op1) => Ok(Brc::new(Value::new::<f32>(op1.ceil()))),
             _ => Err(StoryError::InvalidStoryState(
                 "Operation not available for type.".to_owned(),
             )),
         }
     }
 
-    fn int_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn int_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
-            ValueType::Int(op1) => Ok(Rc::new(Value::new::<i32>(*op1))),
-            ValueType::Float(op1) => Ok(Rc::new(Value::new::<i32>(*op1 as i32))),
+            ValueType::Int(op1) => Ok(Brc::new(Value::new::<i32>(*op1))),
+            ValueType::Float(op1) => Ok(Brc::new(Value::new::<i32>(*op1 as i32))),
             _ => Err(StoryError::InvalidStoryState(
                 "Operation not available for type.".to_owned(),
             )),
         }
     }
 
-    fn float_op(&self, params: &[Rc<Value>]) -> Result<Rc<dyn RTObject>, StoryError> {
+    fn float_op(&self, params: &[Brc<Value>]) -> Result<Brc<dyn RTObject>, StoryError> {
         match &params[0].value {
-            ValueType::Int(op1) => Ok(Rc::new(Value::new::<f32>(*op1 as f32))),
-            ValueType::Float(op1) => Ok(Rc::new(Value::new::<f32>(*op1))),
+            ValueType::Int(op1) => Ok(Brc::new(Value::new::<f32>(*op1 as f32))),
+            ValueType::Float(op1) => Ok(Brc::new(Value::new::<f32>(*op1))),
             _ => Err(StoryError::InvalidStoryState(
                 "Operation not available for type.".to_owned(),
             )),
@@ -1,5 +1,5 @@
+use crate::threadsafe::BrOnceCell;
 use std::{
-    cell::OnceCell,
     fmt,
     hash::{Hash, Hasher},
 };
@@ -11,7 +11,7 @@ const PARENT_ID: &str = "^";
 pub struct Path {
     components: Vec<Component>,
     is_relative: bool,
-    components_string: OnceCell<String>,
+    components_string: BrOnceCell<String>,
 }
 
 impl Path {
@@ -69,7 +69,7 @@ impl Path {
             }
         }
 
-        let cs_cell = OnceCell::new();
+        let cs_cell = BrOnceCell::new();
         let _ = cs_cell.set(cs);
 
         Path {
@@ -184,6 +184,23 @@ impl fmt::Display for Path {
     }
 }
 
+/// Serialized as its components string, the same way paths are written in
+/// the JSON.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Path {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.get_components_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Path {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let components_string = String::deserialize(deserializer)?;
+        Ok(Path::new_with_components_string(Some(&components_string)))
+    }
+}
+
 impl Hash for Path {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.to_string().hash(state)
@@ -0,0 +1,478 @@
+//! Runtime [`Profiler`] to find out where the time is spent while running a
+//! story.
+use std::{collections::HashMap, fmt::Write, time::Duration};
+
+use web_time::Instant;
+
+use crate::callstack::CallStack;
+
+/// Name used for the frames running content that isn't inside any knot.
+const ROOT_FRAME_NAME: &str = "<root>";
+
+/// Number of pointers listed in the [`Profiler::report`], the most expensive
+/// ones.
+const REPORT_MAX_POINTERS: usize = 20;
+
+/// Collects timing information while the story is running.
+///
+/// Start it with [`Story::start_profiling`](crate::story::Story::start_profiling)
+/// and get it back with
+/// [`Story::end_profiling`](crate::story::Story::end_profiling).
+///
+/// Every step of the story is attributed to its callstack, where each frame is
+/// named after the knot, stitch or function that is running (for example
+/// `my_knot.my_stitch`). The samples are accumulated in a tree of
+/// [`ProfileNode`]s that can be printed with [`report`](Profiler::report) or
+/// exported to the folded stacks format used by flame graph tools with
+/// [`to_folded_stacks`](Profiler::to_folded_stacks).
+///
+/// The steps are also attributed to the exact content they run, the
+/// container path and index of the current pointer (for example
+/// `my_knot.0.3`), to tell apart the expensive lines of a knot. See
+/// [`get_pointer_profiles`](Profiler::get_pointer_profiles).
+pub struct Profiler {
+    root_node: ProfileNode,
+    pointers: HashMap<String, PointerProfile>,
+    num_continues: u32,
+    continue_total: Duration,
+    step_total: Duration,
+    snap_total: Duration,
+    continue_depth: usize,
+    continue_start: Option<Instant>,
+    step_depth: usize,
+    step_start: Option<Instant>,
+    snap_start: Option<Instant>,
+    curr_step_stack: Vec<String>,
+    curr_step_pointer: Option<String>,
+    prev_step_stack: Vec<String>,
+}
+
+impl Profiler {
+    pub(crate) fn new() -> Self {
+        Self {
+            root_node: ProfileNode::new(""),
+            pointers: HashMap::new(),
+            num_continues: 0,
+            continue_total: Duration::ZERO,
+            step_total: Duration::ZERO,
+            snap_total: Duration::ZERO,
+            continue_depth: 0,
+            continue_start: None,
+            step_depth: 0,
+            step_start: None,
+            snap_start: None,
+            curr_step_stack: Vec::new(),
+            curr_step_pointer: None,
+            prev_step_stack: Vec::new(),
+        }
+    }
+
+    /// The root of the tree of samples. Its children are the outermost
+    /// frames of the callstack.
+    pub fn get_root_node(&self) -> &ProfileNode {
+        &self.root_node
+    }
+
+    /// The time spent in every pointer of the story, sorted by time, the
+    /// most expensive first.
+    pub fn get_pointer_profiles(&self) -> Vec<&PointerProfile> {
+        let mut pointers: Vec<&PointerProfile> = self.pointers.values().collect();
+        pointers.sort_by(|a, b| b.time.cmp(&a.time).then(a.path.cmp(&b.path)));
+        pointers
+    }
+
+    /// Number of lines of content that have been continued while profiling.
+    pub fn get_num_continues(&self) -> u32 {
+        self.num_continues
+    }
+
+    /// Total time spent continuing the story.
+    pub fn get_continue_total(&self) -> Duration {
+        self.continue_total
+    }
+
+    /// Time spent in the story steps.
+    pub fn get_step_total(&self) -> Duration {
+        self.step_total
+    }
+
+    /// Time spent taking snapshots of the state, needed to rewind the story
+    /// when looking ahead for glue at the end of a line.
+    pub fn get_snapshot_total(&self) -> Duration {
+        self.snap_total
+    }
+
+    /// Generates a human readable report with the totals, the tree of
+    /// frames and the pointers, with the most expensive ones first.
+    pub fn report(&self) -> String {
+        let mut sb = String::new();
+
+        let _ = writeln!(sb, "{} CONTINUES / LINES:", self.num_continues);
+        let _ = writeln!(sb, "TOTAL TIME: {}", format_duration(self.continue_total));
+        let _ = writeln!(sb, "SNAPSHOTTING: {}", format_duration(self.snap_total));
+        let _ = writeln!(
+            sb,
+            "OTHER: {}",
+            format_duration(
+                self.continue_total
+                    .saturating_sub(self.step_total + self.snap_total)
+            )
+        );
+
+        for child in self.root_node.get_children() {
+            child.print_hierarchy(&mut sb, 0);
+        }
+
+        let pointers = self.get_pointer_profiles();
+        if !pointers.is_empty() {
+            let _ = writeln!(sb, "POINTERS:");
+
+            for pointer in pointers.iter().take(REPORT_MAX_POINTERS) {
+                let _ = writeln!(
+                    sb,
+                    "    {}: {} ({} steps)",
+                    pointer.path,
+                    format_duration(pointer.time),
+                    pointer.step_count
+                );
+            }
+        }
+
+        sb
+    }
+
+    /// Exports the samples in the folded stacks format, one line per stack
+    /// with the frames separated by `;` followed by the self time in
+    /// microseconds. The output can be loaded by `flamegraph.pl`, `inferno`
+    /// or speedscope.
+    pub fn to_folded_stacks(&self) -> String {
+        let mut lines = Vec::new();
+        let mut stack = Vec::new();
+
+        for child in self.root_node.get_children() {
+            child.collect_folded_stacks(&mut stack, &mut lines);
+        }
+
+        lines.sort();
+
+        let mut sb = String::new();
+        for line in lines {
+            sb.push_str(&line);
+            sb.push('\n');
+        }
+
+        sb
+    }
+
+    pub(crate) fn pre_continue(&mut self) {
+        self.continue_depth += 1;
+
+        if self.continue_depth == 1 {
+            self.continue_start = Some(Instant::now());
+        }
+    }
+
+    pub(crate) fn post_continue(&mut self) {
+        if self.continue_depth == 1
+            && let Some(start) = self.continue_start.take()
+        {
+            self.continue_total += start.elapsed();
+            self.num_continues += 1;
+        }
+
+        self.continue_depth = self.continue_depth.saturating_sub(1);
+    }
+
+    pub(crate) fn pre_step(&mut self) {
+        self.step_depth += 1;
+
+        if self.step_depth == 1 {
+            self.curr_step_stack.clear();
+            self.curr_step_pointer = None;
+            self.step_start = Some(Instant::now());
+        }
+    }
+
+    /// Records the callstack of the step that is being executed. Steps
+    /// evaluated from inside another step, e.g. by a host function that
+    /// evaluates an ink function, are accounted to the outer step.
+    pub(crate) fn step(&mut self, callstack: &CallStack) {
+        if self.step_depth != 1 {
+            return;
+        }
+
+        let pause = Instant::now();
+
+        self.curr_step_stack = callstack
+            .get_callstack()
+            .iter()
+            .map(|element| match element.current_pointer.get_path() {
+                Some(path) => {
+                    let mut name = String::new();
+
+                    for i in 0..path.len() {
+                        let comp = path.get_component(i).unwrap();
+
+                        match &comp.name {
+                            Some(comp_name) if !comp.is_index() => {
+                                if !name.is_empty() {
+                                    name.push('.');
+                                }
+                                name.push_str(comp_name);
+                            }
+                            _ => break,
+                        }
+                    }
+
+                    if name.is_empty() {
+                        ROOT_FRAME_NAME.to_owned()
+                    } else {
+                        name
+                    }
+                }
+                None => ROOT_FRAME_NAME.to_owned(),
+            })
+            .collect();
+
+        self.curr_step_pointer = callstack
+            .get_current_element()
+            .current_pointer
+            .get_path()
+            .map(|path| path.to_string());
+
+        // Don't count the time spent building the stack
+        if let Some(start) = self.step_start.as_mut() {
+            *start += pause.elapsed();
+        }
+    }
+
+    pub(crate) fn post_step(&mut self) {
+        if self.step_depth == 1
+            && let Some(start) = self.step_start.take()
+        {
+            let duration = start.elapsed();
+            self.step_total += duration;
+
+            // Frames that weren't in the previous step have just been entered
+            let common = self
+                .curr_step_stack
+                .iter()
+                .zip(self.prev_step_stack.iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            self.root_node
+                .add_sample(&self.curr_step_stack, 0, common, duration);
+
+            if let Some(path) = self.curr_step_pointer.take() {
+                let pointer = self
+                    .pointers
+                    .entry(path)
+                    .or_insert_with_key(|path| PointerProfile::new(path));
+                pointer.step_count += 1;
+                pointer.time += duration;
+            }
+
+            std::mem::swap(&mut self.prev_step_stack, &mut self.curr_step_stack);
+        }
+
+        self.step_depth = self.step_depth.saturating_sub(1);
+    }
+
+    pub(crate) fn pre_snapshot(&mut self) {
+        self.snap_start = Some(Instant::now());
+    }
+
+    pub(crate) fn post_snapshot(&mut self) {
+        if let Some(start) = self.snap_start.take() {
+            self.snap_total += start.elapsed();
+        }
+    }
+}
+
+/// The time spent running the content at a pointer of the story, collected
+/// by the [`Profiler`].
+pub struct PointerProfile {
+    path: String,
+    time: Duration,
+    step_count: u32,
+}
+
+impl PointerProfile {
+    fn new(path: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+            time: Duration::ZERO,
+            step_count: 0,
+        }
+    }
+
+    /// The container path and index of the pointer, e.g. `my_knot.0.3`.
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    /// Time spent in the steps run at this pointer.
+    pub fn get_time(&self) -> Duration {
+        self.time
+    }
+
+    /// Number of steps run at this pointer. Content run again after a
+    /// lookahead is rewound is counted again.
+    pub fn get_step_count(&self) -> u32 {
+        self.step_count
+    }
+}
+
+/// A frame in the tree of samples collected by the [`Profiler`].
+pub struct ProfileNode {
+    key: String,
+    children: HashMap<String, ProfileNode>,
+    total_time: Duration,
+    self_time: Duration,
+    total_step_count: u32,
+    self_step_count: u32,
+    call_count: u32,
+}
+
+impl ProfileNode {
+    fn new(key: &str) -> Self {
+        Self {
+            key: key.to_owned(),
+            children: HashMap::new(),
+            total_time: Duration::ZERO,
+            self_time: Duration::ZERO,
+            total_step_count: 0,
+            self_step_count: 0,
+            call_count: 0,
+        }
+    }
+
+    /// The name of the knot, stitch or function of this frame.
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+
+    /// Time spent in this frame, including the frames called from it.
+    pub fn get_total_time(&self) -> Duration {
+        self.total_time
+    }
+
+    /// Time spent in this frame, excluding the frames called from it.
+    pub fn get_self_time(&self) -> Duration {
+        self.self_time
+    }
+
+    /// Number of steps executed in this frame or the frames called from it.
+    pub fn get_total_step_count(&self) -> u32 {
+        self.total_step_count
+    }
+
+    /// Number of steps executed directly in this frame.
+    pub fn get_self_step_count(&self) -> u32 {
+        self.self_step_count
+    }
+
+    /// Number of times the story entered this frame. When the story looks
+    /// ahead past the end of a line and rewinds, the steps run again and
+    /// frames entered during the lookahead are counted again.
+    pub fn get_call_count(&self) -> u32 {
+        self.call_count
+    }
+
+    /// Frames called from this one, sorted by total time, the most expensive
+    /// first.
+    pub fn get_children(&self) -> Vec<&ProfileNode> {
+        let mut children: Vec<&ProfileNode> = self.children.values().collect();
+        children.sort_by(|a, b| b.total_time.cmp(&a.total_time).then(a.key.cmp(&b.key)));
+        children
+    }
+
+    /// Finds the frame with the given stack of keys, starting from the
+    /// children of this node.
+    pub fn get_descendant(&self, stack: &[&str]) -> Option<&ProfileNode> {
+        match stack.split_first() {
+            Some((first, rest)) => self.children.get(*first)?.get_descendant(rest),
+            None => Some(self),
+        }
+    }
+
+    /// Adds a sample for `stack[stack_idx..]` to the children of this
+    /// node. Frames at or after `new_frames_from` have just been entered.
+    fn add_sample(
+        &mut self,
+        stack: &[String],
+        stack_idx: usize,
+        new_frames_from: usize,
+        duration: Duration,
+    ) {
+        self.total_step_count += 1;
+        self.total_time += duration;
+
+        match stack.get(stack_idx) {
+            Some(key) => {
+                let child = self
+                    .children
+                    .entry(key.clone())
+                    .or_insert_with(|| ProfileNode::new(key));
+
+                if stack_idx >= new_frames_from {
+                    child.call_count += 1;
+                }
+
+                child.add_sample(stack, stack_idx + 1, new_frames_from, duration);
+            }
+            None => {
+                self.self_step_count += 1;
+                self.self_time += duration;
+            }
+        }
+    }
+
+    fn print_hierarchy(&self, sb: &mut String, indent: usize) {
+        let _ = writeln!(
+            sb,
+            "{:indent$}{}: total {}, self {} ({} self steps, {} total steps, {} calls)",
+            "",
+            self.key,
+            format_duration(self.total_time),
+            format_duration(self.self_time),
+            self.self_step_count,
+            self.total_step_count,
+            self.call_count,
+            indent = indent * 4
+        );
+
+        for child in self.get_children() {
+            child.print_hierarchy(sb, indent + 1);
+        }
+    }
+
+    fn collect_folded_stacks<'a>(&'a self, stack: &mut Vec<&'a str>, lines: &mut Vec<String>) {
+        stack.push(&self.key);
+
+        if self.self_step_count > 0 {
+            lines.push(format!(
+                "{} {}",
+                stack.join(";"),
+                self.self_time.as_micros()
+            ));
+        }
+
+        for child in self.children.values() {
+            child.collect_folded_stacks(stack, lines);
+        }
+
+        stack.pop();
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    let ms = d.as_secs_f64() * 1000.0;
+
+    if ms > 5000.0 {
+        format!("{:.1} secs", ms / 1000.0)
+    } else {
+        format!("{:.3} ms", ms)
+    }
+}
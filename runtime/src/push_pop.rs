@@ -1,9 +1,15 @@
+//! [`PushPopType`], the kinds of elements of the callstack.
 use crate::story_error::StoryError;
 
+/// Why an element was pushed to the callstack.
 #[derive(PartialEq, Clone, Copy, Eq, Hash, Debug)]
 pub enum PushPopType {
+    /// A tunnel, `-> knot ->`.
     Tunnel,
+    /// A call to an ink function.
     Function,
+    /// A function called by the host, e.g. with
+    /// [`Story::evaluate_function`](crate::story::Story::evaluate_function).
     FunctionEvaluationFromGame,
 }
 
@@ -0,0 +1,468 @@
+//! Read only [`SaveStateView`] of a saved story state, and a [`diff`] of two
+//! of them, to find out why two playthroughs diverged.
+use std::{collections::BTreeMap, fmt};
+
+use serde_json::Map;
+
+use crate::{
+    json::json_read, push_pop::PushPopType, story_error::StoryError,
+    story_state::DEFAULT_FLOW_NAME, value::Value, value_type::ValueType,
+};
+
+/// The content of a state saved with
+/// [`Story::save_state`](crate::story::Story::save_state). It's parsed
+/// without the story, so paths are shown as they were saved.
+#[derive(Debug, Clone)]
+pub struct SaveStateView {
+    /// Global variables whose value differs from the default. The ones with
+    /// the default value are not saved.
+    pub globals: BTreeMap<String, ValueType>,
+    pub visit_counts: BTreeMap<String, i32>,
+    pub turn_indices: BTreeMap<String, i32>,
+    pub current_flow_name: String,
+    pub flows: BTreeMap<String, FlowView>,
+    pub current_turn_index: i32,
+    pub story_seed: i32,
+}
+
+/// A flow of a [`SaveStateView`].
+#[derive(Debug, Clone)]
+pub struct FlowView {
+    /// The threads of the callstack, the current one last.
+    pub threads: Vec<ThreadView>,
+    pub choices: Vec<ChoiceView>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ThreadView {
+    pub thread_index: usize,
+    /// The elements of the callstack, the innermost last.
+    pub callstack: Vec<CallStackElementView>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CallStackElementView {
+    pub element_type: PushPopType,
+    /// Path of the container the element is in, `None` if it has ended.
+    pub container_path: Option<String>,
+    /// Index of the content inside the container.
+    pub index: i32,
+    pub temporary_variables: BTreeMap<String, ValueType>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChoiceView {
+    pub text: String,
+    pub target_path: String,
+    pub tags: Vec<String>,
+}
+
+impl FlowView {
+    /// Path of the container where the flow is, the innermost element of
+    /// the current thread.
+    pub fn current_path(&self) -> Option<&str> {
+        self.threads
+            .last()?
+            .callstack
+            .last()?
+            .container_path
+            .as_deref()
+    }
+}
+
+impl SaveStateView {
+    /// Parses a state in the JSON format written by
+    /// [`Story::save_state`](crate::story::Story::save_state).
+    pub fn from_json(json_state: &str) -> Result<SaveStateView, StoryError> {
+        let j_state: serde_json::Value = serde_json::from_str(json_state)
+            .map_err(|_| StoryError::BadJson("State not in JSON format.".to_owned()))?;
+        let j_obj = j_state
+            .as_object()
+            .ok_or_else(|| StoryError::BadJson("State not in JSON format.".to_owned()))?;
+
+        if !j_obj.contains_key("inkSaveVersion") {
+            return Err(StoryError::BadJson(
+                "ink save format incorrect, can't load.".to_owned(),
+            ));
+        }
+
+        let mut flows = BTreeMap::new();
+
+        match j_obj.get("flows").and_then(|f| f.as_object()) {
+            Some(j_flows) => {
+                for (name, j_flow) in j_flows {
+                    let j_flow = j_flow
+                        .as_object()
+                        .ok_or_else(|| StoryError::BadJson("Invalid flow object".to_owned()))?;
+                    flows.insert(name.clone(), read_flow(j_flow, "callstack")?);
+                }
+            }
+            // Old format, the default flow is in the state itself
+            None => {
+                flows.insert(
+                    DEFAULT_FLOW_NAME.to_owned(),
+                    read_flow(j_obj, "callstackThreads")?,
+                );
+            }
+        }
+
+        let current_flow_name = match j_obj.get("currentFlowName").and_then(|n| n.as_str()) {
+            Some(name) => name.to_owned(),
+            None => flows.keys().next().cloned().unwrap_or_default(),
+        };
+
+        Ok(SaveStateView {
+            globals: read_values(j_obj.get("variablesState"))?,
+            visit_counts: read_counts(j_obj.get("visitCounts")),
+            turn_indices: read_counts(j_obj.get("turnIndices")),
+            current_flow_name,
+            flows,
+            current_turn_index: read_int(j_obj.get("turnIdx")),
+            story_seed: read_int(j_obj.get("storySeed")),
+        })
+    }
+}
+
+fn read_flow(
+    j_flow: &Map<String, serde_json::Value>,
+    callstack_key: &str,
+) -> Result<FlowView, StoryError> {
+    let mut threads = Vec::new();
+
+    if let Some(j_threads) = j_flow
+        .get(callstack_key)
+        .and_then(|c| c.get("threads"))
+        .and_then(|t| t.as_array())
+    {
+        for j_thread in j_threads {
+            threads.push(read_thread(j_thread)?);
+        }
+    }
+
+    let mut choices = Vec::new();
+
+    if let Some(j_choices) = j_flow.get("currentChoices").and_then(|c| c.as_array()) {
+        for j_choice in j_choices {
+            let text = |key: &str| {
+                j_choice
+                    .get(key)
+                    .and_then(|t| t.as_str())
+                    .unwrap_or_default()
+                    .to_owned()
+            };
+
+            choices.push(ChoiceView {
+                text: text("text"),
+                target_path: text("targetPath"),
+                tags: j_choice
+                    .get("tags")
+                    .and_then(|t| t.as_array())
+                    .map(|tags| {
+                        tags.iter()
+                            .filter_map(|t| t.as_str().map(str::to_owned))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            });
+        }
+    }
+
+    Ok(FlowView { threads, choices })
+}
+
+fn read_thread(j_thread: &serde_json::Value) -> Result<ThreadView, StoryError> {
+    let mut callstack = Vec::new();
+
+    if let Some(j_elements) = j_thread.get("callstack").and_then(|c| c.as_array()) {
+        for j_element in j_elements {
+            let element_type = PushPopType::from_value(
+                j_element
+                    .get("type")
+                    .and_then(|t| t.as_u64())
+                    .ok_or_else(|| StoryError::BadJson("Invalid push/pop type".to_owned()))?
+                    as usize,
+            )?;
+
+            callstack.push(CallStackElementView {
+                element_type,
+                container_path: j_element
+                    .get("cPath")
+                    .and_then(|p| p.as_str())
+                    .map(str::to_owned),
+                index: read_int(j_element.get("idx")),
+                temporary_variables: read_values(j_element.get("temp"))?,
+            });
+        }
+    }
+
+    Ok(ThreadView {
+        thread_index: j_thread
+            .get("threadIndex")
+            .and_then(|i| i.as_u64())
+            .unwrap_or_default() as usize,
+        callstack,
+    })
+}
+
+fn read_values(
+    j_values: Option<&serde_json::Value>,
+) -> Result<BTreeMap<String, ValueType>, StoryError> {
+    let mut values = BTreeMap::new();
+
+    if let Some(j_values) = j_values.and_then(|v| v.as_object()) {
+        for (name, token) in j_values {
+            let value = json_read::jtoken_to_runtime_object(token, None)?
+                .into_any()
+                .downcast::<Value>()
+                .map_err(|_| StoryError::BadJson(format!("Invalid variable value: {}", token)))?;
+
+            values.insert(name.clone(), value.value.clone());
+        }
+    }
+
+    Ok(values)
+}
+
+fn read_counts(j_counts: Option<&serde_json::Value>) -> BTreeMap<String, i32> {
+    j_counts
+        .and_then(|c| c.as_object())
+        .map(|c| {
+            c.iter()
+                .filter_map(|(path, count)| Some((path.clone(), count.as_i64()? as i32)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn read_int(j_int: Option<&serde_json::Value>) -> i32 {
+    j_int.and_then(|i| i.as_i64()).unwrap_or_default() as i32
+}
+
+/// What changed from one saved state to another, as returned by [`diff`].
+#[derive(Debug, Clone, Default)]
+pub struct SaveStateDiff {
+    /// Global variables with a different value, sorted by name.
+    pub variables: Vec<VariableChange>,
+    /// Paths with a different visit count, sorted.
+    pub visits: Vec<VisitChange>,
+    pub flows: Vec<FlowChange>,
+}
+
+/// A global variable changed between two states. `None` is the default value
+/// of the variable, which isn't saved.
+#[derive(Debug, Clone)]
+pub struct VariableChange {
+    pub name: String,
+    pub before: Option<ValueType>,
+    pub after: Option<ValueType>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VisitChange {
+    pub path: String,
+    pub before: i32,
+    pub after: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlowChange {
+    /// The current flow is a different one.
+    CurrentFlow {
+        before: String,
+        after: String,
+    },
+    Added(String),
+    Removed(String),
+    /// The flow is at a different path, see [`FlowView::current_path`].
+    Position {
+        flow: String,
+        before: Option<String>,
+        after: Option<String>,
+    },
+    /// The flow has different choices, given by their texts.
+    Choices {
+        flow: String,
+        before: Vec<String>,
+        after: Vec<String>,
+    },
+}
+
+impl SaveStateDiff {
+    /// `true` if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty() && self.visits.is_empty() && self.flows.is_empty()
+    }
+}
+
+/// Compares two saved states: the global variables, the visit counts and
+/// the position and choices of the flows.
+pub fn diff(a: &SaveStateView, b: &SaveStateView) -> SaveStateDiff {
+    let mut result = SaveStateDiff::default();
+
+    let mut names: Vec<&String> = a.globals.keys().chain(b.globals.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    for name in names {
+        let before = a.globals.get(name);
+        let after = b.globals.get(name);
+
+        let same = match (before, after) {
+            (Some(before), Some(after)) => before.same_value(after),
+            (None, None) => true,
+            _ => false,
+        };
+
+        if !same {
+            result.variables.push(VariableChange {
+                name: name.clone(),
+                before: before.cloned(),
+                after: after.cloned(),
+            });
+        }
+    }
+
+    let mut paths: Vec<&String> = a.visit_counts.keys().chain(b.visit_counts.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    for path in paths {
+        let before = a.visit_counts.get(path).copied().unwrap_or_default();
+        let after = b.visit_counts.get(path).copied().unwrap_or_default();
+
+        if before != after {
+            result.visits.push(VisitChange {
+                path: path.clone(),
+                before,
+                after,
+            });
+        }
+    }
+
+    if a.current_flow_name != b.current_flow_name {
+        result.flows.push(FlowChange::CurrentFlow {
+            before: a.current_flow_name.clone(),
+            after: b.current_flow_name.clone(),
+        });
+    }
+
+    for name in a.flows.keys().filter(|n| !b.flows.contains_key(*n)) {
+        result.flows.push(FlowChange::Removed(name.clone()));
+    }
+
+    for (name, flow_b) in b.flows.iter() {
+        let Some(flow_a) = a.flows.get(name) else {
+            result.flows.push(FlowChange::Added(name.clone()));
+            continue;
+        };
+
+        if flow_a.current_path() != flow_b.current_path() {
+            result.flows.push(FlowChange::Position {
+                flow: name.clone(),
+                before: flow_a.current_path().map(str::to_owned),
+                after: flow_b.current_path().map(str::to_owned),
+            });
+        }
+
+        if flow_a.choices != flow_b.choices {
+            let texts = |choices: &[ChoiceView]| choices.iter().map(|c| c.text.clone()).collect();
+
+            result.flows.push(FlowChange::Choices {
+                flow: name.clone(),
+                before: texts(&flow_a.choices),
+                after: texts(&flow_b.choices),
+            });
+        }
+    }
+
+    result
+}
+
+fn fmt_value(value: &Option<ValueType>) -> String {
+    match value {
+        Some(ValueType::String(s)) => format!("\"{}\"", s.string),
+        Some(value) => value.to_string(),
+        None => "(default)".to_owned(),
+    }
+}
+
+fn fmt_path(path: &Option<String>) -> &str {
+    path.as_deref().unwrap_or("(ended)")
+}
+
+impl fmt::Display for VariableChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.name,
+            fmt_value(&self.before),
+            fmt_value(&self.after)
+        )
+    }
+}
+
+impl fmt::Display for VisitChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.path, self.before, self.after)
+    }
+}
+
+impl fmt::Display for FlowChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FlowChange::CurrentFlow { before, after } => {
+                write!(f, "current flow: {} -> {}", before, after)
+            }
+            FlowChange::Added(name) => write!(f, "{}: added", name),
+            FlowChange::Removed(name) => write!(f, "{}: removed", name),
+            FlowChange::Position {
+                flow,
+                before,
+                after,
+            } => write!(
+                f,
+                "{}: at {} -> {}",
+                flow,
+                fmt_path(before),
+                fmt_path(after)
+            ),
+            FlowChange::Choices {
+                flow,
+                before,
+                after,
+            } => write!(f, "{}: choices {:?} -> {:?}", flow, before, after),
+        }
+    }
+}
+
+impl fmt::Display for SaveStateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "NO DIFFERENCES");
+        }
+
+        if !self.variables.is_empty() {
+            writeln!(f, "VARIABLES:")?;
+            for change in self.variables.iter() {
+                writeln!(f, "    {}", change)?;
+            }
+        }
+
+        if !self.visits.is_empty() {
+            writeln!(f, "VISITS:")?;
+            for change in self.visits.iter() {
+                writeln!(f, "    {}", change)?;
+            }
+        }
+
+        if !self.flows.is_empty() {
+            writeln!(f, "FLOWS:")?;
+            for change in self.flows.iter() {
+                writeln!(f, "    {}", change)?;
+            }
+        }
+
+        Ok(())
+    }
+}
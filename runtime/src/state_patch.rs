@@ -1,13 +1,10 @@
-use std::{
-    collections::{HashMap, HashSet},
-    rc::Rc,
-};
+use std::collections::{HashMap, HashSet};
 
-use crate::{container::Container, object::Object, value::Value};
+use crate::{container::Container, object::Object, threadsafe::Brc, value::Value};
 
 #[derive(Clone)]
 pub struct StatePatch {
-    pub globals: HashMap<String, Rc<Value>>,
+    pub globals: HashMap<String, Brc<Value>>,
     pub changed_variables: HashSet<String>,
     pub visit_counts: HashMap<String, i32>,
     pub turn_indices: HashMap<String, i32>,
@@ -23,21 +20,21 @@ impl StatePatch {
         }
     }
 
-    pub fn get_visit_count(&self, container: &Rc<Container>) -> Option<i32> {
+    pub fn get_visit_count(&self, container: &Brc<Container>) -> Option<i32> {
         let key = Object::get_path(container.as_ref()).to_string();
         self.visit_counts.get(&key).copied()
     }
 
-    pub fn set_visit_count(&mut self, container: &Rc<Container>, count: i32) {
+    pub fn set_visit_count(&mut self, container: &Brc<Container>, count: i32) {
         let key = Object::get_path(container.as_ref()).to_string();
         self.visit_counts.insert(key, count);
     }
 
-    pub fn get_global(&self, name: &str) -> Option<Rc<Value>> {
+    pub fn get_global(&self, name: &str) -> Option<Brc<Value>> {
         self.globals.get(name).cloned()
     }
 
-    pub fn set_global(&mut self, name: &str, value: Rc<Value>) {
+    pub fn set_global(&mut self, name: &str, value: Brc<Value>) {
         self.globals.insert(name.to_string(), value);
     }
 
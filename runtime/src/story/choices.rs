@@ -1,8 +1,7 @@
 use crate::{
     choice::Choice, choice_point::ChoicePoint, object::Object, path::Path, story::Story,
-    story_error::StoryError, tag::Tag, value::Value, value_type::StringValue,
+    story_error::StoryError, tag::Tag, threadsafe::Brc, value::Value, value_type::StringValue,
 };
-use std::rc::Rc;
 /// # Choices
 /// Methods to get and select choices.
 impl Story {
@@ -17,6 +16,8 @@ impl Story {
             return Err(StoryError::BadArgument("choice out of range".to_owned()));
         }
 
+        self.record_history();
+
         // Replace callstack with the one from the thread at the choosing point,
         // so that we can jump into the right place in the flow.
         // This is important in case the flow was forked by a new thread, which
@@ -28,6 +29,8 @@ impl Story {
             .borrow_mut()
             .set_current_thread(choice_to_choose.get_thread_at_generation().unwrap());
 
+        self.notify_make_choice(choice_to_choose);
+
         self.choose_path(&choice_to_choose.target_path, true)?;
 
         Ok(())
@@ -49,8 +52,8 @@ impl Story {
 
     pub(crate) fn process_choice(
         &mut self,
-        choice_point: &Rc<ChoicePoint>,
-    ) -> Result<Option<Rc<Choice>>, StoryError> {
+        choice_point: &Brc<ChoicePoint>,
+    ) -> Result<Option<Brc<Choice>>, StoryError> {
         let mut show_choice = true;
 
         // Don't create choice if choice point doesn't pass conditional
@@ -92,7 +95,7 @@ impl Story {
 
         start_text.push_str(&choice_only_text);
 
-        let choice = Rc::new(Choice::new(
+        let choice = Brc::new(Choice::new(
             choice_point.get_path_on_choice(),
             Object::get_path(choice_point.as_ref()).to_string(),
             choice_point.is_invisible_default(),
@@ -113,7 +116,7 @@ impl Story {
         // Is a default invisible choice the ONLY choice?
         // var invisibleChoices = allChoices.Where (c =>
         // c.choicePoint.isInvisibleDefault).ToList();
-        let mut invisible_choices: Vec<Rc<Choice>> = Vec::new();
+        let mut invisible_choices: Vec<Brc<Choice>> = Vec::new();
         for c in all_choices {
             if c.is_invisible_default {
                 invisible_choices.push(c.clone());
@@ -10,27 +10,24 @@ use crate::{
     pointer,
     push_pop::PushPopType,
     story::Story,
-    story_error::StoryError,
+    story_error::{RuntimeErrorKind, StoryError},
     story_state::StoryState,
     tag::Tag,
+    threadsafe::Brc,
     value::Value,
     value_type::{StringValue, ValueType},
     variable_assigment::VariableAssignment,
     variable_reference::VariableReference,
     void::Void,
 };
-use rand::{RngExt, SeedableRng, rngs::StdRng};
-use std::{
-    collections::{HashMap, VecDeque},
-    rc::Rc,
-};
+use std::collections::{HashMap, VecDeque};
 
 /// # Control and Logic
 /// Methods for performing logic and flow control.
 impl Story {
     pub(crate) fn perform_logic_and_flow_control(
         &mut self,
-        content_obj: &Option<Rc<dyn RTObject>>,
+        content_obj: &Option<Brc<dyn RTObject>>,
     ) -> Result<bool, StoryError> {
         let content_obj = match content_obj {
             Some(content_obj) => content_obj.clone(),
@@ -132,8 +129,8 @@ impl Story {
                             // the
                             // only problem is when exporting text for viewing, it
                             // skips over numbers etc.
-                            let text: Rc<dyn RTObject> =
-                                Rc::new(Value::new::<&str>(&output.to_string()));
+                            let text: Brc<dyn RTObject> =
+                                Brc::new(Value::new::<&str>(&output.to_string()));
                             self.get_state_mut().push_to_output_stream(text);
                         }
                     }
@@ -245,8 +242,8 @@ impl Story {
                     // Since we're iterating backward through the content,
                     // build a stack so that when we build the string,
                     // it's in the right order
-                    let mut content_stack_for_string: VecDeque<Rc<dyn RTObject>> = VecDeque::new();
-                    let mut content_to_retain: VecDeque<Rc<dyn RTObject>> = VecDeque::new();
+                    let mut content_stack_for_string: VecDeque<Brc<dyn RTObject>> = VecDeque::new();
+                    let mut content_to_retain: VecDeque<Brc<dyn RTObject>> = VecDeque::new();
                     let mut output_count_consumed = 0;
                     for i in (0..self.get_state().get_output_stream().len()).rev() {
                         let obj = &self.get_state().get_output_stream()[i];
@@ -287,18 +284,18 @@ impl Story {
                     // Return to expression evaluation (from content mode)
                     self.get_state().set_in_expression_evaluation(true);
                     self.get_state_mut()
-                        .push_evaluation_stack(Rc::new(Value::new::<&str>(&sb)));
+                        .push_evaluation_stack(Brc::new(Value::new::<&str>(&sb)));
                 }
                 CommandType::NoOp => {}
                 CommandType::ChoiceCount => {
                     let choice_count = self.get_state().get_generated_choices().len();
                     self.get_state_mut()
-                        .push_evaluation_stack(Rc::new(Value::new::<i32>(choice_count as i32)));
+                        .push_evaluation_stack(Brc::new(Value::new::<i32>(choice_count as i32)));
                 }
                 CommandType::Turns => {
                     let current_turn = self.get_state().current_turn_index;
                     self.get_state_mut()
-                        .push_evaluation_stack(Rc::new(Value::new::<i32>(current_turn + 1)));
+                        .push_evaluation_stack(Brc::new(Value::new::<i32>(current_turn + 1)));
                 }
                 CommandType::TurnsSince | CommandType::ReadCount => {
                     let target = self.get_state_mut().pop_evaluation_stack();
@@ -341,7 +338,8 @@ impl Story {
                                 either_count = 0;
                             } // visit count, assume 0 to default to allowing entry
 
-                            self.add_error(
+                            self.add_runtime_error(
+                                RuntimeErrorKind::NotFound,
                                 &format!(
                                     "Failed to find container for {} lookup at {}",
                                     eval_command, target
@@ -352,7 +350,7 @@ impl Story {
                     }
 
                     self.get_state_mut()
-                        .push_evaluation_stack(Rc::new(Value::new::<i32>(either_count)));
+                        .push_evaluation_stack(Brc::new(Value::new::<i32>(either_count)));
                 }
                 CommandType::Random => {
                     let mut max_int = None;
@@ -391,14 +389,16 @@ impl Story {
                         )));
                     }
 
-                    let result_seed =
-                        self.get_state().story_seed + self.get_state().previous_random;
-                    let mut rng = StdRng::seed_from_u64(result_seed as u64);
-                    let next_random = rng.random::<u32>();
+                    let result_seed = self
+                        .get_state()
+                        .story_seed
+                        .wrapping_add(self.get_state().previous_random);
+                    let next_random = self.random_numbers(result_seed, 1)[0];
                     let chosen_value = (next_random % random_range as u32) as i32 + min_value;
                     self.get_state_mut()
-                        .push_evaluation_stack(Rc::new(Value::new::<i32>(chosen_value)));
-                    self.get_state_mut().previous_random = self.get_state().previous_random + 1;
+                        .push_evaluation_stack(Brc::new(Value::new::<i32>(chosen_value)));
+                    self.get_state_mut().previous_random =
+                        self.get_state().previous_random.wrapping_add(1);
                 }
                 CommandType::SeedRandom => {
                     let mut seed: Option<i32> = None;
@@ -417,18 +417,18 @@ impl Story {
                     self.get_state_mut().story_seed = seed.unwrap();
                     self.get_state_mut().previous_random = 0; // SEED_RANDOM returns nothing.
                     self.get_state_mut()
-                        .push_evaluation_stack(Rc::new(Void::new()));
+                        .push_evaluation_stack(Brc::new(Void::new()));
                 }
                 CommandType::VisitIndex => {
                     let cpc = self.get_state().get_current_pointer().container.unwrap();
                     let count = self.get_state_mut().visit_count_for_container(&cpc) - 1; // index
                     // not count
                     self.get_state_mut()
-                        .push_evaluation_stack(Rc::new(Value::new::<i32>(count)));
+                        .push_evaluation_stack(Brc::new(Value::new::<i32>(count)));
                 }
                 CommandType::SequenceShuffleIndex => {
                     let shuffle_index = self.next_sequence_shuffle_index()?;
-                    let v = Rc::new(Value::new::<i32>(shuffle_index));
+                    let v = Brc::new(Value::new::<i32>(shuffle_index));
                     self.get_state_mut().push_evaluation_stack(v);
                 }
                 CommandType::StartThread => {
@@ -496,7 +496,7 @@ impl Story {
                     }
 
                     self.get_state_mut()
-                        .push_evaluation_stack(Rc::new(generated_list_value.unwrap()));
+                        .push_evaluation_stack(Brc::new(generated_list_value.unwrap()));
                 }
                 CommandType::ListRange => {
                     let mut p = self.get_state_mut().pop_evaluation_stack();
@@ -515,7 +515,7 @@ impl Story {
                         .unwrap()
                         .list_with_sub_range(&min.unwrap().value, &max.unwrap().value);
                     self.get_state_mut()
-                        .push_evaluation_stack(Rc::new(Value::new::<InkList>(result)));
+                        .push_evaluation_stack(Brc::new(Value::new::<InkList>(result)));
                 }
                 CommandType::ListRandom => {
                     let o = self.get_state_mut().pop_evaluation_stack();
@@ -535,10 +535,11 @@ impl Story {
                         // Non-empty source list
                         else {
                             // Generate a random index for the element to take
-                            let result_seed =
-                                self.get_state().story_seed + self.get_state().previous_random;
-                            let mut rng = StdRng::seed_from_u64(result_seed as u64);
-                            let next_random = rng.random::<u32>();
+                            let result_seed = self
+                                .get_state()
+                                .story_seed
+                                .wrapping_add(self.get_state().previous_random);
+                            let next_random = self.random_numbers(result_seed, 1)[0];
                             let list_item_index = (next_random as usize) % list.items.len(); // Iterate through to get the random element, sorted for
                             // predictibility
                             let mut sorted: Vec<(&InkListItem, &i32)> = list.items.iter().collect();
@@ -554,7 +555,7 @@ impl Story {
                         }
                     };
                     self.get_state_mut()
-                        .push_evaluation_stack(Rc::new(Value::new::<InkList>(new_list)));
+                        .push_evaluation_stack(Brc::new(Value::new::<InkList>(new_list)));
                 }
                 CommandType::BeginTag => self
                     .get_state_mut()
@@ -616,7 +617,7 @@ impl Story {
                         }
 
                         let choice_tag =
-                            Rc::new(Tag::new(&StoryState::clean_output_whitespace(&sb)));
+                            Brc::new(Tag::new(&StoryState::clean_output_whitespace(&sb)));
                         // Pushing to the evaluation stack means it gets picked up
                         // when a Choice is generated from the next Choice Point.
                         self.get_state_mut().push_evaluation_stack(choice_tag);
@@ -657,13 +658,13 @@ impl Story {
             .into_any()
             .downcast::<VariableReference>()
         {
-            let found_value: Rc<Value>; // Explicit read count value
+            let found_value: Brc<Value>; // Explicit read count value
             if var_ref.path_for_count.is_some() {
                 let container = var_ref.get_container_for_count();
                 let count = self
                     .get_state_mut()
                     .visit_count_for_container(container.as_ref().unwrap());
-                found_value = Rc::new(Value::new::<i32>(count));
+                found_value = Brc::new(Value::new::<i32>(count));
             }
             // Normal variable reference
             else {
@@ -674,8 +675,8 @@ impl Story {
                 {
                     Some(v) => found_value = v,
                     None => {
-                        self.add_error(&format!("Variable not found: '{}'. Using default value of 0 (false). This can happen with temporary variables if the declaration hasn't yet been hit. Globals are always given a default value on load if a value doesn't exist in the save state.", var_ref.name), true);
-                        found_value = Rc::new(Value::new::<i32>(0));
+                        self.add_runtime_error(RuntimeErrorKind::NotFound, &format!("Variable not found: '{}'. Using default value of 0 (false). This can happen with temporary variables if the declaration hasn't yet been hit. Globals are always given a default value on load if a value doesn't exist in the save state.", var_ref.name), true);
+                        found_value = Brc::new(Value::new::<i32>(0));
                     }
                 }
             }
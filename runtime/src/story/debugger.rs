@@ -0,0 +1,209 @@
+pub use crate::push_pop::PushPopType;
+use crate::{path::Path, story::Story, story_error::StoryError, value_type::ValueType};
+
+/// Why [`Story::debug_continue`] or [`Story::debug_step`] returned.
+#[derive(Debug, PartialEq, Clone)]
+pub enum DebugStop {
+    /// The story is paused before running the content at the breakpoint with
+    /// the given path.
+    Breakpoint(String),
+    /// The story is paused after running a single step.
+    Step,
+    /// The evaluation of the current line has finished, its text is available
+    /// with [`get_current_text`](Story::get_current_text).
+    LineCompleted,
+}
+
+/// A frame of the callstack, as seen by the debugger.
+#[derive(Clone)]
+pub struct DebugFrame {
+    /// How the frame was entered: a tunnel, an ink function or a function
+    /// evaluated from the game. The outermost frame is always a tunnel.
+    pub element_type: PushPopType,
+    /// Path of the content the frame is pointing at.
+    pub path: Option<String>,
+    /// Whether the frame is evaluating an expression.
+    pub in_expression_evaluation: bool,
+    /// Temporary variables of the frame, sorted by name.
+    pub temporary_variables: Vec<(String, ValueType)>,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub(crate) enum DebugMode {
+    Off,
+    Breakpoints,
+    SingleStep,
+}
+
+/// # Debugger
+/// Methods to pause the story in the middle of the evaluation of a line and
+/// inspect its state.
+///
+/// [`debug_continue`](Story::debug_continue) works like
+/// [`cont`](Story::cont), but pauses before running the content at any of
+/// the breakpoints. [`debug_step`](Story::debug_step) runs a single runtime
+/// object. While paused, the callstack, the evaluation stack and the output
+/// stream can be inspected, and evaluation is resumed with another debug call
+/// or with [`cont`](Story::cont), which ignores the breakpoints.
+///
+/// Breakpoints are only honoured by the outermost evaluation, not while a
+/// host function evaluates ink. When the story looks ahead past the end of
+/// a line, a breakpoint can be hit in content that is later rewound, in that
+/// case it will be hit again when the content is run for real.
+impl Story {
+    /// Adds a breakpoint at the given container path, e.g. `knot`,
+    /// `knot.stitch` or `knot.stitch.0`.
+    pub fn add_breakpoint(&mut self, path: &str) -> Result<(), StoryError> {
+        let p = Path::new_with_components_string(Some(path));
+
+        if p.is_empty() || self.content_at_path(&p).approximate {
+            return Err(StoryError::BadArgument(format!(
+                "Breakpoint path not found: '{path}'"
+            )));
+        }
+
+        self.breakpoints.insert(p.get_components_string());
+
+        Ok(())
+    }
+
+    /// Removes a breakpoint. Returns `false` if there wasn't a breakpoint at
+    /// the given path.
+    pub fn remove_breakpoint(&mut self, path: &str) -> bool {
+        let p = Path::new_with_components_string(Some(path));
+        self.breakpoints.remove(&p.get_components_string())
+    }
+
+    /// Removes all the breakpoints.
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// The paths of all the breakpoints, sorted.
+    pub fn get_breakpoints(&self) -> Vec<String> {
+        self.breakpoints.iter().cloned().collect()
+    }
+
+    /// `true` if the evaluation of the current line was paused by the
+    /// debugger.
+    pub fn is_debug_paused(&self) -> bool {
+        self.debug_paused
+    }
+
+    /// Continues the evaluation of the current line until a breakpoint is
+    /// hit or the line is completed.
+    pub fn debug_continue(&mut self) -> Result<DebugStop, StoryError> {
+        self.debug_run(DebugMode::Breakpoints)
+    }
+
+    /// Runs a single step of the story, i.e. a single runtime object.
+    pub fn debug_step(&mut self) -> Result<DebugStop, StoryError> {
+        self.debug_run(DebugMode::SingleStep)
+    }
+
+    fn debug_run(&mut self, mode: DebugMode) -> Result<DebugStop, StoryError> {
+        self.debug_mode = mode;
+        let result = self.continue_async(0.0);
+        self.debug_mode = DebugMode::Off;
+        result?;
+
+        match self.debug_stop.take() {
+            Some(stop) => Ok(stop),
+            None => Ok(DebugStop::LineCompleted),
+        }
+    }
+
+    /// The frames of the callstack of the current thread, the outermost
+    /// first.
+    pub fn debug_callstack(&self) -> Vec<DebugFrame> {
+        self.get_state()
+            .get_callstack()
+            .borrow()
+            .get_callstack()
+            .iter()
+            .map(|element| {
+                let mut temporary_variables: Vec<(String, ValueType)> = element
+                    .temporary_variables
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.value.clone()))
+                    .collect();
+                temporary_variables.sort_by(|a, b| a.0.cmp(&b.0));
+
+                DebugFrame {
+                    element_type: element.push_pop_type,
+                    path: element
+                        .current_pointer
+                        .get_path()
+                        .map(|p| p.get_components_string()),
+                    in_expression_evaluation: element.in_expression_evaluation,
+                    temporary_variables,
+                }
+            })
+            .collect()
+    }
+
+    /// The contents of the evaluation stack, from the bottom to the top.
+    pub fn debug_evaluation_stack(&self) -> Vec<String> {
+        self.get_state()
+            .evaluation_stack
+            .iter()
+            .map(|o| o.to_string())
+            .collect()
+    }
+
+    /// The objects in the output stream of the line being evaluated.
+    pub fn debug_output_stream(&self) -> Vec<String> {
+        self.get_state()
+            .get_output_stream()
+            .iter()
+            .map(|o| o.to_string())
+            .collect()
+    }
+
+    /// Called before every step of the evaluation. Returns `true` if the
+    /// evaluation must be paused.
+    pub(crate) fn check_debug_pause(&mut self, steps: usize, resuming: bool) -> bool {
+        if self.recursive_continue_count != 1 {
+            return false;
+        }
+
+        let stop = match self.debug_mode {
+            DebugMode::Off => None,
+            DebugMode::SingleStep => (steps == 1).then_some(DebugStop::Step),
+            DebugMode::Breakpoints => {
+                // Don't stop again at the breakpoint we are resuming from
+                if self.breakpoints.is_empty() || (resuming && steps == 0) {
+                    None
+                } else {
+                    self.breakpoint_at_current_pointer()
+                        .map(DebugStop::Breakpoint)
+                }
+            }
+        };
+
+        self.debug_paused = stop.is_some();
+        self.debug_stop = stop;
+
+        self.debug_paused
+    }
+
+    fn breakpoint_at_current_pointer(&self) -> Option<String> {
+        let pointer = self.get_state().get_current_pointer();
+        let container = pointer.container.as_ref()?;
+
+        let path = pointer.get_path()?.get_components_string();
+        if self.breakpoints.contains(&path) {
+            return Some(path);
+        }
+
+        // At the start of a container, a breakpoint in the container itself
+        if pointer.index <= 0 {
+            let path = container.get_path().get_components_string();
+            if self.breakpoints.contains(&path) {
+                return Some(path);
+            }
+        }
+
+        None
+    }
+}
@@ -1,15 +1,25 @@
-use std::{cell::RefCell, rc::Rc};
+use crate::{
+    story::Story,
+    story_error::{RuntimeError, RuntimeErrorKind},
+    threadsafe::{BrCell, BrSync, Brc},
+};
 
-use crate::story::Story;
-
-/// Defines the method that will be called when an error occurs while executing
-/// the story.
-pub trait ErrorHandler {
+/// Defines the methods that will be called when an error occurs while
+/// executing the story. [`error`](ErrorHandler::error) gets the formatted
+/// message. Override [`runtime_error`](ErrorHandler::runtime_error) to also
+/// get the kind of error, the ink path and the callstack.
+pub trait ErrorHandler: BrSync {
     fn error(&mut self, message: &str, error_type: ErrorType);
+
+    /// Called for every error or warning. By default it calls
+    /// [`error`](ErrorHandler::error) with the formatted message.
+    fn runtime_error(&mut self, error: &RuntimeError) {
+        self.error(&error.to_string(), error.error_type);
+    }
 }
 
 /// Types of errors an Ink story might throw.
-#[derive(PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum ErrorType {
     /// Problem that is not critical, but should be fixed.
     Warning,
@@ -25,25 +35,42 @@ impl Story {
     /// the story.
     /// It's strongly recommended that you assign an error handler to your
     /// story instance, to avoid getting panics for ink errors.
-    pub fn set_error_handler(&mut self, err_handler: Rc<RefCell<dyn ErrorHandler>>) {
+    pub fn set_error_handler(&mut self, err_handler: Brc<BrCell<dyn ErrorHandler>>) {
         self.on_error = Some(err_handler);
     }
 
     pub(crate) fn add_error(&mut self, message: &str, is_warning: bool) {
-        let error_type_str = if is_warning { "WARNING" } else { "ERROR" };
+        self.add_runtime_error(RuntimeErrorKind::InvalidStoryState, message, is_warning);
+    }
 
-        let m = if !self.get_state().get_current_pointer().is_null() {
-            format!(
-                "RUNTIME {}: ({}): {}",
-                error_type_str,
-                self.get_state().get_current_pointer().get_path().unwrap(),
-                message
-            )
-        } else {
-            format!("RUNTIME {}: {}", error_type_str, message)
-        };
+    pub(crate) fn add_runtime_error(
+        &mut self,
+        kind: RuntimeErrorKind,
+        message: &str,
+        is_warning: bool,
+    ) {
+        let mut error = RuntimeError::new(kind, message.to_owned());
 
-        self.get_state_mut().add_error(m, is_warning);
+        if is_warning {
+            error.error_type = ErrorType::Warning;
+        }
+
+        if !self.get_state().get_current_pointer().is_null() {
+            error.path = self
+                .get_state()
+                .get_current_pointer()
+                .get_path()
+                .map(|p| p.to_string());
+        }
+
+        error.callstack_trace = Some(
+            self.get_state()
+                .get_callstack()
+                .borrow()
+                .get_callstack_trace(),
+        );
+
+        self.get_state_mut().add_error(error);
 
         if !is_warning {
             self.get_state_mut().force_end();
@@ -71,4 +98,16 @@ impl Story {
     pub fn get_current_warnings(&self) -> &[String] {
         self.get_state().get_current_warnings()
     }
+
+    /// The errors of [`get_current_errors`](Story::get_current_errors), with
+    /// their kind, ink path and callstack.
+    pub fn get_current_runtime_errors(&self) -> &[RuntimeError] {
+        self.get_state().get_current_runtime_errors()
+    }
+
+    /// The warnings of [`get_current_warnings`](Story::get_current_warnings),
+    /// with their kind, ink path and callstack.
+    pub fn get_current_runtime_warnings(&self) -> &[RuntimeError] {
+        self.get_state().get_current_runtime_warnings()
+    }
 }
@@ -0,0 +1,118 @@
+use crate::{
+    choice::Choice,
+    story::Story,
+    threadsafe::{BrCell, BrSync, Brc},
+    value_type::ValueType,
+};
+
+/// Receives notifications about the main actions performed on a [`Story`].
+///
+/// All methods have an empty default implementation, so a listener only needs
+/// to implement the events it is interested in.
+pub trait StoryEventListener: BrSync {
+    /// Called after a line of content has been evaluated with
+    /// [`cont`](Story::cont) or
+    /// [`continue_async`](Story::continue_async), once the evaluation has
+    /// finished. It receives the text and the tags of the line.
+    fn on_did_continue(&mut self, _text: &str, _tags: &[String]) {}
+
+    /// Called when a choice is selected with
+    /// [`choose_choice_index`](Story::choose_choice_index), before the story
+    /// moves to the choice target.
+    fn on_make_choice(&mut self, _choice: &Choice) {}
+
+    /// Called when [`evaluate_function`](Story::evaluate_function) starts
+    /// evaluating an ink function.
+    fn on_evaluate_function(&mut self, _function_name: &str, _args: &[ValueType]) {}
+
+    /// Called when [`evaluate_function`](Story::evaluate_function) has
+    /// finished, with the text output and the value returned by the function.
+    fn on_complete_evaluate_function(
+        &mut self,
+        _function_name: &str,
+        _args: &[ValueType],
+        _text_output: &str,
+        _result: Option<&ValueType>,
+    ) {
+    }
+
+    /// Called when the story is moved to a new path with
+    /// [`choose_path_string`](Story::choose_path_string).
+    fn on_choose_path_string(&mut self, _path: &str, _args: &[ValueType]) {}
+}
+
+/// # Events
+/// Methods to register listeners that are notified when the story continues,
+/// a choice is made, the story jumps to a path or a function is evaluated.
+impl Story {
+    /// Adds a listener that will be notified of the story events. Several
+    /// listeners can be registered, they are notified in the order they were
+    /// added.
+    pub fn add_event_listener(&mut self, listener: Brc<BrCell<dyn StoryEventListener>>) {
+        self.event_listeners.push(listener);
+    }
+
+    /// Removes a listener previously registered with
+    /// [`add_event_listener`](Story::add_event_listener). Returns `false` if
+    /// the listener wasn't registered.
+    pub fn remove_event_listener(
+        &mut self,
+        listener: &Brc<BrCell<dyn StoryEventListener>>,
+    ) -> bool {
+        match self
+            .event_listeners
+            .iter()
+            .position(|l| Brc::ptr_eq(l, listener))
+        {
+            Some(index) => {
+                self.event_listeners.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn notify_did_continue(&mut self) {
+        if self.event_listeners.is_empty() {
+            return;
+        }
+
+        let text = self.get_state_mut().get_current_text();
+        let tags = self.get_state_mut().get_current_tags();
+
+        for l in self.event_listeners.iter() {
+            l.borrow_mut().on_did_continue(&text, &tags);
+        }
+    }
+
+    pub(crate) fn notify_make_choice(&self, choice: &Choice) {
+        for l in self.event_listeners.iter() {
+            l.borrow_mut().on_make_choice(choice);
+        }
+    }
+
+    pub(crate) fn notify_evaluate_function(&self, function_name: &str, args: &[ValueType]) {
+        for l in self.event_listeners.iter() {
+            l.borrow_mut().on_evaluate_function(function_name, args);
+        }
+    }
+
+    pub(crate) fn notify_complete_evaluate_function(
+        &self,
+        function_name: &str,
+        args: &[ValueType],
+        text_output: &str,
+        result: Option<&ValueType>,
+    ) {
+        for l in self.event_listeners.iter() {
+            l.borrow_mut()
+                .on_complete_evaluate_function(function_name, args, text_output, result);
+        }
+    }
+
+    pub(crate) fn notify_choose_path_string(&self, path: &str, args: &[ValueType]) {
+        for l in self.event_listeners.iter() {
+            l.borrow_mut().on_choose_path_string(path, args);
+        }
+    }
+}
@@ -1,21 +1,270 @@
-use std::{cell::RefCell, collections::HashSet, rc::Rc};
+use std::{collections::HashSet, fmt::Display, marker::PhantomData};
 
 use crate::{
-    container::Container, divert::Divert, object::RTObject, pointer::Pointer,
-    push_pop::PushPopType, story::Story, story_error::StoryError, value::Value,
-    value_type::ValueType, void::Void,
+    container::Container,
+    divert::Divert,
+    object::RTObject,
+    pointer::Pointer,
+    push_pop::PushPopType,
+    story::Story,
+    story_error::{RuntimeErrorKind, StoryError},
+    threadsafe::{BrCell, BrSync, Brc},
+    value::Value,
+    value_type::ValueType,
+    void::Void,
 };
 
 /// Defines the method callback implementing an external function.
-pub trait ExternalFunction {
+pub trait ExternalFunction: BrSync {
     fn call(&mut self, func_name: &str, args: Vec<ValueType>) -> Option<ValueType>;
 }
 
+/// Like [`ExternalFunction`], but the call can fail. The error is reported as
+/// an ink runtime error, with the current ink path, to the
+/// [`ErrorHandler`](crate::story::errors::ErrorHandler), just like errors in
+/// the ink code itself.
+pub trait FallibleExternalFunction: BrSync {
+    fn call(&mut self, func_name: &str, args: Vec<ValueType>) -> Result<Option<ValueType>, String>;
+}
+
+/// A closure that can be bound with [`Story::bind_fn`].
+///
+/// Implemented for `FnMut` closures taking from 0 to 8 arguments. Each
+/// argument is converted from the ink value with `TryFrom<&ValueType>`, so
+/// `bool`, `i32`, `f32`, `String`, `InkList` and `ValueType` itself are
+/// accepted. The return value is converted with [`ExternalFnReturn`].
+pub trait ExternalFn<Args>: BrSync + 'static {
+    /// The number of arguments of the closure.
+    const ARITY: usize;
+
+    fn call_with_args(
+        &mut self,
+        func_name: &str,
+        args: &[ValueType],
+    ) -> Result<Option<ValueType>, StoryError>;
+}
+
+/// Return types of the closures bound with [`Story::bind_fn`]: `()`, any
+/// type convertible into a [`ValueType`], an `Option` of it, or a `Result`
+/// of any of them. An `Err` is reported as an ink runtime error.
+pub trait ExternalFnReturn {
+    fn into_return_value(self) -> Result<Option<ValueType>, String>;
+}
+
+impl ExternalFnReturn for () {
+    fn into_return_value(self) -> Result<Option<ValueType>, String> {
+        Ok(None)
+    }
+}
+
+impl<T: Into<ValueType>> ExternalFnReturn for T {
+    fn into_return_value(self) -> Result<Option<ValueType>, String> {
+        Ok(Some(self.into()))
+    }
+}
+
+impl<T: Into<ValueType>> ExternalFnReturn for Option<T> {
+    fn into_return_value(self) -> Result<Option<ValueType>, String> {
+        Ok(self.map(Into::into))
+    }
+}
+
+impl<T: ExternalFnReturn, E: Display> ExternalFnReturn for Result<T, E> {
+    fn into_return_value(self) -> Result<Option<ValueType>, String> {
+        self.map_err(|e| e.to_string())?.into_return_value()
+    }
+}
+
+impl TryFrom<&ValueType> for ValueType {
+    type Error = ();
+    fn try_from(value: &ValueType) -> Result<Self, Self::Error> {
+        Ok(value.clone())
+    }
+}
+
+fn convert_arg<T>(func_name: &str, index: usize, arg: &ValueType) -> Result<T, StoryError>
+where
+    T: for<'a> TryFrom<&'a ValueType>,
+{
+    T::try_from(arg).map_err(|_| {
+        StoryError::BadArgument(format!(
+            "External function '{}' can't accept a {} as argument {}",
+            func_name,
+            arg.get_type_name(),
+            index + 1
+        ))
+    })
+}
+
+fn external_function_error(func_name: &str, message: &str) -> StoryError {
+    StoryError::InvalidStoryState(format!(
+        "External function '{}' failed: {}",
+        func_name, message
+    ))
+}
+
+fn bad_arity_error(func_name: &str, arity: usize, number_of_arguments: usize) -> StoryError {
+    StoryError::BadArgument(format!(
+        "External function '{}' expects {} argument{} but ink calls it with {}",
+        func_name,
+        arity,
+        if arity == 1 { "" } else { "s" },
+        number_of_arguments
+    ))
+}
+
+macro_rules! impl_external_fn {
+    ($arity:expr; $($arg:ident),*) => {
+        impl<F, R, $($arg,)*> ExternalFn<($($arg,)*)> for F
+        where
+            F: FnMut($($arg),*) -> R + BrSync + 'static,
+            R: ExternalFnReturn,
+            $($arg: for<'a> TryFrom<&'a ValueType>,)*
+        {
+            const ARITY: usize = $arity;
+
+            #[allow(non_snake_case, unused_variables, unused_mut)]
+            fn call_with_args(
+                &mut self,
+                func_name: &str,
+                args: &[ValueType],
+            ) -> Result<Option<ValueType>, StoryError> {
+                let mut args = args.iter().enumerate();
+                $(
+                    let (i, arg) = args.next().unwrap();
+                    let $arg: $arg = convert_arg(func_name, i, arg)?;
+                )*
+
+                (self)($($arg),*)
+                    .into_return_value()
+                    .map_err(|e| external_function_error(func_name, &e))
+            }
+        }
+    };
+}
+
+impl_external_fn!(0;);
+impl_external_fn!(1; A1);
+impl_external_fn!(2; A1, A2);
+impl_external_fn!(3; A1, A2, A3);
+impl_external_fn!(4; A1, A2, A3, A4);
+impl_external_fn!(5; A1, A2, A3, A4, A5);
+impl_external_fn!(6; A1, A2, A3, A4, A5, A6);
+impl_external_fn!(7; A1, A2, A3, A4, A5, A6, A7);
+impl_external_fn!(8; A1, A2, A3, A4, A5, A6, A7, A8);
+
+/// The two ways of binding an external function, behind a common interface.
+trait ExternalFunctionHandler: BrSync {
+    fn call(
+        &mut self,
+        func_name: &str,
+        args: Vec<ValueType>,
+    ) -> Result<Option<ValueType>, StoryError>;
+
+    /// Number of arguments expected, if known.
+    fn arity(&self) -> Option<usize> {
+        None
+    }
+}
+
+struct TraitHandler(Brc<BrCell<dyn ExternalFunction>>);
+
+impl ExternalFunctionHandler for TraitHandler {
+    fn call(
+        &mut self,
+        func_name: &str,
+        args: Vec<ValueType>,
+    ) -> Result<Option<ValueType>, StoryError> {
+        Ok(self.0.borrow_mut().call(func_name, args))
+    }
+}
+
+struct FallibleTraitHandler(Brc<BrCell<dyn FallibleExternalFunction>>);
+
+impl ExternalFunctionHandler for FallibleTraitHandler {
+    fn call(
+        &mut self,
+        func_name: &str,
+        args: Vec<ValueType>,
+    ) -> Result<Option<ValueType>, StoryError> {
+        self.0
+            .borrow_mut()
+            .call(func_name, args)
+            .map_err(|e| external_function_error(func_name, &e))
+    }
+}
+
+struct ClosureHandler<F, Args> {
+    function: F,
+    _args: PhantomData<fn(Args)>,
+}
+
+impl<F: ExternalFn<Args>, Args: 'static> ExternalFunctionHandler for ClosureHandler<F, Args> {
+    fn call(
+        &mut self,
+        func_name: &str,
+        args: Vec<ValueType>,
+    ) -> Result<Option<ValueType>, StoryError> {
+        if args.len() != F::ARITY {
+            return Err(bad_arity_error(func_name, F::ARITY, args.len()));
+        }
+
+        self.function.call_with_args(func_name, &args)
+    }
+
+    fn arity(&self) -> Option<usize> {
+        Some(F::ARITY)
+    }
+}
+
+/// Shared with the forks of the story, see [`Story::fork`].
+#[derive(Clone)]
 pub(crate) struct ExternalFunctionDef {
-    function: Rc<RefCell<dyn ExternalFunction>>,
+    /// `None` for the async functions, resolved later by the host.
+    function: Option<Brc<BrCell<dyn ExternalFunctionHandler>>>,
     lookahead_safe: bool,
 }
 
+/// A call to an async external function waiting for its result, see
+/// [`Story::bind_async_external_function`].
+#[derive(Clone)]
+pub struct PendingExternalCall {
+    /// The name of the called function.
+    pub func_name: String,
+    /// The arguments of the call.
+    pub args: Vec<ValueType>,
+}
+
+impl PendingExternalCall {
+    pub(crate) fn args_as_rt_objs(&self) -> Vec<Brc<dyn RTObject>> {
+        self.args
+            .iter()
+            .map(|arg| Brc::new(Value::new_value_type(arg.clone())) as Brc<dyn RTObject>)
+            .collect()
+    }
+
+    pub(crate) fn from_rt_objs(
+        func_name: &str,
+        args: Vec<Brc<dyn RTObject>>,
+    ) -> Result<PendingExternalCall, StoryError> {
+        let args = args
+            .into_iter()
+            .map(|arg| match arg.into_any().downcast::<Value>() {
+                Ok(value) => Ok(value.value.clone()),
+                Err(_) => Err(StoryError::InvalidStoryState(format!(
+                    "The arguments of the pending call to '{}' are not values.",
+                    func_name
+                ))),
+            })
+            .collect::<Result<Vec<ValueType>, StoryError>>()?;
+
+        Ok(PendingExternalCall {
+            func_name: func_name.to_owned(),
+            args,
+        })
+    }
+}
+
 /// # External Functions
 /// Methods dealing with external function call handlers that will be called
 /// while [`Story`] is processing.
@@ -49,8 +298,174 @@ impl Story {
     pub fn bind_external_function(
         &mut self,
         func_name: &str,
-        function: Rc<RefCell<dyn ExternalFunction>>,
+        function: Brc<BrCell<dyn ExternalFunction>>,
+        lookahead_safe: bool,
+    ) -> Result<(), StoryError> {
+        self.bind_external_function_handler(
+            func_name,
+            Brc::new(BrCell::new(TraitHandler(function))),
+            lookahead_safe,
+        )
+    }
+
+    /// Bind a [`FallibleExternalFunction`] to an ink `EXTERNAL` function
+    /// declaration. When the call returns an error, it's reported as an ink
+    /// runtime error at the current ink path and the story stops, see
+    /// [`set_error_handler`](Story::set_error_handler).
+    ///
+    /// See [`bind_external_function`](Story::bind_external_function) for the
+    /// meaning of the arguments.
+    pub fn bind_fallible_external_function(
+        &mut self,
+        func_name: &str,
+        function: Brc<BrCell<dyn FallibleExternalFunction>>,
+        lookahead_safe: bool,
+    ) -> Result<(), StoryError> {
+        self.bind_external_function_handler(
+            func_name,
+            Brc::new(BrCell::new(FallibleTraitHandler(function))),
+            lookahead_safe,
+        )
+    }
+
+    /// Bind a Rust closure to an ink `EXTERNAL` function declaration.
+    ///
+    /// The ink arguments are converted to the types of the closure
+    /// parameters, and the returned value back to an ink value:
+    ///
+    /// ```
+    /// # use bladeink::{story::Story, story_error::StoryError};
+    /// # fn main() -> Result<(), StoryError> {
+    /// # let json_string = r##"{"inkVersion":21, "root":["done",null],"listDefs":{}}"##;
+    /// let mut story = Story::new(json_string)?;
+    ///
+    /// story.bind_fn("is_even", |n: i32| n % 2 == 0)?;
+    /// story.bind_fn("greet", |name: String, times: i32| name.repeat(times as usize))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// If ink calls the function with a different number of arguments, or
+    /// with an argument that can't be converted, the story fails with
+    /// [`StoryError::BadArgument`], also when an error handler is set.
+    ///
+    /// The closure can also return a `Result`. An `Err` is reported as an
+    /// ink runtime error like the ones of
+    /// [`bind_fallible_external_function`](Story::bind_fallible_external_function).
+    ///
+    /// The function is bound as lookahead safe, see
+    /// [`bind_external_function`](Story::bind_external_function). Use
+    /// [`bind_fn_lookahead_unsafe`](Story::bind_fn_lookahead_unsafe) for
+    /// functions with side effects.
+    pub fn bind_fn<Args: 'static>(
+        &mut self,
+        func_name: &str,
+        function: impl ExternalFn<Args>,
+    ) -> Result<(), StoryError> {
+        self.bind_external_function_handler(
+            func_name,
+            Brc::new(BrCell::new(ClosureHandler {
+                function,
+                _args: PhantomData,
+            })),
+            true,
+        )
+    }
+
+    /// Like [`bind_fn`](Story::bind_fn), but the function is not lookahead
+    /// safe, so it's only called once the story reaches it.
+    pub fn bind_fn_lookahead_unsafe<Args: 'static>(
+        &mut self,
+        func_name: &str,
+        function: impl ExternalFn<Args>,
+    ) -> Result<(), StoryError> {
+        self.bind_external_function_handler(
+            func_name,
+            Brc::new(BrCell::new(ClosureHandler {
+                function,
+                _args: PhantomData,
+            })),
+            false,
+        )
+    }
+
+    /// Bind an ink `EXTERNAL` function declaration to a function whose
+    /// result is not known until later, e.g. because it waits for the player
+    /// or for I/O.
+    ///
+    /// When ink calls the function the story stops:
+    /// [`cont`](Story::cont) returns the text produced before the call,
+    /// [`can_continue`](Story::can_continue) is `false` and
+    /// [`get_pending_external_call`](Story::get_pending_external_call)
+    /// returns the name and arguments of the call. Once the result is
+    /// known, pass it to
+    /// [`resolve_external_call`](Story::resolve_external_call) and continue
+    /// the story, which resumes right at the call.
+    ///
+    /// The pending call is kept in the saved state, so a story saved while
+    /// waiting can be resolved after loading it. Jumping elsewhere with
+    /// [`choose_path_string`](Story::choose_path_string) abandons the call.
+    ///
+    /// Async functions are never called ahead of time, like the functions
+    /// that are not lookahead safe, and they can't be called while
+    /// generating text, e.g. in choice text or `{func()}`, nor from
+    /// [`evaluate_function`](Story::evaluate_function).
+    pub fn bind_async_external_function(&mut self, func_name: &str) -> Result<(), StoryError> {
+        self.bind_external_function_def(
+            func_name,
+            ExternalFunctionDef {
+                function: None,
+                lookahead_safe: false,
+            },
+        )
+    }
+
+    /// The call to an async external function the story is waiting for, if
+    /// any. See [`bind_async_external_function`](Story::bind_async_external_function).
+    pub fn get_pending_external_call(&self) -> Option<&PendingExternalCall> {
+        self.get_state().pending_external_call.as_ref()
+    }
+
+    /// Sets the result of the pending call to an async external function,
+    /// `None` if it doesn't return a value, so the story can continue.
+    pub fn resolve_external_call(&mut self, result: Option<ValueType>) -> Result<(), StoryError> {
+        self.if_async_we_cant("resolve an external function call")?;
+
+        if self.get_state_mut().pending_external_call.take().is_none() {
+            return Err(StoryError::InvalidStoryState(
+                "There is no pending external function call to resolve.".to_owned(),
+            ));
+        }
+
+        let return_obj: Brc<dyn RTObject> = match result {
+            Some(result) => Brc::new(Value::new_value_type(result)),
+            None => Brc::new(Void::new()),
+        };
+
+        self.get_state_mut().push_evaluation_stack(return_obj);
+
+        Ok(())
+    }
+
+    fn bind_external_function_handler(
+        &mut self,
+        func_name: &str,
+        function: Brc<BrCell<dyn ExternalFunctionHandler>>,
         lookahead_safe: bool,
+    ) -> Result<(), StoryError> {
+        self.bind_external_function_def(
+            func_name,
+            ExternalFunctionDef {
+                function: Some(function),
+                lookahead_safe,
+            },
+        )
+    }
+
+    fn bind_external_function_def(
+        &mut self,
+        func_name: &str,
+        external_function_def: ExternalFunctionDef,
     ) -> Result<(), StoryError> {
         self.if_async_we_cant("bind an external function")?;
 
@@ -60,11 +475,6 @@ impl Story {
             )));
         }
 
-        let external_function_def = ExternalFunctionDef {
-            function,
-            lookahead_safe,
-        };
-
         self.externals
             .insert(func_name.to_string(), external_function_def);
 
@@ -94,6 +504,29 @@ impl Story {
         // Should this function break glue? Abort run if we've already seen a newline.
         // Set a bool to tell it to restore the snapshot at the end of this instruction.
         if let Some(func_def) = self.externals.get(func_name) {
+            if func_def.function.is_none() {
+                if self.get_state().in_string_evaluation() {
+                    self.add_runtime_error(RuntimeErrorKind::ExternalFunction, &format!("Async external function {} can't be called in the middle of string generation, either because choice text is being generated, or because you have ink like \"hello {{func()}}\". You can work around this by generating the result of your function into a temporary variable before the string or choice gets generated: ~ temp x = {}()", func_name, func_name), false);
+
+                    return Ok(());
+                }
+
+                if self.temporary_evaluation_container.is_some()
+                    || self
+                        .get_state()
+                        .get_callstack()
+                        .borrow()
+                        .get_callstack()
+                        .iter()
+                        .any(|e| e.push_pop_type == PushPopType::FunctionEvaluationFromGame)
+                {
+                    return Err(StoryError::InvalidStoryState(format!(
+                        "Async external function '{}' can't be called while evaluating a function or an expression from the game.",
+                        func_name
+                    )));
+                }
+            }
+
             if func_def.lookahead_safe && self.get_state().in_string_evaluation() {
                 // 16th Jan 2023: Example ink that was failing:
                 //
@@ -118,7 +551,7 @@ impl Story {
                 // hence these things are incompatible, you can't have unsafe functions that
                 // cause snapshot rewinding in the middle of string generation.
                 //
-                self.add_error(&format!("External function {} could not be called because 1) it wasn't marked as lookaheadSafe when BindExternalFunction was called and 2) the story is in the middle of string generation, either because choice text is being generated, or because you have ink like \"hello {{func()}}\". You can work around this by generating the result of your function into a temporary variable before the string or choice gets generated: ~ temp x = {}()", func_name, func_name), false);
+                self.add_runtime_error(RuntimeErrorKind::ExternalFunction, &format!("External function {} could not be called because 1) it wasn't marked as lookaheadSafe when BindExternalFunction was called and 2) the story is in the middle of string generation, either because choice text is being generated, or because you have ink like \"hello {{func()}}\". You can work around this by generating the result of your function into a temporary variable before the string or choice gets generated: ~ temp x = {}()", func_name, func_name), false);
 
                 return Ok(());
             }
@@ -175,18 +608,32 @@ impl Story {
         // so they're the right way round again.
         arguments.reverse();
 
-        // Run the function!
-        let func_def = self.externals.get(func_name);
-        let func_result = func_def
-            .unwrap()
-            .function
-            .borrow_mut()
-            .call(func_name, arguments);
+        // Run the function! Or wait for the host to resolve it, if it's async
+        let func_def = self.externals.get(func_name).unwrap();
+        let func_result = match func_def.function.clone() {
+            Some(function) => match function.borrow_mut().call(func_name, arguments) {
+                Ok(func_result) => func_result,
+                Err(StoryError::BadArgument(msg)) => return Err(StoryError::BadArgument(msg)),
+                // The function itself failed
+                Err(e) => {
+                    self.external_function_failed = true;
+                    return Err(e);
+                }
+            },
+            None => {
+                self.get_state_mut().pending_external_call = Some(PendingExternalCall {
+                    func_name: func_name.to_owned(),
+                    args: arguments,
+                });
+
+                return Ok(());
+            }
+        };
 
         // Convert return value (if any) to a type that the ink engine can use
-        let return_obj: Rc<dyn RTObject> = match func_result {
-            Some(func_result) => Rc::new(Value::new_value_type(func_result)),
-            None => Rc::new(Void::new()),
+        let return_obj: Brc<dyn RTObject> = match func_result {
+            Some(func_result) => Brc::new(Value::new_value_type(func_result)),
+            None => Brc::new(Void::new()),
         };
 
         self.get_state_mut().push_evaluation_stack(return_obj);
@@ -229,7 +676,7 @@ impl Story {
 
     fn validate_external_bindings_container(
         &self,
-        c: &Rc<Container>,
+        c: &Brc<Container>,
         missing_externals: &mut std::collections::HashSet<String>,
     ) -> Result<(), StoryError> {
         for inner_content in c.content.iter() {
@@ -264,7 +711,7 @@ impl Story {
 
     fn validate_external_bindings_rtobject(
         &self,
-        o: &Rc<dyn RTObject>,
+        o: &Brc<dyn RTObject>,
         missing_externals: &mut std::collections::HashSet<String>,
     ) -> Result<(), StoryError> {
         let divert = o.clone().into_any().downcast::<Divert>().ok();
@@ -274,6 +721,15 @@ impl Story {
         {
             let name = divert.get_target_path_string().unwrap();
 
+            if let Some(arity) = self
+                .externals
+                .get(&name)
+                .and_then(|f| f.function.as_ref()?.borrow().arity())
+                && arity != divert.external_args
+            {
+                return Err(bad_arity_error(&name, arity, divert.external_args));
+            }
+
             if !self.externals.contains_key(&name) {
                 if self.allow_external_function_fallbacks {
                     let fallback_found = self
@@ -7,6 +7,8 @@ impl Story {
         self.if_async_we_cant("ResetCallstack")?;
 
         self.get_state_mut().force_end();
+        // The ink waiting for the result is gone with the callstack
+        self.get_state_mut().pending_external_call = None;
 
         Ok(())
     }
@@ -22,6 +24,13 @@ impl Story {
             )));
         }
 
+        if let Some(pending_call) = self.get_pending_external_call() {
+            return Err(StoryError::InvalidStoryState(format!(
+                "Can't switch flow to {} while waiting for the result of the external function '{}'",
+                flow_name, pending_call.func_name
+            )));
+        }
+
         self.get_state_mut().switch_flow_internal(flow_name);
 
         Ok(())
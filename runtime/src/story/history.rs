@@ -0,0 +1,85 @@
+use crate::{story::Story, story_error::StoryError};
+
+/// # History
+/// Methods to go back to the state of the story before the last choices.
+///
+/// The history is disabled by default. When enabled with
+/// [`set_history_depth`](Story::set_history_depth), a copy of the state is
+/// kept every time a choice is chosen with
+/// [`choose_choice_index`](Story::choose_choice_index), and
+/// [`rewind`](Story::rewind) goes back to it, with the same choices
+/// available.
+///
+/// The copies are of the whole state, with all its flows, so rewinding also
+/// switches back to the flow that was current when the choice was chosen.
+/// Variable observers are not notified of the variables changed by a rewind.
+/// Loading or resetting the state discards the history.
+impl Story {
+    /// Sets the maximum number of choices that can be rewound. `0`, the
+    /// default, disables the history and discards it.
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history_depth = depth;
+
+        while self.history.len() > depth {
+            self.history.pop_front();
+        }
+    }
+
+    /// The maximum number of choices that can be rewound.
+    pub fn get_history_depth(&self) -> usize {
+        self.history_depth
+    }
+
+    /// The number of choices that can be rewound now.
+    pub fn get_history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Discards the history, keeping it enabled.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Goes back to the state of the story before the last `n` choices were
+    /// chosen. The states after it are discarded.
+    pub fn rewind(&mut self, n: usize) -> Result<(), StoryError> {
+        self.if_async_we_cant("rewind")?;
+
+        if self.async_saving {
+            return Err(StoryError::InvalidStoryState(
+                "Story is in background saving mode, can't rewind".to_owned(),
+            ));
+        }
+
+        if n > self.history.len() {
+            return Err(StoryError::BadArgument(format!(
+                "Can't rewind {} choices, only {} in the history",
+                n,
+                self.history.len()
+            )));
+        }
+
+        if n == 0 {
+            return Ok(());
+        }
+
+        self.history.truncate(self.history.len() - n + 1);
+        self.state = self.history.pop_back().unwrap(); // unwrap: n > 0 checked above
+
+        Ok(())
+    }
+
+    /// Called before choosing a choice, keeps a copy of the current state if
+    /// the history is enabled.
+    pub(crate) fn record_history(&mut self) {
+        if self.history_depth == 0 {
+            return;
+        }
+
+        if self.history.len() == self.history_depth {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(self.get_state().copy_for_history());
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    container::Container,
+    control_command::{CommandType, ControlCommand},
+    divert::Divert,
+    object::RTObject,
+    path::Path,
+    push_pop::PushPopType,
+    story::Story,
+    threadsafe::Brc,
+    variable_assigment::VariableAssignment,
+};
+
+/// Name of the container with the global variable declarations.
+const GLOBAL_DECL: &str = "global decl";
+
+/// # Introspection
+/// Methods to query the structure of the story: its knots, stitches,
+/// functions and external functions.
+///
+/// The compiled story doesn't keep whether a knot was declared as a
+/// function, so functions are told apart by their content: a knot is a
+/// function if it returns a value with `~ return` or if it is called as a
+/// function from anywhere in the story.
+impl Story {
+    /// Names of the knots of the story, sorted. Functions are not included,
+    /// see [`functions`](Story::functions).
+    pub fn knots(&self) -> Vec<String> {
+        let functions = self.function_names();
+
+        self.knot_names()
+            .into_iter()
+            .filter(|name| !functions.contains(name))
+            .collect()
+    }
+
+    /// Names of the stitches of a knot, sorted, or `None` if the knot doesn't
+    /// exist.
+    pub fn stitches_of(&self, knot: &str) -> Option<Vec<String>> {
+        let container = self.knot_container_with_name(knot)?;
+
+        // Choices and gathers at the top level of the knot can also be named
+        // content, but they only count visits at their start
+        let mut stitches: Vec<String> = container
+            .named_content
+            .iter()
+            .filter(|(_, c)| !c.counting_at_start_only)
+            .map(|(name, _)| name.clone())
+            .collect();
+        stitches.sort();
+
+        Some(stitches)
+    }
+
+    /// Names of the functions defined in ink, sorted.
+    pub fn functions(&self) -> Vec<String> {
+        self.function_names().into_iter().collect()
+    }
+
+    /// `true` if there is a knot or function with the given name that can be
+    /// called with [`evaluate_function`](Story::evaluate_function). Like in
+    /// the reference implementation, any knot can be evaluated, so this
+    /// returns `true` for knots too.
+    pub fn has_function(&self, name: &str) -> bool {
+        name != GLOBAL_DECL && self.knot_container_with_name(name).is_some()
+    }
+
+    /// Names of the parameters of a knot, stitch or function, in declaration
+    /// order, or `None` if there isn't a knot, stitch or function at the
+    /// path. Reference parameters are returned without the `ref` keyword.
+    pub fn parameters_of(&self, path: &str) -> Option<Vec<String>> {
+        let p = Path::new_with_components_string(Some(path));
+        if p.is_empty() {
+            return None;
+        }
+
+        let result = self.content_at_path(&p);
+        if result.approximate {
+            return None;
+        }
+
+        let container = result.container()?;
+
+        // Arguments are popped from the evaluation stack at the start of the
+        // container, so the last parameter is assigned first
+        let mut parameters: Vec<String> = container
+            .content
+            .iter()
+            .map_while(|o| {
+                o.as_ref()
+                    .as_any()
+                    .downcast_ref::<VariableAssignment>()
+                    .filter(|va| va.is_new_declaration && !va.is_global)
+                    .map(|va| va.variable_name.clone())
+            })
+            .collect();
+        parameters.reverse();
+
+        Some(parameters)
+    }
+
+    /// `true` if the path points to content of the story, e.g. `knot`,
+    /// `knot.stitch` or `knot.stitch.0`.
+    pub fn path_exists(&self, path: &str) -> bool {
+        let p = Path::new_with_components_string(Some(path));
+
+        !p.is_empty() && !self.content_at_path(&p).approximate
+    }
+
+    /// Names of the external functions called by the story, sorted. These are
+    /// the functions that must be bound before continuing the story, unless
+    /// fallbacks are allowed. `EXTERNAL` declarations that are never called
+    /// are not part of the compiled story.
+    pub fn required_externals(&self) -> Vec<String> {
+        let mut externals = BTreeSet::new();
+
+        visit_content(&self.main_content_container, &mut |o| {
+            if let Ok(divert) = o.clone().into_any().downcast::<Divert>()
+                && divert.is_external
+                && let Some(name) = divert.get_target_path_string()
+            {
+                externals.insert(name);
+            }
+        });
+
+        externals.into_iter().collect()
+    }
+
+    fn knot_names(&self) -> Vec<String> {
+        let mut knots: Vec<String> = self
+            .main_content_container
+            .named_content
+            .keys()
+            .filter(|name| *name != GLOBAL_DECL)
+            .cloned()
+            .collect();
+        knots.sort();
+
+        knots
+    }
+
+    fn function_names(&self) -> BTreeSet<String> {
+        let mut functions = BTreeSet::new();
+
+        // Knots called as functions
+        visit_content(&self.main_content_container, &mut |o| {
+            if let Ok(divert) = o.clone().into_any().downcast::<Divert>()
+                && divert.pushes_to_stack
+                && divert.stack_push_type == PushPopType::Function
+                && let Some(path) = divert.get_target_path()
+                && let Some(name) = path.get_component(0).and_then(|c| c.name.as_ref())
+            {
+                functions.insert(name.clone());
+            }
+        });
+
+        // Knots that return a value
+        for name in self.knot_names() {
+            let container = self.knot_container_with_name(&name).unwrap();
+            let mut returns = false;
+
+            visit_content(&container, &mut |o| {
+                if let Some(cc) = o.as_ref().as_any().downcast_ref::<ControlCommand>()
+                    && cc.command_type == CommandType::PopFunction
+                {
+                    returns = true;
+                }
+            });
+
+            if returns {
+                functions.insert(name);
+            }
+        }
+
+        functions.retain(|name| self.has_function(name));
+
+        functions
+    }
+}
+
+/// Calls `f` for every object inside the container, including the content of
+/// the named subcontainers.
+fn visit_content(c: &Brc<Container>, f: &mut impl FnMut(&Brc<dyn RTObject>)) {
+    for o in c.content.iter() {
+        match o.clone().into_any().downcast::<Container>() {
+            // Named containers are visited below
+            Ok(container) => {
+                if !container.has_valid_name() {
+                    visit_content(&container, f);
+                }
+            }
+            Err(_) => f(o),
+        }
+    }
+
+    for container in c.named_content.values() {
+        visit_content(container, f);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{choice::Choice, story::Story, story_error::StoryError, threadsafe::Brc};
+
+/// A line of text of the story with its tags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    /// The text of the line, usually ending with a new line.
+    pub text: String,
+    /// The tags of the line.
+    pub tags: Vec<String>,
+    /// `true` if the story can't continue after this line, because there
+    /// are choices to choose from or the story has ended.
+    pub is_end_of_turn: bool,
+}
+
+/// What [`Lines`] returns: the lines of the turn and finally its choices.
+#[derive(Clone)]
+pub enum TurnEvent {
+    Line(Line),
+    /// The choices at the end of the turn. Not returned if the story has
+    /// ended.
+    Choices(Vec<Brc<Choice>>),
+}
+
+/// Iterator over the lines of the story until the next choices, returned by
+/// [`Story::lines`]. After an error nothing else is returned.
+pub struct Lines<'a> {
+    story: &'a mut Story,
+    done: bool,
+}
+
+impl Iterator for Lines<'_> {
+    type Item = Result<TurnEvent, StoryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if self.story.can_continue() {
+            let line = self.story.continue_line();
+            self.done = line.is_err();
+
+            return Some(line.map(TurnEvent::Line));
+        }
+
+        self.done = true;
+
+        let choices = self.story.get_current_choices();
+        if choices.is_empty() {
+            None
+        } else {
+            Some(Ok(TurnEvent::Choices(choices)))
+        }
+    }
+}
+
+/// # Lines
+/// Methods to continue the story a line at a time, getting the text and the
+/// tags together.
+impl Story {
+    /// Continues the story for a line of content, like [`cont`](Story::cont),
+    /// and returns its text and tags.
+    pub fn continue_line(&mut self) -> Result<Line, StoryError> {
+        self.continue_async(0.0)?;
+
+        Ok(Line {
+            text: self.get_state_mut().get_current_text(),
+            tags: self.get_state_mut().get_current_tags(),
+            is_end_of_turn: !self.can_continue(),
+        })
+    }
+
+    /// Iterates over the lines of the story until the next choices, which
+    /// are returned as the last event.
+    ///
+    /// ```
+    /// # use bladeink::{story::{Story, lines::TurnEvent}, story_error::StoryError};
+    /// # fn main() -> Result<(), StoryError> {
+    /// # let json_string = r##"{"inkVersion":21,"root":[["^Hello","\n",["done",{"#n":"g-0"}],null],"done",null],"listDefs":{}}"##;
+    /// let mut story = Story::new(json_string)?;
+    ///
+    /// for event in story.lines() {
+    ///     match event? {
+    ///         TurnEvent::Line(line) => print!("{}", line.text),
+    ///         TurnEvent::Choices(choices) => {
+    ///             for choice in choices {
+    ///                 println!("* {}", choice.text);
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lines(&mut self) -> Lines<'_> {
+        Lines {
+            story: self,
+            done: false,
+        }
+    }
+}
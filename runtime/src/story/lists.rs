@@ -0,0 +1,24 @@
+use crate::{ink_list::InkList, story::Story, story_error::StoryError};
+
+/// # Lists
+/// Methods to build list values from code.
+impl Story {
+    /// Creates a list value with items of the list `list_name` declared in
+    /// the story, e.g. `story.list_from_items("colors", &["red", "blue"])`.
+    ///
+    /// Returns [`StoryError::BadArgument`] if the list or any of the items
+    /// are not defined in the story.
+    pub fn list_from_items(
+        &self,
+        list_name: &str,
+        item_names: &[&str],
+    ) -> Result<InkList, StoryError> {
+        InkList::from_items(list_name, item_names, &self.list_definitions)
+    }
+
+    /// Every item of the lists declared in the story that the items of
+    /// `list` belong to, like `LIST_ALL` in ink.
+    pub fn list_all(&self, list: &InkList) -> InkList {
+        list.all(&self.list_definitions)
+    }
+}
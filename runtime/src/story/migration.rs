@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, json};
+
+use crate::{
+    container::Container, flow::Flow, path::Path, story::Story, story_error::StoryError,
+    story_state::DEFAULT_FLOW_NAME, threadsafe::Brc,
+};
+
+/// What to do when the position of a saved state, its callstack, choices or
+/// divert target, doesn't exist in the story anymore.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum MigrationFallback {
+    /// The state is not loaded and an error is returned.
+    #[default]
+    Fail,
+    /// The state is loaded without its flows, which are replaced by the
+    /// default flow starting at the given path, e.g. a knot. Global
+    /// variables, visit counts and turn indices are kept.
+    RestartAt(String),
+}
+
+/// How to load a state saved with an older version of the story, see
+/// [`Story::load_state_with_migration`].
+#[derive(Debug, Clone, Default)]
+pub struct SaveMigration {
+    /// Paths renamed since the state was saved, from the old path to the
+    /// new one. A rename applies to everything inside the path too, so
+    /// renaming a knot also renames its stitches.
+    pub renames: HashMap<String, String>,
+    pub fallback: MigrationFallback,
+}
+
+/// Where a path of a saved state was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SavedPathKind {
+    VisitCount,
+    TurnIndex,
+    /// The position of an element of the callstack.
+    Callstack,
+    /// The target of a current choice.
+    Choice,
+    /// The divert target of the state.
+    DivertTarget,
+    /// A divert target stored in a variable or in the evaluation stack.
+    Variable,
+}
+
+/// A path of a saved state that doesn't exist in the story.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnresolvedPath {
+    pub kind: SavedPathKind,
+    /// The flow the path was found in, for callstack and choice paths.
+    pub flow: Option<String>,
+    /// The path, after the renames.
+    pub path: String,
+}
+
+/// What [`Story::load_state_with_migration`] found and changed in a saved
+/// state.
+#[derive(Debug, Clone, Default)]
+pub struct MigrationReport {
+    /// The renamed paths, from the old path to the new one.
+    pub renamed: Vec<(String, String)>,
+    /// The paths that don't exist in the story. The visit counts and turn
+    /// indices of these paths are discarded, divert targets in variables
+    /// are kept as they are.
+    pub unresolved: Vec<UnresolvedPath>,
+    /// `true` if the position of the state was lost and the story was
+    /// restarted, following [`MigrationFallback::RestartAt`].
+    pub restarted: bool,
+}
+
+impl MigrationReport {
+    /// `true` if the state could be loaded as it was, with only the renames.
+    pub fn is_clean(&self) -> bool {
+        self.unresolved.is_empty()
+    }
+
+    /// `true` if the state has paths that make it lose its position: the
+    /// callstack, the current choices or the divert target.
+    pub fn has_lost_position(&self) -> bool {
+        self.unresolved.iter().any(|p| {
+            matches!(
+                p.kind,
+                SavedPathKind::Callstack | SavedPathKind::Choice | SavedPathKind::DivertTarget
+            )
+        })
+    }
+}
+
+/// # Migration
+/// Methods to load states saved with an older version of the story, whose
+/// knots, stitches or choices may have been renamed or removed.
+impl Story {
+    /// Checks a state saved in JSON format against the current story,
+    /// without loading it. The report lists the paths that would be renamed
+    /// and the ones that don't exist anymore.
+    pub fn check_state(
+        &self,
+        json_state: &str,
+        migration: &SaveMigration,
+    ) -> Result<MigrationReport, StoryError> {
+        let mut j_state = parse_state(json_state)?;
+        Ok(self.migrate_state_json(&mut j_state, migration))
+    }
+
+    /// Loads a state saved in JSON format with an older version of the
+    /// story, renaming its paths with the given renames.
+    ///
+    /// Visit counts and turn indices of paths that don't exist anymore are
+    /// discarded. If the position of the state doesn't exist anymore, the
+    /// [`fallback`](SaveMigration::fallback) is followed.
+    pub fn load_state_with_migration(
+        &mut self,
+        json_state: &str,
+        migration: &SaveMigration,
+    ) -> Result<MigrationReport, StoryError> {
+        self.if_async_we_cant("load a state")?;
+
+        let mut j_state = parse_state(json_state)?;
+        let mut report = self.migrate_state_json(&mut j_state, migration);
+
+        if !report.has_lost_position() {
+            self.load_state_json(j_state)?;
+            return Ok(report);
+        }
+
+        match &migration.fallback {
+            MigrationFallback::Fail => {
+                let paths: Vec<&str> = report
+                    .unresolved
+                    .iter()
+                    .filter(|p| p.kind != SavedPathKind::Variable)
+                    .map(|p| p.path.as_str())
+                    .collect();
+
+                Err(StoryError::BadJson(format!(
+                    "Can't load the state, these paths don't exist in the story: {}",
+                    paths.join(", ")
+                )))
+            }
+            MigrationFallback::RestartAt(restart_path) => {
+                if !self.path_exists(restart_path) {
+                    return Err(StoryError::BadArgument(format!(
+                        "Can't restart the story at '{}', the path doesn't exist",
+                        restart_path
+                    )));
+                }
+
+                let j_obj = j_state.as_object_mut().unwrap(); // unwrap: checked in parse_state
+                let flow = Flow::new(DEFAULT_FLOW_NAME, self.main_content_container.clone());
+                let mut flows = Map::new();
+                flows.insert(DEFAULT_FLOW_NAME.to_owned(), flow.write_json()?);
+                j_obj.insert("flows".to_owned(), serde_json::Value::Object(flows));
+                j_obj.insert("evalStack".to_owned(), json!([]));
+                for key in [
+                    "currentFlowName",
+                    "currentDivertTarget",
+                    "pendingExternalCall",
+                    "callstackThreads",
+                    "currentChoices",
+                    "choiceThreads",
+                    "outputStream",
+                ] {
+                    j_obj.remove(key);
+                }
+
+                self.load_state_json(j_state)?;
+                self.choose_path_string(restart_path, true, None)?;
+                report.restarted = true;
+
+                Ok(report)
+            }
+        }
+    }
+
+    fn load_state_json(&mut self, j_state: serde_json::Value) -> Result<(), StoryError> {
+        self.history.clear();
+        self.get_state_mut().load_json_obj(j_state)
+    }
+
+    /// Renames the paths of the state and removes the counts of the paths
+    /// that don't exist.
+    fn migrate_state_json(
+        &self,
+        j_state: &mut serde_json::Value,
+        migration: &SaveMigration,
+    ) -> MigrationReport {
+        let mut migrator = Migrator {
+            main_content_container: &self.main_content_container,
+            renames: &migration.renames,
+            report: MigrationReport::default(),
+        };
+
+        let j_obj = j_state.as_object_mut().unwrap(); // unwrap: checked in parse_state
+
+        if let Some(flows) = j_obj.get_mut("flows").and_then(|f| f.as_object_mut()) {
+            for (flow_name, flow) in flows.iter_mut() {
+                if let Some(flow) = flow.as_object_mut() {
+                    migrator.migrate_flow(flow_name, flow, "callstack");
+                }
+            }
+        } else {
+            // Old format, the default flow is in the state itself
+            migrator.migrate_flow(DEFAULT_FLOW_NAME, j_obj, "callstackThreads");
+        }
+
+        if let Some(variables) = j_obj.get_mut("variablesState") {
+            migrator.migrate_values(variables);
+        }
+
+        if let Some(eval_stack) = j_obj.get_mut("evalStack") {
+            migrator.migrate_values(eval_stack);
+        }
+
+        if let Some(divert_target) = j_obj.get_mut("currentDivertTarget") {
+            migrator.migrate_path(divert_target, SavedPathKind::DivertTarget, None);
+        }
+
+        for (key, kind) in [
+            ("visitCounts", SavedPathKind::VisitCount),
+            ("turnIndices", SavedPathKind::TurnIndex),
+        ] {
+            if let Some(counts) = j_obj.get_mut(key).and_then(|c| c.as_object_mut()) {
+                migrator.migrate_counts(counts, kind);
+            }
+        }
+
+        migrator.report
+    }
+}
+
+fn parse_state(json_state: &str) -> Result<serde_json::Value, StoryError> {
+    match serde_json::from_str::<serde_json::Value>(json_state) {
+        Ok(value) if value.is_object() => Ok(value),
+        _ => Err(StoryError::BadJson("State not in JSON format.".to_owned())),
+    }
+}
+
+struct Migrator<'a> {
+    main_content_container: &'a Brc<Container>,
+    renames: &'a HashMap<String, String>,
+    report: MigrationReport,
+}
+
+impl Migrator<'_> {
+    fn migrate_flow(
+        &mut self,
+        flow_name: &str,
+        flow: &mut Map<String, serde_json::Value>,
+        callstack_key: &str,
+    ) {
+        if let Some(threads) = flow
+            .get_mut(callstack_key)
+            .and_then(|c| c.get_mut("threads"))
+            .and_then(|t| t.as_array_mut())
+        {
+            for thread in threads {
+                self.migrate_thread(flow_name, thread);
+            }
+        }
+
+        if let Some(choice_threads) = flow
+            .get_mut("choiceThreads")
+            .and_then(|t| t.as_object_mut())
+        {
+            for thread in choice_threads.values_mut() {
+                self.migrate_thread(flow_name, thread);
+            }
+        }
+
+        if let Some(choices) = flow
+            .get_mut("currentChoices")
+            .and_then(|c| c.as_array_mut())
+        {
+            for choice in choices {
+                if let Some(target_path) = choice.get_mut("targetPath") {
+                    self.migrate_path(target_path, SavedPathKind::Choice, Some(flow_name));
+                }
+
+                // Only informative, it isn't checked
+                if let Some(source_path) = choice.get_mut("originalChoicePath")
+                    && let Some(new_path) = source_path.as_str().and_then(|p| self.rename(p))
+                {
+                    *source_path = json!(new_path);
+                }
+            }
+        }
+    }
+
+    fn migrate_thread(&mut self, flow_name: &str, thread: &mut serde_json::Value) {
+        if let Some(elements) = thread.get_mut("callstack").and_then(|c| c.as_array_mut()) {
+            for element in elements {
+                let Some(element) = element.as_object_mut() else {
+                    continue;
+                };
+
+                if let Some(c_path) = element.get_mut("cPath") {
+                    self.migrate_path(c_path, SavedPathKind::Callstack, Some(flow_name));
+                }
+
+                if let Some(temps) = element.get_mut("temp") {
+                    self.migrate_values(temps);
+                }
+            }
+        }
+
+        if let Some(previous_path) = thread.get_mut("previousContentObject") {
+            self.migrate_path(previous_path, SavedPathKind::Callstack, Some(flow_name));
+        }
+    }
+
+    /// Renames the divert targets found in a list or a map of values.
+    fn migrate_values(&mut self, values: &mut serde_json::Value) {
+        let values: Vec<&mut serde_json::Value> = match values {
+            serde_json::Value::Array(a) => a.iter_mut().collect(),
+            serde_json::Value::Object(o) => o.values_mut().collect(),
+            _ => return,
+        };
+
+        for value in values {
+            if let Some(target) = value.get_mut("^->") {
+                self.migrate_path(target, SavedPathKind::Variable, None);
+            }
+        }
+    }
+
+    fn migrate_counts(&mut self, counts: &mut Map<String, serde_json::Value>, kind: SavedPathKind) {
+        let old_counts = std::mem::take(counts);
+
+        for (path, count) in old_counts {
+            let path = match self.rename(&path) {
+                Some(new_path) => new_path,
+                None => path,
+            };
+
+            if self.resolves(&path) {
+                counts.insert(path, count);
+            } else {
+                self.add_unresolved(kind, None, path);
+            }
+        }
+    }
+
+    fn migrate_path(
+        &mut self,
+        j_path: &mut serde_json::Value,
+        kind: SavedPathKind,
+        flow_name: Option<&str>,
+    ) {
+        let Some(path) = j_path.as_str() else {
+            return;
+        };
+
+        let path = match self.rename(path) {
+            Some(new_path) => {
+                *j_path = json!(new_path);
+                new_path
+            }
+            None => path.to_owned(),
+        };
+
+        if !self.resolves(&path) {
+            self.add_unresolved(kind, flow_name.map(str::to_owned), path);
+        }
+    }
+
+    /// The new path for a renamed path, using the longest matching rename.
+    fn rename(&mut self, path: &str) -> Option<String> {
+        let (old_prefix, new_prefix) = self
+            .renames
+            .iter()
+            .filter(|(old, _)| {
+                path == old.as_str()
+                    || (path.starts_with(old.as_str()) && path[old.len()..].starts_with('.'))
+            })
+            .max_by_key(|(old, _)| old.len())?;
+
+        let new_path = format!("{}{}", new_prefix, &path[old_prefix.len()..]);
+
+        let renamed = (path.to_owned(), new_path.clone());
+        if !self.report.renamed.contains(&renamed) {
+            self.report.renamed.push(renamed);
+        }
+
+        Some(new_path)
+    }
+
+    fn resolves(&self, path: &str) -> bool {
+        !self
+            .main_content_container
+            .content_at_path(&Path::new_with_components_string(Some(path)), 0, -1)
+            .approximate
+    }
+
+    fn add_unresolved(&mut self, kind: SavedPathKind, flow: Option<String>, path: String) {
+        let unresolved = UnresolvedPath { kind, flow, path };
+
+        if !self.report.unresolved.contains(&unresolved) {
+            self.report.unresolved.push(unresolved);
+        }
+    }
+}
@@ -2,13 +2,19 @@
 use crate::{
     container::Container,
     list_definitions_origin::ListDefinitionsOrigin,
+    profiler::Profiler,
     story::{
-        errors::ErrorHandler, external_functions::ExternalFunctionDef,
-        variable_observer::VariableObserver,
+        debugger::{DebugMode, DebugStop},
+        errors::ErrorHandler,
+        events::StoryEventListener,
+        external_functions::ExternalFunctionDef,
+        random::StoryRng,
+        variable_observer::{Subscription, VariableObserver},
     },
     story_state::StoryState,
+    threadsafe::{BrCell, Brc},
 };
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::collections::{BTreeSet, HashMap, VecDeque};
 
 /// The current version of the Ink story file format.
 pub const INK_VERSION_CURRENT: i32 = 21;
@@ -26,71 +32,155 @@ pub(crate) enum OutputStateChange {
 /// A `Story` is the core struct representing a complete Ink narrative,
 /// managing evaluation and state.
 pub struct Story {
-    main_content_container: Rc<Container>,
+    main_content_container: Brc<Container>,
     state: StoryState,
-    temporary_evaluation_container: Option<Rc<Container>>,
+    temporary_evaluation_container: Option<Brc<Container>>,
     recursive_continue_count: usize,
     async_continue_active: bool,
     async_saving: bool,
-    prev_containers: Vec<Rc<Container>>,
-    list_definitions: Rc<ListDefinitionsOrigin>,
-    pub(crate) on_error: Option<Rc<RefCell<dyn ErrorHandler>>>,
+    prev_containers: Vec<Brc<Container>>,
+    list_definitions: Brc<ListDefinitionsOrigin>,
+    pub(crate) on_error: Option<Brc<BrCell<dyn ErrorHandler>>>,
     pub(crate) state_snapshot_at_last_new_line: Option<StoryState>,
-    pub(crate) variable_observers: HashMap<String, Vec<Rc<RefCell<dyn VariableObserver>>>>,
+    pub(crate) variable_observers: HashMap<String, Vec<Brc<BrCell<dyn VariableObserver>>>>,
+    pub(crate) variable_subscriptions: Vec<Subscription>,
     pub(crate) has_validated_externals: bool,
     pub(crate) allow_external_function_fallbacks: bool,
     pub(crate) saw_lookahead_unsafe_function_after_new_line: bool,
+    /// Set when the error returned by the last step comes from a failing
+    /// external function, to report it with its own kind.
+    pub(crate) external_function_failed: bool,
     pub(crate) externals: HashMap<String, ExternalFunctionDef>,
+    pub(crate) event_listeners: Vec<Brc<BrCell<dyn StoryEventListener>>>,
+    pub(crate) profiler: Option<Profiler>,
+    pub(crate) breakpoints: BTreeSet<String>,
+    pub(crate) debug_mode: DebugMode,
+    pub(crate) debug_paused: bool,
+    pub(crate) debug_stop: Option<DebugStop>,
+    pub(crate) rng: Brc<BrCell<dyn StoryRng>>,
+    pub(crate) history: VecDeque<StoryState>,
+    pub(crate) history_depth: usize,
 }
 mod misc {
     use crate::{
-        json::{json_read, json_read_stream},
+        compiled_story::CompiledStory,
+        container::Container,
+        list_definitions_origin::ListDefinitionsOrigin,
         object::{Object, RTObject},
         path::Path,
-        story::{INK_VERSION_CURRENT, Story},
-        story_error::StoryError,
+        story::{INK_VERSION_CURRENT, Story, debugger::DebugMode, random::DefaultRng},
+        story_error::{RuntimeErrorKind, StoryError},
         story_state::StoryState,
+        threadsafe::{BrCell, Brc},
         value::Value,
     };
-    use rand::{RngExt, SeedableRng, rngs::StdRng};
-    use std::{collections::HashMap, rc::Rc};
+    use std::{
+        collections::{BTreeSet, HashMap, VecDeque},
+        io::Read,
+    };
 
     impl Story {
         /// Construct a `Story` out of a JSON string that was compiled with
         /// `inklecate`.
+        ///
+        /// To run several stories with the same content, parse it once with
+        /// [`CompiledStory::new`] and use [`Story::from_compiled`].
         pub fn new(json_string: &str) -> Result<Self, StoryError> {
-            let (version, main_content_container, list_definitions) =
-                if cfg!(feature = "stream-json-parser") {
-                    json_read_stream::load_from_string(json_string)?
-                } else {
-                    json_read::load_from_string(json_string)?
-                };
+            Story::from_compiled(&CompiledStory::new(json_string)?)
+        }
+
+        /// Construct a `Story` reading the compiled JSON from a reader, e.g.
+        /// a file.
+        ///
+        /// The streamed JSON parser is used, so the whole JSON text is never
+        /// held in memory.
+        pub fn from_reader(reader: impl Read) -> Result<Self, StoryError> {
+            Story::from_compiled(&CompiledStory::from_reader(reader)?)
+        }
+
+        /// Construct a `Story` out of a binary story, see
+        /// [`CompiledStory::to_binary`].
+        pub fn from_binary(data: &[u8]) -> Result<Self, StoryError> {
+            Story::from_compiled(&CompiledStory::from_binary(data)?)
+        }
 
-            let mut story = Story {
-                main_content_container: main_content_container.clone(),
-                state: StoryState::new(main_content_container.clone(), list_definitions.clone()),
+        /// Construct a `Story` that shares the content of an already parsed
+        /// [`CompiledStory`]. Only a fresh state is allocated.
+        pub fn from_compiled(compiled: &CompiledStory) -> Result<Self, StoryError> {
+            let version = compiled.version;
+            let main_content_container = compiled.main_content_container.clone();
+            let list_definitions = compiled.list_definitions.clone();
+
+            let state = StoryState::new(main_content_container.clone(), list_definitions.clone());
+            let mut story = Story::with_state(main_content_container, list_definitions, state);
+
+            story.reset_globals()?;
+
+            if version != INK_VERSION_CURRENT {
+                story.add_runtime_error(RuntimeErrorKind::VersionMismatch, &format!("WARNING: Version of ink used to build story ({}) doesn't match current version ({}) of engine. Non-critical, but recommend synchronising.", version, INK_VERSION_CURRENT), true);
+            }
+
+            Ok(story)
+        }
+
+        /// Creates an independent copy of the story to explore other
+        /// branches. The content is shared and only the state is copied,
+        /// which is much faster than saving and loading the state.
+        ///
+        /// The external functions and the random number generator are shared
+        /// with the fork, functions can be bound and unbound in the fork
+        /// without affecting the original story. Variable observers, event
+        /// listeners, error handler, profiler, breakpoints and history are not
+        /// copied.
+        pub fn fork(&self) -> Result<Story, StoryError> {
+            self.if_async_we_cant("fork the story")?;
+
+            let mut story = Story::with_state(
+                self.main_content_container.clone(),
+                self.list_definitions.clone(),
+                self.get_state().copy_for_history(),
+            );
+
+            story.externals = self.externals.clone();
+            story.allow_external_function_fallbacks = self.allow_external_function_fallbacks;
+            story.rng = self.rng.clone();
+
+            Ok(story)
+        }
+
+        fn with_state(
+            main_content_container: Brc<Container>,
+            list_definitions: Brc<ListDefinitionsOrigin>,
+            state: StoryState,
+        ) -> Story {
+            Story {
+                main_content_container,
+                state,
                 temporary_evaluation_container: None,
                 recursive_continue_count: 0,
                 async_continue_active: false,
                 async_saving: false,
                 saw_lookahead_unsafe_function_after_new_line: false,
+                external_function_failed: false,
                 state_snapshot_at_last_new_line: None,
                 on_error: None,
                 prev_containers: Vec::new(),
                 list_definitions,
                 variable_observers: HashMap::with_capacity(0),
+                variable_subscriptions: Vec::new(),
                 has_validated_externals: false,
                 allow_external_function_fallbacks: false,
                 externals: HashMap::with_capacity(0),
-            };
-
-            story.reset_globals()?;
-
-            if version != INK_VERSION_CURRENT {
-                story.add_error(&format!("WARNING: Version of ink used to build story ({}) doesn't match current version ({}) of engine. Non-critical, but recommend synchronising.", version, INK_VERSION_CURRENT), true);
+                event_listeners: Vec::new(),
+                profiler: None,
+                breakpoints: BTreeSet::new(),
+                debug_mode: DebugMode::Off,
+                debug_paused: false,
+                debug_stop: None,
+                rng: Brc::new(BrCell::new(DefaultRng::new())),
+                history: VecDeque::new(),
+                history_depth: 0,
             }
-
-            Ok(story)
         }
 
         /// Creates a string representing the hierarchy of objects and
@@ -108,7 +198,7 @@ mod misc {
             sb
         }
 
-        pub(crate) fn is_truthy(&self, obj: Rc<dyn RTObject>) -> Result<bool, StoryError> {
+        pub(crate) fn is_truthy(&self, obj: Brc<dyn RTObject>) -> Result<bool, StoryError> {
             let truthy = false;
 
             if let Some(val) = obj.as_ref().as_any().downcast_ref::<Value>() {
@@ -157,20 +247,20 @@ mod misc {
             // - How many times the runtime has looped around this full shuffle
             let seq_path_str = Object::get_path(seq_container.as_ref()).to_string();
             let sequence_hash: i32 = seq_path_str.chars().map(|c| c as i32).sum();
-            let random_seed = sequence_hash + loop_index + self.get_state().story_seed;
+            let random_seed = sequence_hash
+                .wrapping_add(loop_index)
+                .wrapping_add(self.get_state().story_seed);
 
-            let mut rng = StdRng::seed_from_u64(random_seed as u64);
+            let randoms = self.random_numbers(random_seed, iteration_index as usize + 1);
 
             let mut unpicked_indices: Vec<i32> = (0..num_elements).collect();
 
-            for i in 0..=iteration_index {
-                let chosen = rng
-                    .random::<i32>()
-                    .rem_euclid(unpicked_indices.len() as i32);
+            for (i, next_random) in randoms.into_iter().enumerate() {
+                let chosen = (next_random as i32).rem_euclid(unpicked_indices.len() as i32);
                 let chosen_index = unpicked_indices[chosen as usize];
                 unpicked_indices.retain(|&x| x != chosen_index);
 
-                if i == iteration_index {
+                if i == iteration_index as usize {
                     return Ok(chosen_index);
                 }
             }
@@ -184,11 +274,21 @@ mod misc {
 
 mod choices;
 mod control_logic;
+pub mod debugger;
 pub mod errors;
+pub mod events;
 pub mod external_functions;
 mod flow;
+mod history;
+mod introspection;
+pub mod lines;
+mod lists;
+pub mod migration;
 mod navigation;
+pub mod preview;
+mod profiling;
 mod progress;
-mod state;
+pub mod random;
+pub mod state;
 mod tags;
 pub mod variable_observer;
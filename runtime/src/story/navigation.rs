@@ -7,14 +7,14 @@ use crate::{
     search_result::SearchResult,
     story::Story,
     story_error::StoryError,
+    threadsafe::Brc,
     value_type::ValueType,
 };
-use std::rc::Rc;
 
 /// # Navigation
 /// Methods to access specific sections of the story.
 impl Story {
-    pub(crate) fn get_main_content_container(&self) -> Rc<Container> {
+    pub(crate) fn get_main_content_container(&self) -> Brc<Container> {
         match self.temporary_evaluation_container.as_ref() {
             Some(c) => c.clone(),
             None => self.main_content_container.clone(),
@@ -58,8 +58,10 @@ impl Story {
     /// the middle of a tunnel, it'll redirect only the inner-most
     /// tunnel, meaning that when you tunnel-return using `->->`,
     /// it'll return to where you were before. This may be what you
-    /// want though. However, if you're in the middle of a function,
-    /// `choose_path_string` will throw an error.
+    /// want though. However, if you're in the middle of a function, or
+    /// waiting for the result of an async external function,
+    /// `choose_path_string` will throw an error. Resetting the call stack
+    /// abandons the pending call to an async external function, if any.
     pub fn choose_path_string(
         &mut self,
         path: &str,
@@ -68,9 +70,18 @@ impl Story {
     ) -> Result<(), StoryError> {
         self.if_async_we_cant("call ChoosePathString right now")?;
 
+        self.notify_choose_path_string(path, args.map(|a| a.as_slice()).unwrap_or_default());
+
         if reset_call_stack {
             self.reset_callstack()?;
         } else {
+            if let Some(pending_call) = self.get_pending_external_call() {
+                return Err(StoryError::InvalidStoryState(format!(
+                    "Can't call ChoosePathString({}) without resetting the call stack while waiting for the result of the external function '{}'",
+                    path, pending_call.func_name
+                )));
+            }
+
             // ChoosePathString is potentially dangerous since you can call it when the
             // stack is
             // pretty much in any state. Let's catch one of the worst offenders.
@@ -142,6 +153,10 @@ impl Story {
             return Err(StoryError::BadArgument(e));
         }
 
+        let args_slice = args.map(|a| a.as_slice()).unwrap_or_default();
+        self.notify_evaluate_function(func_name, args_slice);
+        let text_output_start = text_output.len();
+
         // Snapshot the output stream
         let output_stream_before = self.get_state().get_output_stream().clone();
         self.get_state_mut().reset_output(None);
@@ -163,8 +178,18 @@ impl Story {
             .reset_output(Some(output_stream_before));
 
         // Finish evaluation, and see whether anything was produced
-        self.get_state_mut()
-            .complete_function_evaluation_from_game()
+        let result = self
+            .get_state_mut()
+            .complete_function_evaluation_from_game()?;
+
+        self.notify_complete_evaluate_function(
+            func_name,
+            args_slice,
+            &text_output[text_output_start..],
+            result.as_ref(),
+        );
+
+        Ok(result)
     }
 
     pub(crate) fn visit_changed_containers_due_to_divert(&mut self) {
@@ -217,7 +242,7 @@ impl Story {
             if !self
                 .prev_containers
                 .iter()
-                .any(|e| Rc::ptr_eq(e, &current_container))
+                .any(|e| Brc::ptr_eq(e, &current_container))
                 || current_container.counting_at_start_only
             {
                 // Check whether this ancestor container is being entered at the start,
@@ -226,7 +251,7 @@ impl Story {
                     .content
                     .first()
                     .map(|first_child| {
-                        Rc::ptr_eq(first_child, &current_child_of_container)
+                        Brc::ptr_eq(first_child, &current_child_of_container)
                             && all_children_entered_at_start
                     })
                     .unwrap_or(false);
@@ -251,7 +276,7 @@ impl Story {
     }
 
     pub(crate) fn pointer_at_path(
-        main_content_container: &Rc<Container>,
+        main_content_container: &Brc<Container>,
         path: &Path,
     ) -> Result<Pointer, StoryError> {
         if path.is_empty() {
@@ -281,9 +306,9 @@ impl Story {
                 result
             };
 
-        let main_container: Rc<dyn RTObject> = main_content_container.clone();
+        let main_container: Brc<dyn RTObject> = main_content_container.clone();
 
-        if Rc::ptr_eq(&result.obj, &main_container) && path_length_to_use > 0 {
+        if Brc::ptr_eq(&result.obj, &main_container) && path_length_to_use > 0 {
             return Err(StoryError::InvalidStoryState(format!(
                 "Failed to find content at path '{}', and no approximation of it was possible.",
                 path
@@ -298,7 +323,7 @@ impl Story {
         Ok(p)
     }
 
-    pub(crate) fn knot_container_with_name(&self, name: &str) -> Option<Rc<Container>> {
+    pub(crate) fn knot_container_with_name(&self, name: &str) -> Option<Brc<Container>> {
         let named_container = self.main_content_container.named_content.get(name);
 
         named_container.cloned()
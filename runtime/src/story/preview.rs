@@ -0,0 +1,159 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    mem,
+};
+
+use crate::{
+    profiler::Profiler,
+    story::{
+        Story,
+        errors::ErrorHandler,
+        events::StoryEventListener,
+        variable_observer::{Subscription, VariableObserver},
+    },
+    story_error::StoryError,
+    story_state::StoryState,
+    threadsafe::{BrCell, Brc},
+    value_type::ValueType,
+};
+
+/// What follows a choice, as returned by [`Story::preview_choice`].
+#[derive(Clone)]
+pub struct ChoicePreview {
+    /// The lines of text that follow the choice, in order.
+    pub lines: Vec<PreviewLine>,
+    /// The global variables that have a different value after the previewed
+    /// lines, with their new values, sorted by name.
+    pub variable_changes: Vec<(String, ValueType)>,
+    /// The texts of the choices offered after the previewed lines. Empty if
+    /// the preview stopped before reaching them.
+    pub choices: Vec<String>,
+}
+
+/// A line of text produced while previewing a choice.
+#[derive(Clone)]
+pub struct PreviewLine {
+    /// The text of the line, ending with a new line.
+    pub text: String,
+    /// The tags of the line.
+    pub tags: Vec<String>,
+}
+
+/// What [`Story::suspend_hooks`] puts aside.
+pub(crate) struct SuspendedHooks {
+    variable_observers: HashMap<String, Vec<Brc<BrCell<dyn VariableObserver>>>>,
+    variable_subscriptions: Vec<Subscription>,
+    event_listeners: Vec<Brc<BrCell<dyn StoryEventListener>>>,
+    on_error: Option<Brc<BrCell<dyn ErrorHandler>>>,
+    profiler: Option<Profiler>,
+    history: VecDeque<StoryState>,
+    history_depth: usize,
+}
+
+/// # Preview
+/// Methods to look ahead without changing the state of the story.
+impl Story {
+    /// Runs the story forward after choosing the choice with the given index,
+    /// up to `max_lines` lines or until the next choices, and returns what
+    /// was produced.
+    ///
+    /// The story runs on a copy of the state, so the real state, including
+    /// the visit counts and the random seed, is left untouched. Variable
+    /// observers, event listeners, the profiler, the history and the error
+    /// handler are not called during the preview, errors are returned
+    /// instead.
+    ///
+    /// Every bound external function reached by the previewed lines is
+    /// really called, with all its side effects on the host. Marking a
+    /// function as lookahead unsafe doesn't prevent it. Don't preview
+    /// choices that call functions whose side effects must only happen
+    /// when the choice is actually taken.
+    pub fn preview_choice(
+        &mut self,
+        choice_index: usize,
+        max_lines: usize,
+    ) -> Result<ChoicePreview, StoryError> {
+        self.if_async_we_cant("preview a choice")?;
+
+        let preview_state = self.get_state().copy_for_history();
+        let real_state = mem::replace(&mut self.state, preview_state);
+
+        let hooks = self.suspend_hooks();
+        let result = self.run_preview(choice_index, max_lines, &real_state);
+        self.resume_hooks(hooks);
+
+        self.state = real_state;
+
+        result
+    }
+
+    /// Puts aside the host callbacks, the profiler and the history, to run
+    /// the story without them.
+    pub(crate) fn suspend_hooks(&mut self) -> SuspendedHooks {
+        SuspendedHooks {
+            variable_observers: mem::take(&mut self.variable_observers),
+            variable_subscriptions: mem::take(&mut self.variable_subscriptions),
+            event_listeners: mem::take(&mut self.event_listeners),
+            on_error: self.on_error.take(),
+            profiler: self.profiler.take(),
+            history: mem::take(&mut self.history),
+            history_depth: mem::replace(&mut self.history_depth, 0),
+        }
+    }
+
+    pub(crate) fn resume_hooks(&mut self, hooks: SuspendedHooks) {
+        self.variable_observers = hooks.variable_observers;
+        self.variable_subscriptions = hooks.variable_subscriptions;
+        self.event_listeners = hooks.event_listeners;
+        self.on_error = hooks.on_error;
+        self.profiler = hooks.profiler;
+        self.history = hooks.history;
+        self.history_depth = hooks.history_depth;
+    }
+
+    fn run_preview(
+        &mut self,
+        choice_index: usize,
+        max_lines: usize,
+        real_state: &StoryState,
+    ) -> Result<ChoicePreview, StoryError> {
+        self.choose_choice_index(choice_index)?;
+
+        let mut lines = Vec::new();
+        while lines.len() < max_lines && self.can_continue() {
+            let line = self.continue_line()?;
+            lines.push(PreviewLine {
+                text: line.text,
+                tags: line.tags,
+            });
+        }
+
+        let choices = if self.can_continue() {
+            Vec::new()
+        } else {
+            self.get_current_choices()
+                .iter()
+                .map(|c| c.text.clone())
+                .collect()
+        };
+
+        let mut variable_changes: Vec<(String, ValueType)> = Vec::new();
+        for name in self.get_state().variables_state.global_variables.keys() {
+            let new_value = self.get_state().variables_state.get(name);
+            let old_value = real_state.variables_state.get(name);
+
+            if let Some(new_value) = new_value
+                && !old_value.is_some_and(|old_value| old_value.same_value(&new_value))
+            {
+                variable_changes.push((name.clone(), new_value));
+            }
+        }
+        variable_changes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(ChoicePreview {
+            lines,
+            variable_changes,
+            choices,
+        })
+    }
+}
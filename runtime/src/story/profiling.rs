@@ -0,0 +1,25 @@
+use crate::{profiler::Profiler, story::Story, story_error::StoryError};
+
+/// # Profiling
+/// Methods to measure where the time is spent while running the story.
+impl Story {
+    /// Starts recording the time spent in every knot, stitch and function of
+    /// the story. Any previous profiling data is discarded.
+    pub fn start_profiling(&mut self) -> Result<(), StoryError> {
+        self.if_async_we_cant("start profiling")?;
+        self.profiler = Some(Profiler::new());
+
+        Ok(())
+    }
+
+    /// Stops profiling and returns the collected data, or `None` if
+    /// profiling wasn't started.
+    pub fn end_profiling(&mut self) -> Option<Profiler> {
+        self.profiler.take()
+    }
+
+    /// The profiler collecting data, if profiling has been started.
+    pub fn get_profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+}
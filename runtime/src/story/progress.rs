@@ -6,13 +6,13 @@ use crate::{
     object::RTObject,
     pointer::{self, Pointer},
     push_pop::PushPopType,
-    story::{OutputStateChange, Story, errors::ErrorType},
-    story_error::StoryError,
+    story::{OutputStateChange, Story, debugger::DebugMode},
+    story_error::{RuntimeErrorKind, StoryError},
+    threadsafe::Brc,
     value::Value,
     value_type::VariablePointerValue,
     void::Void,
 };
-use std::{self, rc::Rc};
 
 /// # Story Progress
 /// Methods to move the story forwards.
@@ -50,9 +50,35 @@ impl Story {
             self.validate_external_bindings()?;
         }
 
+        if let Some(pending_call) = self.get_pending_external_call() {
+            return Err(StoryError::InvalidStoryState(format!(
+                "Can't continue - waiting for the result of the external function '{}', call resolve_external_call first",
+                pending_call.func_name
+            )));
+        }
+
         self.continue_internal(millisecs_limit_async)
     }
 
+    /// `true` if a line started by [`continue_async`](Story::continue_async)
+    /// is still in progress.
+    pub(crate) fn is_continue_in_progress(&self) -> bool {
+        self.async_continue_active
+    }
+
+    /// Abandons a line left in progress by
+    /// [`continue_async`](Story::continue_async). The state is left halfway
+    /// through the line, so it must be replaced afterwards, e.g. by loading
+    /// a saved one.
+    pub(crate) fn abort_continue(&mut self) {
+        if self.state_snapshot_at_last_new_line.is_some() {
+            self.restore_state_snapshot();
+        }
+
+        self.state.variables_state.complete_variable_observation();
+        self.async_continue_active = false;
+    }
+
     pub(crate) fn if_async_we_cant(&self, activity_str: &str) -> Result<(), StoryError> {
         if self.async_continue_active {
             return Err(StoryError::InvalidStoryState(format!(
@@ -72,6 +98,12 @@ impl Story {
 
         self.recursive_continue_count += 1;
 
+        // Resuming after the debugger paused the evaluation
+        let resuming = self.async_continue_active;
+        if self.recursive_continue_count == 1 {
+            self.debug_paused = false;
+        }
+
         // Doing either:
         // - full run through non-async (so not active and don't want to be)
         // - Starting async run-through
@@ -83,6 +115,10 @@ impl Story {
                 ));
             }
 
+            if let Some(profiler) = self.profiler.as_mut() {
+                profiler.pre_continue();
+            }
+
             self.get_state_mut().set_did_safe_exit(false);
 
             self.get_state_mut().reset_output(None);
@@ -105,16 +141,40 @@ impl Story {
 
         let mut output_stream_ends_in_newline = false;
         self.saw_lookahead_unsafe_function_after_new_line = false;
+        let mut steps = 0;
+        let mut host_error = None;
 
         loop {
+            if self.debug_mode != DebugMode::Off && self.check_debug_pause(steps, resuming) {
+                // Keep the line in progress, it will be resumed by the next call
+                self.async_continue_active = true;
+                break;
+            }
+
             match self.continue_single_step() {
                 Ok(r) => output_stream_ends_in_newline = r,
                 Err(e) => {
-                    self.add_error(e.get_message(), false);
+                    let kind = if self.external_function_failed {
+                        self.external_function_failed = false;
+                        RuntimeErrorKind::ExternalFunction
+                    } else {
+                        e.get_runtime_error_kind()
+                    };
+
+                    self.add_runtime_error(kind, e.get_message(), false);
+
+                    // A host function that can't accept the arguments ink
+                    // passes is a bug of the host, returned as it is
+                    if let StoryError::BadArgument(_) = e {
+                        host_error = Some(e);
+                    }
+
                     break;
                 }
             }
 
+            steps += 1;
+
             if output_stream_ends_in_newline {
                 break;
             }
@@ -148,7 +208,8 @@ impl Story {
             }
 
             // Finished a section of content / reached a choice point?
-            if !self.can_continue() {
+            // Waiting for an async external function is neither.
+            if !self.can_continue() && self.get_pending_external_call().is_none() {
                 if self.state.get_callstack().borrow().can_pop_thread() {
                     self.add_error("Thread available to pop, threads should always be flat by the end of evaluation?", false);
                 }
@@ -163,24 +224,26 @@ impl Story {
                         .borrow()
                         .can_pop_type(Some(PushPopType::Tunnel))
                     {
-                        self.add_error("unexpectedly reached end of content. Do you need a '->->' to return from a tunnel?", false);
+                        self.add_runtime_error(RuntimeErrorKind::OutOfContent, "unexpectedly reached end of content. Do you need a '->->' to return from a tunnel?", false);
                     } else if self
                         .state
                         .get_callstack()
                         .borrow()
                         .can_pop_type(Some(PushPopType::Function))
                     {
-                        self.add_error(
+                        self.add_runtime_error(
+                            RuntimeErrorKind::OutOfContent,
                             "unexpectedly reached end of content. Do you need a '~ return'?",
                             false,
                         );
                     } else if !self.get_state().get_callstack().borrow().can_pop() {
-                        self.add_error(
+                        self.add_runtime_error(
+                            RuntimeErrorKind::OutOfContent,
                             "ran out of content. Do you need a '-> DONE' or '-> END'?",
                             false,
                         );
                     } else {
-                        self.add_error("unexpectedly reached end of content for unknown reason. Please debug compiler!", false);
+                        self.add_runtime_error(RuntimeErrorKind::OutOfContent, "unexpectedly reached end of content for unknown reason. Please debug compiler!", false);
                     }
                 }
             }
@@ -193,6 +256,12 @@ impl Story {
             }
 
             self.async_continue_active = false;
+
+            if let Some(profiler) = self.profiler.as_mut() {
+                profiler.post_continue();
+            }
+
+            self.notify_did_continue();
         }
 
         self.recursive_continue_count -= 1;
@@ -204,14 +273,14 @@ impl Story {
             match &self.on_error {
                 Some(on_err) => {
                     if self.get_state().has_error() {
-                        for err in self.get_state().get_current_errors() {
-                            on_err.borrow_mut().error(err, ErrorType::Error);
+                        for err in self.get_state().get_current_runtime_errors() {
+                            on_err.borrow_mut().runtime_error(err);
                         }
                     }
 
                     if self.get_state().has_warning() {
-                        for err in self.get_state().get_current_warnings() {
-                            on_err.borrow_mut().error(err, ErrorType::Warning);
+                        for err in self.get_state().get_current_runtime_warnings() {
+                            on_err.borrow_mut().runtime_error(err);
                         }
                     }
 
@@ -219,6 +288,10 @@ impl Story {
                 }
                 // No error handler: throw for errors, silently discard warnings
                 None => {
+                    if let Some(e) = host_error {
+                        return Err(e);
+                    }
+
                     if self.get_state().has_error() {
                         let mut sb = String::new();
                         sb.push_str("Ink had ");
@@ -255,22 +328,36 @@ impl Story {
             }
         }
 
+        // Also with an error handler, that has been told about it
+        if let Some(e) = host_error {
+            return Err(e);
+        }
+
         // Send out variable observation events at the last second, since it might trigger new ink to be run
         if let Some(changed) = changed_variables_to_observe {
-            for (variable_name, value) in changed {
-                self.notify_variable_changed(&variable_name, &value);
-            }
+            self.notify_variables_changed(changed);
         }
 
         Ok(())
     }
 
     pub(crate) fn continue_single_step(&mut self) -> Result<bool, StoryError> {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.pre_step();
+        }
+
         // Run main step function (walks through content)
-        self.step()?;
+        let step_result = self.step();
+
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.post_step();
+        }
+
+        step_result?;
 
         // Run out of content and we have a default invisible choice that we can follow?
         if !self.can_continue()
+            && self.get_pending_external_call().is_none()
             && !self
                 .get_state()
                 .get_callstack()
@@ -381,6 +468,10 @@ impl Story {
 
         self.get_state_mut().set_current_pointer(pointer.clone());
 
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.step(&self.state.get_callstack().borrow());
+        }
+
         // Is the current content Object:
         // - Normal content
         // - Or a logic/flow statement - if so, do it
@@ -442,7 +533,7 @@ impl Story {
                     .get_callstack()
                     .borrow()
                     .context_for_variable_named(&var_pointer.variable_name);
-                current_content_obj = Some(Rc::new(Value::new_variable_pointer(
+                current_content_obj = Some(Brc::new(Value::new_variable_pointer(
                     &var_pointer.variable_name,
                     context_idx as i32,
                 )));
@@ -532,7 +623,7 @@ impl Story {
                 // something to chomp on if it needs it
                 if self.get_state().get_in_expression_evaluation() {
                     self.get_state_mut()
-                        .push_evaluation_stack(Rc::new(Void::new()));
+                        .push_evaluation_stack(Brc::new(Void::new()));
                 }
 
                 did_pop = true;
@@ -591,13 +682,13 @@ impl Story {
                 break;
             }
 
-            let rto: Rc<dyn RTObject> = container;
+            let rto: Brc<dyn RTObject> = container;
             let index_in_ancestor = next_ancestor
                 .as_ref()
                 .unwrap()
                 .content
                 .iter()
-                .position(|s| Rc::ptr_eq(s, &rto));
+                .position(|s| Brc::ptr_eq(s, &rto));
             if index_in_ancestor.is_none() {
                 break;
             }
@@ -666,7 +757,7 @@ impl Story {
         OutputStateChange::NoChange
     }
 
-    pub(crate) fn visit_container(&mut self, container: &Rc<Container>, at_start: bool) {
+    pub(crate) fn visit_container(&mut self, container: &Brc<Container>, at_start: bool) {
         if !container.counting_at_start_only || at_start {
             if container.visits_should_be_counted {
                 self.get_state_mut()
@@ -687,7 +778,7 @@ impl Story {
     /// Once [`can_continue`](Story::can_continue) becomes `false`, this
     /// vector will be populated, and is usually (but not always) on the
     /// final [`cont`](Story::cont) step.
-    pub fn get_current_choices(&self) -> Vec<Rc<Choice>> {
+    pub fn get_current_choices(&self) -> Vec<Brc<Choice>> {
         // Don't include invisible choices for external usage.
         let mut choices = Vec::new();
 
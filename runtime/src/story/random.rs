@@ -0,0 +1,76 @@
+use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+use crate::{
+    story::Story,
+    threadsafe::{BrCell, BrSync, Brc},
+};
+
+/// Source of the random numbers used by `RANDOM`, `LIST_RANDOM` and shuffle
+/// sequences.
+///
+/// Randomness in ink is deterministic: every time random numbers are needed
+/// the generator is seeded again with a value derived from the story seed,
+/// so for the same seed it must always return the same sequence of numbers.
+/// Implementing the .NET `System.Random` algorithm gives the same shuffles as
+/// the reference C# runtime.
+pub trait StoryRng: BrSync {
+    /// Restarts the sequence of numbers with the given seed.
+    fn seed(&mut self, seed: i32);
+
+    /// The next number of the sequence.
+    fn next_u32(&mut self) -> u32;
+}
+
+/// The generator used when the host doesn't install one.
+pub(crate) struct DefaultRng {
+    rng: StdRng,
+}
+
+impl DefaultRng {
+    pub(crate) fn new() -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+}
+
+impl StoryRng for DefaultRng {
+    fn seed(&mut self, seed: i32) {
+        self.rng = StdRng::seed_from_u64(seed as u64);
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        self.rng.random::<u32>()
+    }
+}
+
+/// # Random
+/// Methods to control the random numbers of the story.
+impl Story {
+    /// Installs the generator used by `RANDOM`, `LIST_RANDOM` and shuffle
+    /// sequences.
+    pub fn set_rng(&mut self, rng: Brc<BrCell<dyn StoryRng>>) {
+        self.rng = rng;
+    }
+
+    /// Sets the seed of the random numbers, like `SEED_RANDOM` does in ink.
+    /// The seed is stored in the saved state.
+    pub fn set_seed(&mut self, seed: i32) {
+        self.get_state_mut().story_seed = seed;
+        self.get_state_mut().previous_random = 0;
+    }
+
+    /// The seed of the random numbers. A new story starts with a random seed.
+    pub fn get_seed(&self) -> i32 {
+        self.get_state().story_seed
+    }
+
+    /// Seeds the generator and returns the first `count` numbers of the
+    /// sequence.
+    pub(crate) fn random_numbers(&self, seed: i32, count: usize) -> Vec<u32> {
+        let mut rng = self.rng.borrow_mut();
+        rng.seed(seed);
+
+        (0..count).map(|_| rng.next_u32()).collect()
+    }
+}
@@ -1,8 +1,23 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use crate::story_state_snapshot::StoryStateSnapshot;
 use crate::{
     path::Path, story::Story, story_error::StoryError, story_state::StoryState,
     value_type::ValueType,
 };
 
+/// A global variable of the story, as returned by
+/// [`Story::global_variables`].
+#[derive(Clone)]
+pub struct GlobalVariable {
+    pub name: String,
+    /// The current value.
+    pub value: ValueType,
+    /// The value the variable is declared with.
+    pub default_value: ValueType,
+}
+
 /// # State
 /// Methods to read and write story state.
 impl Story {
@@ -56,7 +71,10 @@ impl Story {
             .set(variable_name, value_type.clone())?;
 
         if notify_observers {
-            self.notify_variable_changed(variable_name, value_type);
+            self.notify_variables_changed(HashMap::from([(
+                variable_name.to_owned(),
+                value_type.clone(),
+            )]));
         }
 
         Ok(())
@@ -68,6 +86,53 @@ impl Story {
         self.get_state().variables_state.get(variable_name)
     }
 
+    /// The global variables of the story, sorted by name, with their
+    /// current value and the default value they are declared with.
+    pub fn global_variables(&self) -> Vec<GlobalVariable> {
+        let variables_state = &self.get_state().variables_state;
+
+        let mut variables: Vec<GlobalVariable> = variables_state
+            .default_global_variables
+            .iter()
+            .map(|(name, default_value)| {
+                let value = variables_state
+                    .get(name)
+                    .unwrap_or_else(|| default_value.value.clone());
+
+                GlobalVariable {
+                    name: name.clone(),
+                    value,
+                    default_value: default_value.value.clone(),
+                }
+            })
+            .collect();
+        variables.sort_by(|a, b| a.name.cmp(&b.name));
+
+        variables
+    }
+
+    /// Sets a global variable back to the default value it's declared with.
+    /// Variable observers are notified like with
+    /// [`set_variable`](Story::set_variable).
+    pub fn reset_variable(&mut self, variable_name: &str) -> Result<(), StoryError> {
+        let default_value = match self
+            .get_state()
+            .variables_state
+            .default_global_variables
+            .get(variable_name)
+        {
+            Some(default_value) => default_value.value.clone(),
+            None => {
+                return Err(StoryError::BadArgument(format!(
+                    "Cannot reset variable '{}' because it wasn't declared in the ink story.",
+                    variable_name
+                )));
+            }
+        };
+
+        self.set_variable(variable_name, &default_value)
+    }
+
     pub(crate) fn restore_state_snapshot(&mut self) {
         // Patched state had temporarily hijacked our
         // VariablesState and set its own callstack on it,
@@ -90,10 +155,18 @@ impl Story {
     }
 
     pub(crate) fn state_snapshot(&mut self) {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.pre_snapshot();
+        }
+
         // tmp_state contains the new state and current state is stored in snapshot
         let mut tmp_state = self.state.copy_and_start_patching(false);
         std::mem::swap(&mut tmp_state, &mut self.state);
         self.state_snapshot_at_last_new_line = Some(tmp_state);
+
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.post_snapshot();
+        }
     }
 
     pub(crate) fn discard_snapshot(&mut self) {
@@ -118,13 +191,49 @@ impl Story {
 
     /// Loads a previously saved state in JSON format.
     pub fn load_state(&mut self, json_state: &str) -> Result<(), StoryError> {
+        self.history.clear();
         self.get_state_mut().load_json(json_state)
     }
 
+    /// Exports the current state to a compact binary format, in order to
+    /// save the game. It's smaller and faster to write and load than
+    /// [`save_state`](Story::save_state), but it can only be loaded back by
+    /// this runtime.
+    pub fn save_state_binary(&self) -> Result<Vec<u8>, StoryError> {
+        self.get_state().to_binary()
+    }
+
+    /// Loads a previously saved state in the binary format written by
+    /// [`save_state_binary`](Story::save_state_binary).
+    pub fn load_state_binary(&mut self, data: &[u8]) -> Result<(), StoryError> {
+        self.get_state_mut().load_binary(data)?;
+        self.history.clear();
+
+        Ok(())
+    }
+
+    /// Exports the current state as a [`StoryStateSnapshot`], to embed it
+    /// in a serde based save file or message.
+    #[cfg(feature = "serde")]
+    pub fn save_state_snapshot(&self) -> Result<StoryStateSnapshot, StoryError> {
+        StoryStateSnapshot::from_save_json(self.get_state().write_json()?)
+    }
+
+    /// Loads a state previously exported with
+    /// [`save_state_snapshot`](Story::save_state_snapshot).
+    #[cfg(feature = "serde")]
+    pub fn load_state_snapshot(&mut self, snapshot: &StoryStateSnapshot) -> Result<(), StoryError> {
+        let j_state = snapshot.to_save_json()?;
+        self.history.clear();
+        self.get_state_mut().load_json_obj(j_state)
+    }
+
     /// Reset the Story back to its initial state as it was when it was first constructed.
     pub fn reset_state(&mut self) -> Result<(), StoryError> {
         self.if_async_we_cant("ResetState")?;
 
+        self.history.clear();
+
         self.state = StoryState::new(
             self.main_content_container.clone(),
             self.list_definitions.clone(),
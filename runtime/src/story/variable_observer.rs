@@ -1,13 +1,83 @@
-use std::{cell::RefCell, rc::Rc};
+use std::collections::HashMap;
 
-use crate::{story::Story, story_error::StoryError, value_type::ValueType};
+use crate::{
+    story::Story,
+    story_error::StoryError,
+    threadsafe::{BrCell, BrSync, Brc},
+    value_type::ValueType,
+};
 
 /// Defines the method that will be called when an observed global variable
 /// changes.
-pub trait VariableObserver {
+pub trait VariableObserver: BrSync {
     fn changed(&mut self, variable_name: &str, value: &ValueType);
 }
 
+/// Handle of an observer registered with
+/// [`observe_variable_fn`](Story::observe_variable_fn) or
+/// [`observe_all_variables`](Story::observe_all_variables). The observer is
+/// removed when the handle is dropped, unless it's
+/// [`detach`](VariableSubscription::detach)ed.
+#[must_use = "the observer is removed when the subscription is dropped"]
+pub struct VariableSubscription {
+    active: Brc<BrCell<bool>>,
+    detached: bool,
+}
+
+impl VariableSubscription {
+    /// `true` until the observer is removed.
+    pub fn is_active(&self) -> bool {
+        *self.active.borrow()
+    }
+
+    /// Removes the observer. The same as dropping the handle.
+    pub fn unsubscribe(self) {}
+
+    /// Drops the handle keeping the observer, for as long as the story
+    /// lives.
+    pub fn detach(mut self) {
+        self.detached = true;
+    }
+}
+
+impl Drop for VariableSubscription {
+    fn drop(&mut self) {
+        if !self.detached {
+            *self.active.borrow_mut() = false;
+        }
+    }
+}
+
+/// The observers registered with a [`VariableSubscription`].
+pub(crate) struct Subscription {
+    observer: SubscriptionObserver,
+    active: Brc<BrCell<bool>>,
+}
+
+enum SubscriptionObserver {
+    Variable(String, Box<dyn VariableObserver>),
+    AllVariables(Box<dyn VariablesObserver>),
+}
+
+/// Called with all the global variables changed at once.
+trait VariablesObserver: BrSync {
+    fn changed(&mut self, changes: &[(String, ValueType)]);
+}
+
+struct ClosureObserver<F>(F);
+
+impl<F: FnMut(&str, &ValueType) + BrSync> VariableObserver for ClosureObserver<F> {
+    fn changed(&mut self, variable_name: &str, value: &ValueType) {
+        (self.0)(variable_name, value)
+    }
+}
+
+impl<F: FnMut(&[(String, ValueType)]) + BrSync> VariablesObserver for ClosureObserver<F> {
+    fn changed(&mut self, changes: &[(String, ValueType)]) {
+        (self.0)(changes)
+    }
+}
+
 /// # Variable Observers
 /// Methods dealing with variable observer callbacks that will be called while
 /// the [`Story`] is processing.
@@ -24,7 +94,7 @@ impl Story {
     pub fn observe_variable(
         &mut self,
         variable_name: &str,
-        observer: Rc<RefCell<dyn VariableObserver>>,
+        observer: Brc<BrCell<dyn VariableObserver>>,
     ) -> Result<(), StoryError> {
         self.if_async_we_cant("observe a new variable")?;
 
@@ -43,7 +113,7 @@ impl Story {
                 v.push(observer);
             }
             None => {
-                let v: Vec<Rc<RefCell<dyn VariableObserver>>> = vec![observer];
+                let v: Vec<Brc<BrCell<dyn VariableObserver>>> = vec![observer];
                 self.variable_observers.insert(variable_name.to_string(), v);
             }
         }
@@ -51,13 +121,87 @@ impl Story {
         Ok(())
     }
 
+    /// Like [`observe_variable`](Story::observe_variable), but the observer
+    /// is a closure. It's removed when the returned handle is dropped.
+    ///
+    /// ```
+    /// # use bladeink::{story::Story, story_error::StoryError};
+    /// # fn main() -> Result<(), StoryError> {
+    /// # let json_string = r##"{"inkVersion":21,"root":[[["done",{"#n":"g-0"}],null],"done",{"global decl":["ev",0,{"VAR=":"gold"},"/ev","end",null]}],"listDefs":{}}"##;
+    /// let mut story = Story::new(json_string)?;
+    ///
+    /// let subscription = story.observe_variable_fn("gold", |name, value| {
+    ///     println!("{name} is now {value}");
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn observe_variable_fn(
+        &mut self,
+        variable_name: &str,
+        observer: impl FnMut(&str, &ValueType) + BrSync + 'static,
+    ) -> Result<VariableSubscription, StoryError> {
+        self.if_async_we_cant("observe a new variable")?;
+
+        if !self
+            .get_state()
+            .variables_state
+            .global_variable_exists_with_name(variable_name)
+        {
+            return Err(StoryError::BadArgument(format!(
+                "Cannot observe variable '{variable_name}' because it wasn't declared in the ink story."
+            )));
+        }
+
+        Ok(self.add_subscription(SubscriptionObserver::Variable(
+            variable_name.to_owned(),
+            Box::new(ClosureObserver(observer)),
+        )))
+    }
+
+    /// Observes all the global variables. The observer is called once with
+    /// all the variables changed, sorted by name, at the end of every
+    /// [`cont`](Story::cont), and with the variable set by every
+    /// [`set_variable`](Story::set_variable). It's removed when the returned
+    /// handle is dropped.
+    pub fn observe_all_variables(
+        &mut self,
+        observer: impl FnMut(&[(String, ValueType)]) + BrSync + 'static,
+    ) -> Result<VariableSubscription, StoryError> {
+        self.if_async_we_cant("observe all variables")?;
+
+        Ok(
+            self.add_subscription(SubscriptionObserver::AllVariables(Box::new(
+                ClosureObserver(observer),
+            ))),
+        )
+    }
+
+    fn add_subscription(&mut self, observer: SubscriptionObserver) -> VariableSubscription {
+        self.variable_subscriptions
+            .retain(|subscription| *subscription.active.borrow());
+
+        let active = Brc::new(BrCell::new(true));
+
+        self.variable_subscriptions.push(Subscription {
+            observer,
+            active: active.clone(),
+        });
+
+        VariableSubscription {
+            active,
+            detached: false,
+        }
+    }
+
     /// Removes a variable observer, to stop getting variable change
     /// notifications. If you pass a specific variable name, it will stop
     /// observing that particular one. If you pass None, then the observer
-    /// will be removed from all variables that it's subscribed to.
+    /// will be removed from all variables that it's subscribed to. Removing
+    /// an observer that isn't registered does nothing.
     pub fn remove_variable_observer(
         &mut self,
-        observer: &Rc<RefCell<dyn VariableObserver>>,
+        observer: &Brc<BrCell<dyn VariableObserver>>,
         specific_variable_name: Option<&str>,
     ) -> Result<(), StoryError> {
         self.if_async_we_cant("remove a variable observer")?;
@@ -66,8 +210,7 @@ impl Story {
         match specific_variable_name {
             Some(specific_variable_name) => {
                 if let Some(v) = self.variable_observers.get_mut(specific_variable_name) {
-                    let index = v.iter().position(|x| Rc::ptr_eq(x, observer)).unwrap();
-                    v.remove(index);
+                    v.retain(|x| !Brc::ptr_eq(x, observer));
 
                     if v.is_empty() {
                         self.variable_observers.remove(specific_variable_name);
@@ -76,32 +219,47 @@ impl Story {
             }
             None => {
                 // Remove observer for all variables
-                let mut keys_to_remove = Vec::new();
-
-                for (k, v) in self.variable_observers.iter_mut() {
-                    let index = v.iter().position(|x| Rc::ptr_eq(x, observer)).unwrap();
-                    v.remove(index);
-
-                    if v.is_empty() {
-                        keys_to_remove.push(k.to_string());
-                    }
+                for v in self.variable_observers.values_mut() {
+                    v.retain(|x| !Brc::ptr_eq(x, observer));
                 }
 
-                for key_to_remove in keys_to_remove.iter() {
-                    self.variable_observers.remove(key_to_remove);
-                }
+                self.variable_observers.retain(|_, v| !v.is_empty());
             }
         }
 
         Ok(())
     }
 
-    pub(crate) fn notify_variable_changed(&self, variable_name: &str, value: &ValueType) {
-        let observers = self.variable_observers.get(variable_name);
+    /// Notifies the observers of the given changed variables.
+    pub(crate) fn notify_variables_changed(&mut self, changed: HashMap<String, ValueType>) {
+        let mut changes: Vec<(String, ValueType)> = changed.into_iter().collect();
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (variable_name, value) in changes.iter() {
+            if let Some(observers) = self.variable_observers.get(variable_name) {
+                for o in observers.iter() {
+                    o.borrow_mut().changed(variable_name, value);
+                }
+            }
+        }
+
+        self.variable_subscriptions
+            .retain(|subscription| *subscription.active.borrow());
 
-        if let Some(observers) = observers {
-            for o in observers.iter() {
-                o.borrow_mut().changed(variable_name, value);
+        for subscription in self.variable_subscriptions.iter_mut() {
+            match &mut subscription.observer {
+                SubscriptionObserver::Variable(name, observer) => {
+                    for (variable_name, value) in changes.iter() {
+                        if variable_name == name {
+                            observer.changed(variable_name, value);
+                        }
+                    }
+                }
+                SubscriptionObserver::AllVariables(observer) => {
+                    if !changes.is_empty() {
+                        observer.changed(&changes);
+                    }
+                }
             }
         }
     }
@@ -1,6 +1,8 @@
 //! Errors that happen at runtime, when running a [`Story`](crate::story::Story).
 use core::fmt;
 
+use crate::story::errors::ErrorType;
+
 /// Error that represents an error when running a [`Story`](crate::story::Story) at runtime.
 /// An error of this type typically means there's
 /// a bug in your ink, rather than in the ink engine itself!
@@ -10,6 +12,8 @@ pub enum StoryError {
     InvalidStoryState(String),
     /// JSON for the ink was not valid.
     BadJson(String),
+    /// Binary story or save state data was not valid.
+    BadBinary(String),
     /// A method was called with an inappropriate argument.
     BadArgument(String),
 }
@@ -19,9 +23,17 @@ impl StoryError {
         match self {
             StoryError::InvalidStoryState(msg)
             | StoryError::BadJson(msg)
+            | StoryError::BadBinary(msg)
             | StoryError::BadArgument(msg) => msg.as_str(),
         }
     }
+
+    pub(crate) fn get_runtime_error_kind(&self) -> RuntimeErrorKind {
+        match self {
+            StoryError::BadArgument(_) => RuntimeErrorKind::BadArgument,
+            _ => RuntimeErrorKind::InvalidStoryState,
+        }
+    }
 }
 
 impl std::error::Error for StoryError {}
@@ -37,7 +49,81 @@ impl fmt::Display for StoryError {
         match self {
             StoryError::InvalidStoryState(desc) => write!(f, "Invalid story state: {}", desc),
             StoryError::BadJson(desc) => write!(f, "Error parsing JSON: {}", desc),
+            StoryError::BadBinary(desc) => write!(f, "Error reading binary data: {}", desc),
             StoryError::BadArgument(arg) => write!(f, "Bad argument: {}", arg),
         }
     }
 }
+
+/// The kind of a [`RuntimeError`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RuntimeErrorKind {
+    /// The ink reached an invalid state, e.g. a divert to a missing target
+    /// or an operation with values of the wrong type.
+    InvalidStoryState,
+    /// A value of the wrong type was passed, e.g. an external function was
+    /// called with arguments it can't accept.
+    BadArgument,
+    /// An external function failed or couldn't be called.
+    ExternalFunction,
+    /// The story ran out of content, e.g. a missing `-> END`, `->->` or
+    /// `~ return`.
+    OutOfContent,
+    /// A variable or a container referenced by the ink couldn't be found.
+    NotFound,
+    /// The story was built with a different version of ink.
+    VersionMismatch,
+}
+
+/// An error or warning found while running the ink.
+///
+/// Runtime errors are delivered to the
+/// [`ErrorHandler`](crate::story::errors::ErrorHandler). When there isn't
+/// one, the first error is returned in the message of the [`StoryError`], and
+/// all of them are available with
+/// [`Story::get_current_runtime_errors`](crate::story::Story::get_current_runtime_errors).
+/// Its `Display` is the message used by
+/// [`Story::get_current_errors`](crate::story::Story::get_current_errors),
+/// e.g. `RUNTIME ERROR: (knot.0.3): message`.
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    pub error_type: ErrorType,
+    /// The description of the problem, without the location.
+    pub message: String,
+    /// Path of the ink content that was running.
+    pub path: Option<String>,
+    /// The callstack at the moment of the error, as returned by the callstack
+    /// trace of the story state.
+    pub callstack_trace: Option<String>,
+}
+
+impl RuntimeError {
+    pub(crate) fn new(kind: RuntimeErrorKind, message: String) -> Self {
+        Self {
+            kind,
+            error_type: ErrorType::Error,
+            message,
+            path: None,
+            callstack_trace: None,
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let error_type_str = match self.error_type {
+            ErrorType::Warning => "WARNING",
+            ErrorType::Error => "ERROR",
+        };
+
+        match &self.path {
+            Some(path) => write!(
+                f,
+                "RUNTIME {}: ({}): {}",
+                error_type_str, path, self.message
+            ),
+            None => write!(f, "RUNTIME {}: {}", error_type_str, self.message),
+        }
+    }
+}
@@ -1,6 +1,11 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::collections::HashMap;
 
 use crate::{
+    binary::{
+        self,
+        binary_io::{BinaryReader, BinaryWriter},
+        binary_read, binary_write,
+    },
     callstack::CallStack,
     choice::Choice,
     container::Container,
@@ -15,9 +20,12 @@ use crate::{
     pointer::{self, Pointer},
     push_pop::PushPopType,
     state_patch::StatePatch,
-    story::{INK_VERSION_CURRENT, Story},
-    story_error::StoryError,
+    story::{
+        INK_VERSION_CURRENT, Story, errors::ErrorType, external_functions::PendingExternalCall,
+    },
+    story_error::{RuntimeError, StoryError},
     tag::Tag,
+    threadsafe::{BrCell, Brc},
     value::Value,
     value_type::{StringValue, ValueType},
     variables_state::VariablesState,
@@ -30,7 +38,7 @@ use serde_json::{Map, json};
 pub const INK_SAVE_STATE_VERSION: u32 = 10;
 pub const MIN_COMPATIBLE_LOAD_VERSION: u32 = 8;
 
-static DEFAULT_FLOW_NAME: &str = "DEFAULT_FLOW";
+pub(crate) static DEFAULT_FLOW_NAME: &str = "DEFAULT_FLOW";
 
 pub(crate) struct StoryState {
     pub current_flow: Flow,
@@ -39,10 +47,12 @@ pub(crate) struct StoryState {
     output_stream_tags_dirty: bool,
     pub variables_state: VariablesState,
     alive_flow_names_dirty: bool,
-    pub evaluation_stack: Vec<Rc<dyn RTObject>>,
-    main_content_container: Rc<Container>,
+    pub evaluation_stack: Vec<Brc<dyn RTObject>>,
+    main_content_container: Brc<Container>,
     current_errors: Vec<String>,
     current_warnings: Vec<String>,
+    current_runtime_errors: Vec<RuntimeError>,
+    current_runtime_warnings: Vec<RuntimeError>,
     current_text: Option<String>,
     patch: Option<StatePatch>,
     named_flows: Option<HashMap<String, Flow>>,
@@ -53,13 +63,14 @@ pub(crate) struct StoryState {
     pub story_seed: i32,
     pub previous_random: i32,
     current_tags: Vec<String>,
-    list_definitions: Rc<ListDefinitionsOrigin>,
+    list_definitions: Brc<ListDefinitionsOrigin>,
+    pub pending_external_call: Option<PendingExternalCall>,
 }
 
 impl StoryState {
     pub fn new(
-        main_content_container: Rc<Container>,
-        list_definitions: Rc<ListDefinitionsOrigin>,
+        main_content_container: Brc<Container>,
+        list_definitions: Brc<ListDefinitionsOrigin>,
     ) -> StoryState {
         let current_flow = Flow::new(DEFAULT_FLOW_NAME, main_content_container.clone());
         let callstack = current_flow.callstack.clone();
@@ -78,6 +89,8 @@ impl StoryState {
             main_content_container,
             current_errors: Vec::with_capacity(0),
             current_warnings: Vec::with_capacity(0),
+            current_runtime_errors: Vec::with_capacity(0),
+            current_runtime_warnings: Vec::with_capacity(0),
             current_text: None,
             patch: None,
             named_flows: None,
@@ -89,6 +102,7 @@ impl StoryState {
             previous_random: 0,
             current_tags: Vec::with_capacity(0),
             list_definitions,
+            pending_external_call: None,
         };
 
         state.go_to_start();
@@ -97,7 +111,9 @@ impl StoryState {
     }
 
     pub fn can_continue(&self) -> bool {
-        !self.get_current_pointer().is_null() && !self.has_error()
+        !self.get_current_pointer().is_null()
+            && !self.has_error()
+            && self.pending_external_call.is_none()
     }
 
     pub fn has_error(&self) -> bool {
@@ -129,7 +145,7 @@ impl StoryState {
             .clone()
     }
 
-    pub fn get_callstack(&self) -> &Rc<RefCell<CallStack>> {
+    pub fn get_callstack(&self) -> &Brc<BrCell<CallStack>> {
         &self.current_flow.callstack
     }
 
@@ -137,7 +153,7 @@ impl StoryState {
         self.did_safe_exit = did_safe_exit;
     }
 
-    pub fn reset_output(&mut self, objs: Option<Vec<Rc<dyn RTObject>>>) {
+    pub fn reset_output(&mut self, objs: Option<Vec<Brc<dyn RTObject>>>) {
         self.get_output_stream_mut().clear();
         if let Some(objs) = objs {
             for o in objs {
@@ -147,11 +163,11 @@ impl StoryState {
         self.output_stream_dirty();
     }
 
-    pub fn get_generated_choices_mut(&mut self) -> &mut Vec<Rc<Choice>> {
+    pub fn get_generated_choices_mut(&mut self) -> &mut Vec<Brc<Choice>> {
         &mut self.current_flow.current_choices
     }
 
-    pub fn get_generated_choices(&self) -> &Vec<Rc<Choice>> {
+    pub fn get_generated_choices(&self) -> &Vec<Brc<Choice>> {
         &self.current_flow.current_choices
     }
 
@@ -171,11 +187,19 @@ impl StoryState {
         &self.current_warnings
     }
 
-    pub fn get_output_stream(&self) -> &Vec<Rc<dyn RTObject>> {
+    pub fn get_current_runtime_errors(&self) -> &[RuntimeError] {
+        &self.current_runtime_errors
+    }
+
+    pub fn get_current_runtime_warnings(&self) -> &[RuntimeError] {
+        &self.current_runtime_warnings
+    }
+
+    pub fn get_output_stream(&self) -> &Vec<Brc<dyn RTObject>> {
         &self.current_flow.output_stream
     }
 
-    fn get_output_stream_mut(&mut self) -> &mut Vec<Rc<dyn RTObject>> {
+    fn get_output_stream_mut(&mut self) -> &mut Vec<Brc<dyn RTObject>> {
         &mut self.current_flow.output_stream
     }
 
@@ -362,7 +386,7 @@ impl StoryState {
             .in_expression_evaluation = value;
     }
 
-    pub fn push_evaluation_stack(&mut self, obj: Rc<dyn RTObject>) {
+    pub fn push_evaluation_stack(&mut self, obj: Brc<dyn RTObject>) {
         if let Some(list) = Value::get_value::<&InkList>(obj.as_ref()) {
             let origin_names = list.get_origin_names();
 
@@ -379,7 +403,7 @@ impl StoryState {
         self.evaluation_stack.push(obj);
     }
 
-    pub fn push_to_output_stream(&mut self, obj: Rc<dyn RTObject>) {
+    pub fn push_to_output_stream(&mut self, obj: Brc<dyn RTObject>) {
         let text = Value::get_value::<&StringValue>(obj.as_ref());
 
         if let Some(s) = text {
@@ -387,7 +411,7 @@ impl StoryState {
 
             if let Some(list_text) = list_text {
                 for text_obj in list_text {
-                    self.push_to_output_stream_individual(Rc::new(text_obj));
+                    self.push_to_output_stream_individual(Brc::new(text_obj));
                 }
                 self.output_stream_dirty();
                 return;
@@ -397,7 +421,7 @@ impl StoryState {
         self.push_to_output_stream_individual(obj);
     }
 
-    pub fn increment_visit_count_for_container(&mut self, container: &Rc<Container>) {
+    pub fn increment_visit_count_for_container(&mut self, container: &Brc<Container>) {
         let has_patch = self.patch.is_some();
 
         if has_patch {
@@ -420,7 +444,7 @@ impl StoryState {
         }
     }
 
-    pub fn visit_count_for_container(&mut self, container: &Rc<Container>) -> i32 {
+    pub fn visit_count_for_container(&mut self, container: &Brc<Container>) -> i32 {
         if !container.visits_should_be_counted {
             // TODO
 
@@ -531,7 +555,7 @@ impl StoryState {
         Some(list_texts)
     }
 
-    fn push_to_output_stream_individual(&mut self, obj: Rc<dyn RTObject>) {
+    fn push_to_output_stream_individual(&mut self, obj: Brc<dyn RTObject>) {
         let glue = obj.clone().into_any().downcast::<Glue>();
         let text = Value::get_value::<&StringValue>(obj.as_ref());
         let mut include_in_output = true;
@@ -747,11 +771,13 @@ impl StoryState {
             .current_pointer = Pointer::start_of(self.main_content_container.clone())
     }
 
-    pub fn get_current_choices(&self) -> Option<&Vec<Rc<Choice>>> {
+    pub fn get_current_choices(&self) -> Option<&Vec<Brc<Choice>>> {
         // If we can continue generating text content rather than choices,
         // then we reflect the choice list as being empty, since choices
         // should always come at the end.
-        if self.can_continue() {
+        // The same while waiting for an async external function, the turn
+        // isn't finished yet.
+        if self.can_continue() || self.pending_external_call.is_some() {
             return None;
         }
 
@@ -770,7 +796,7 @@ impl StoryState {
         // If the patch is applied, then this new flow will replace the old one in
         // _namedFlows
         copy.current_flow.name = self.current_flow.name.clone();
-        copy.current_flow.callstack = Rc::new(RefCell::new(
+        copy.current_flow.callstack = Brc::new(BrCell::new(
             self.current_flow.callstack.as_ref().borrow().clone(),
         ));
         copy.current_flow.output_stream = self.current_flow.output_stream.clone();
@@ -787,33 +813,29 @@ impl StoryState {
 
             for choice in self.current_flow.current_choices.iter() {
                 let c = choice.as_ref().clone();
-                copy.current_flow.current_choices.push(Rc::new(c));
+                copy.current_flow.current_choices.push(Brc::new(c));
             }
         } else {
             copy.current_flow.current_choices = self.current_flow.current_choices.clone();
         }
 
-        // The copy of the state has its own copy of the named flows dictionary,
-        // except with the current flow replaced with the copy above
+        // The copy of the state has its own copy of the named flows dictionary.
+        // The current flow is kept out of it, it is the copy above
         // (Assuming we're in multi-flow mode at all. If we're not then
         // the above copy is simply the default flow copy and we're done)
         if self.named_flows.is_some() {
-            let mut nf = self.named_flows.clone();
-            nf.as_mut().unwrap().insert(
-                copy.current_flow.name.to_string(),
-                copy.current_flow.clone(),
-            );
+            copy.named_flows = self.named_flows.clone();
             copy.alive_flow_names_dirty = true;
-
-            copy.named_flows = nf;
         }
 
         if self.has_error() {
             copy.current_errors = self.current_errors.clone();
+            copy.current_runtime_errors = self.current_runtime_errors.clone();
         }
 
         if self.has_warning() {
             copy.current_warnings = self.current_warnings.clone();
+            copy.current_runtime_warnings = self.current_runtime_warnings.clone();
         }
 
         // ref copy - exactly the same variables state!
@@ -825,6 +847,7 @@ impl StoryState {
         copy.variables_state.patch = copy.patch.clone();
 
         copy.evaluation_stack = self.evaluation_stack.clone();
+        copy.pending_external_call = self.pending_external_call.clone();
 
         if !self.diverted_pointer.is_null() {
             copy.diverted_pointer = self.diverted_pointer.clone();
@@ -846,6 +869,23 @@ impl StoryState {
         copy
     }
 
+    /// Copy of the state that doesn't share anything mutable with this one,
+    /// to go back to it later. The callstacks of all the flows are copied
+    /// and any pending patch is applied to the copy.
+    pub fn copy_for_history(&self) -> StoryState {
+        let mut copy = self.copy_and_start_patching(false);
+        copy.apply_any_patch();
+
+        if let Some(named_flows) = copy.named_flows.as_mut() {
+            for flow in named_flows.values_mut() {
+                let callstack = flow.callstack.as_ref().borrow().clone();
+                flow.callstack = Brc::new(BrCell::new(callstack));
+            }
+        }
+
+        copy
+    }
+
     pub fn restore_after_patch(&mut self) {
         // VariablesState was being borrowed by the patched
         // state, so restore it with our own callstack.
@@ -896,16 +936,16 @@ impl StoryState {
         self.output_stream_dirty();
     }
 
-    pub fn pop_evaluation_stack(&mut self) -> Rc<dyn RTObject> {
+    pub fn pop_evaluation_stack(&mut self) -> Brc<dyn RTObject> {
         self.evaluation_stack.pop().unwrap()
     }
 
     pub fn pop_evaluation_stack_multiple(
         &mut self,
         number_of_objects: usize,
-    ) -> Vec<Rc<dyn RTObject>> {
+    ) -> Vec<Brc<dyn RTObject>> {
         let start = self.evaluation_stack.len() - number_of_objects;
-        let obj: Vec<Rc<dyn RTObject>> = self.evaluation_stack.drain(start..).collect();
+        let obj: Vec<Brc<dyn RTObject>> = self.evaluation_stack.drain(start..).collect();
 
         obj
     }
@@ -991,13 +1031,13 @@ impl StoryState {
         }
     }
 
-    pub fn peek_evaluation_stack(&self) -> Option<&Rc<dyn RTObject>> {
+    pub fn peek_evaluation_stack(&self) -> Option<&Brc<dyn RTObject>> {
         self.evaluation_stack.last()
     }
 
     pub fn start_function_evaluation_from_game(
         &mut self,
-        func_container: Rc<Container>,
+        func_container: Brc<Container>,
         arguments: Option<&Vec<ValueType>>,
     ) -> Result<(), StoryError> {
         self.get_callstack().borrow_mut().push(
@@ -1034,7 +1074,7 @@ impl StoryState {
                     }
                 };
 
-                self.push_evaluation_stack(Rc::new(value));
+                self.push_evaluation_stack(Brc::new(value));
             }
         }
 
@@ -1203,7 +1243,7 @@ impl StoryState {
         }
     }
 
-    fn write_json(&self) -> Result<serde_json::Value, StoryError> {
+    pub(crate) fn write_json(&self) -> Result<serde_json::Value, StoryError> {
         let mut obj: Map<String, serde_json::Value> = Map::new();
 
         // Flows
@@ -1259,6 +1299,19 @@ impl StoryState {
         obj.insert("storySeed".to_owned(), json!(self.story_seed));
         obj.insert("previousRandom".to_owned(), json!(self.previous_random));
 
+        if let Some(pending_call) = &self.pending_external_call {
+            let mut call_obj: Map<String, serde_json::Value> = Map::new();
+            call_obj.insert("func".to_owned(), json!(pending_call.func_name));
+            call_obj.insert(
+                "args".to_owned(),
+                json_write::write_list_rt_objs(&pending_call.args_as_rt_objs())?,
+            );
+            obj.insert(
+                "pendingExternalCall".to_owned(),
+                serde_json::Value::Object(call_obj),
+            );
+        }
+
         obj.insert("inkSaveVersion".to_owned(), json!(INK_SAVE_STATE_VERSION));
 
         // Not using this right now, but could do in future.
@@ -1267,7 +1320,7 @@ impl StoryState {
         Ok(serde_json::Value::Object(obj))
     }
 
-    fn load_json_obj(&mut self, j_object: serde_json::Value) -> Result<(), StoryError> {
+    pub(crate) fn load_json_obj(&mut self, j_object: serde_json::Value) -> Result<(), StoryError> {
         let j_save_version = match j_object.get("inkSaveVersion") {
             Some(version) => version,
             None => {
@@ -1437,6 +1490,201 @@ impl StoryState {
             self.previous_random = 0;
         }
 
+        self.pending_external_call = match j_object.get("pendingExternalCall") {
+            Some(call_obj) => {
+                let func_name = call_obj
+                    .get("func")
+                    .and_then(|f| f.as_str())
+                    .ok_or_else(|| {
+                        StoryError::BadJson("Invalid pending external call".to_string())
+                    })?;
+                let args = call_obj
+                    .get("args")
+                    .and_then(|a| a.as_array())
+                    .ok_or_else(|| {
+                        StoryError::BadJson("Invalid pending external call".to_string())
+                    })?;
+
+                Some(PendingExternalCall::from_rt_objs(
+                    func_name,
+                    json_read::jarray_to_runtime_obj_list(args, false)?,
+                )?)
+            }
+            None => None,
+        };
+
+        Ok(())
+    }
+
+    pub fn to_binary(&self) -> Result<Vec<u8>, StoryError> {
+        let mut w = BinaryWriter::new(binary::SAVE_MAGIC, binary::SAVE_FORMAT_VERSION);
+        self.write_binary(&mut w)?;
+        Ok(w.finish())
+    }
+
+    pub fn load_binary(&mut self, data: &[u8]) -> Result<(), StoryError> {
+        let mut r = BinaryReader::new(data, binary::SAVE_MAGIC, binary::SAVE_FORMAT_VERSION)?;
+
+        // Loaded in a new state, so this one is left untouched if the data is
+        // not valid
+        let mut loaded = StoryState::new(
+            self.main_content_container.clone(),
+            self.list_definitions.clone(),
+        );
+        loaded.variables_state.default_global_variables =
+            self.variables_state.default_global_variables.clone();
+
+        loaded.load_binary_obj(&mut r)?;
+
+        if !r.is_at_end() {
+            return Err(StoryError::BadBinary(
+                "Unexpected data after the end of the save state".to_owned(),
+            ));
+        }
+
+        *self = loaded;
+
+        Ok(())
+    }
+
+    fn write_binary(&self, w: &mut BinaryWriter) -> Result<(), StoryError> {
+        w.write_uint(INK_SAVE_STATE_VERSION);
+
+        // Flows, the current one first
+        let mut named_flows: Vec<&Flow> = match &self.named_flows {
+            Some(named_flows) => named_flows
+                .values()
+                .filter(|f| f.name != self.current_flow.name)
+                .collect(),
+            None => Vec::new(),
+        };
+        named_flows.sort_by(|a, b| a.name.cmp(&b.name));
+
+        w.write_len(named_flows.len() + 1);
+
+        for flow in std::iter::once(&self.current_flow).chain(named_flows) {
+            w.write_str(&flow.name);
+            flow.write_binary(w)?;
+        }
+
+        self.variables_state.write_binary(w);
+        binary_write::write_list_rt_objs(w, &self.evaluation_stack)?;
+
+        if self.diverted_pointer.is_null() {
+            w.write_opt_str(None);
+        } else {
+            w.write_opt_str(Some(
+                &self
+                    .diverted_pointer
+                    .get_path()
+                    .unwrap()
+                    .get_components_string(),
+            ));
+        }
+
+        // Counts changed while patching are merged in, like the globals
+        let mut visit_counts = self.visit_counts.clone();
+        let mut turn_indices = self.turn_indices.clone();
+
+        if let Some(patch) = &self.patch {
+            visit_counts.extend(patch.visit_counts.clone());
+            turn_indices.extend(patch.turn_indices.clone());
+        }
+
+        binary_write::write_int_dictionary(w, &visit_counts);
+        binary_write::write_int_dictionary(w, &turn_indices);
+
+        w.write_int(self.current_turn_index);
+        w.write_int(self.story_seed);
+        w.write_int(self.previous_random);
+
+        match &self.pending_external_call {
+            Some(pending_call) => {
+                w.write_opt_str(Some(&pending_call.func_name));
+                binary_write::write_list_rt_objs(w, &pending_call.args_as_rt_objs())?;
+            }
+            None => w.write_opt_str(None),
+        }
+
+        Ok(())
+    }
+
+    fn load_binary_obj(&mut self, r: &mut BinaryReader) -> Result<(), StoryError> {
+        let save_version = r.read_uint()?;
+
+        if save_version < MIN_COMPATIBLE_LOAD_VERSION {
+            return Err(StoryError::BadBinary(format!(
+                "Ink save format isn't compatible with the current version (saw '{}', but minimum is {}), so can't load.",
+                save_version, MIN_COMPATIBLE_LOAD_VERSION
+            )));
+        }
+
+        if save_version > INK_SAVE_STATE_VERSION {
+            return Err(StoryError::BadBinary(format!(
+                "Ink save format is newer than the current version (saw '{}', but current is {}), so can't load.",
+                save_version, INK_SAVE_STATE_VERSION
+            )));
+        }
+
+        let num_flows = r.read_count()?;
+
+        if num_flows == 0 {
+            return Err(StoryError::BadBinary("No flows in save state".to_owned()));
+        }
+
+        let current_flow_name = r.read_string()?;
+        self.current_flow =
+            Flow::from_binary(&current_flow_name, self.main_content_container.clone(), r)?;
+
+        if num_flows == 1 {
+            self.named_flows = None;
+        } else {
+            let mut named_flows = HashMap::with_capacity(num_flows - 1);
+
+            for _ in 1..num_flows {
+                let name = r.read_string()?;
+                let flow = Flow::from_binary(&name, self.main_content_container.clone(), r)?;
+                named_flows.insert(name, flow);
+            }
+
+            self.named_flows = Some(named_flows);
+        }
+
+        self.output_stream_dirty();
+        self.alive_flow_names_dirty = true;
+
+        self.variables_state.load_binary(r)?;
+        self.variables_state
+            .set_callstack(self.current_flow.callstack.clone());
+
+        self.evaluation_stack = binary_read::read_list_rt_objs(r)?;
+
+        self.diverted_pointer = match r.read_opt_string()? {
+            Some(divert_path) => Story::pointer_at_path(
+                &self.main_content_container,
+                &Path::new_with_components_string(Some(&divert_path)),
+            )?,
+            None => pointer::NULL.clone(),
+        };
+
+        self.visit_counts = binary_read::read_int_dictionary(r)?;
+        self.turn_indices = binary_read::read_int_dictionary(r)?;
+
+        self.current_turn_index = r.read_int()?;
+        self.story_seed = r.read_int()?;
+        self.previous_random = r.read_int()?;
+
+        // Added in version 2 of the format
+        self.pending_external_call = None;
+        if r.get_format_version() >= 2
+            && let Some(func_name) = r.read_opt_string()?
+        {
+            self.pending_external_call = Some(PendingExternalCall::from_rt_objs(
+                &func_name,
+                binary_read::read_list_rt_objs(r)?,
+            )?);
+        }
+
         Ok(())
     }
 
@@ -1464,15 +1712,55 @@ impl StoryState {
         }
     }
 
-    pub(crate) fn add_error(&mut self, message: String, is_warning: bool) {
-        if !is_warning {
-            self.current_errors.push(message);
-        } else {
-            self.current_warnings.push(message);
+    pub(crate) fn add_error(&mut self, error: RuntimeError) {
+        match error.error_type {
+            ErrorType::Error => {
+                self.current_errors.push(error.to_string());
+                self.current_runtime_errors.push(error);
+            }
+            ErrorType::Warning => {
+                self.current_warnings.push(error.to_string());
+                self.current_runtime_warnings.push(error);
+            }
         }
     }
 
     pub(crate) fn reset_errors(&mut self) {
         self.current_errors.clear();
+        self.current_runtime_errors.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{path::Path, story::Story, story_error::StoryError, value_type::ValueType};
+
+    const JSON: &str = r##"{"inkVersion":21,"root":[[["done",{"#n":"g-0"}],null],"done",{"knot":["ev",7,"/ev",{"VAR=":"x","re":true},"^hi","\n","done",{"#f":1}],"global decl":["ev",5,{"VAR=":"x"},"/ev","end",null]}],"listDefs":{}}"##;
+
+    #[test]
+    fn binary_save_with_patch_test() -> Result<(), StoryError> {
+        let mut story = Story::new(JSON)?;
+
+        // Changes made on a patched copy, like while a background save is running
+        let mut state = story.get_state().copy_and_start_patching(true);
+        state.variables_state.set("x", ValueType::Int(7))?;
+        let knot = state
+            .main_content_container
+            .content_at_path(&Path::new_with_components_string(Some("knot")), 0, -1)
+            .container()
+            .unwrap();
+        state.increment_visit_count_for_container(&knot);
+        *story.get_state_mut() = state;
+
+        let saved = story.save_state_binary()?;
+
+        let mut story = Story::new(JSON)?;
+        assert_eq!(5, story.get_variable("x").unwrap().get::<i32>().unwrap());
+        story.load_state_binary(&saved)?;
+
+        assert_eq!(7, story.get_variable("x").unwrap().get::<i32>().unwrap());
+        assert_eq!(1, story.get_visit_count_at_path_string("knot")?);
+
+        Ok(())
     }
 }
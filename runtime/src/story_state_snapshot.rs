@@ -0,0 +1,204 @@
+//! Structured representation of a saved story state, available with the
+//! `serde` feature.
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    json::{json_read, json_write},
+    object::RTObject,
+    story_error::StoryError,
+    threadsafe::Brc,
+    value::Value,
+    value_type::ValueType,
+};
+
+/// The state of a story, with the same content as the JSON written by
+/// [`Story::save_state`](crate::story::Story::save_state), but as plain
+/// structs that can be embedded in any serde based save file or message.
+///
+/// Get one with
+/// [`Story::save_state_snapshot`](crate::story::Story::save_state_snapshot)
+/// and restore it with
+/// [`Story::load_state_snapshot`](crate::story::Story::load_state_snapshot).
+/// Field names are the keys used in the JSON save. Variables are
+/// [`ValueType`]s, while the objects in the output stream and the evaluation
+/// stack are kept in the ink JSON encoding.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoryStateSnapshot {
+    pub flows: BTreeMap<String, FlowSnapshot>,
+    pub current_flow_name: String,
+    /// Global variables whose value differs from the default.
+    pub variables_state: BTreeMap<String, ValueType>,
+    pub eval_stack: Vec<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_divert_target: Option<String>,
+    pub visit_counts: BTreeMap<String, i32>,
+    pub turn_indices: BTreeMap<String, i32>,
+    pub turn_idx: i32,
+    pub story_seed: i32,
+    #[serde(default)]
+    pub previous_random: i32,
+    /// The call to an async external function the story is waiting for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_external_call: Option<PendingExternalCallSnapshot>,
+    pub ink_save_version: u32,
+    pub ink_format_version: i32,
+}
+
+/// The state of a flow: its callstack, output and current choices.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowSnapshot {
+    pub callstack: CallStackSnapshot,
+    pub output_stream: Vec<serde_json::Value>,
+    /// Threads of the current choices that are no longer in the callstack,
+    /// by thread index.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub choice_threads: BTreeMap<usize, ThreadSnapshot>,
+    pub current_choices: Vec<ChoiceSnapshot>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallStackSnapshot {
+    pub threads: Vec<ThreadSnapshot>,
+    pub thread_counter: usize,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadSnapshot {
+    pub callstack: Vec<CallStackElementSnapshot>,
+    pub thread_index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_content_object: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CallStackElementSnapshot {
+    /// Path of the current container, `None` when the pointer is null.
+    #[serde(rename = "cPath", default, skip_serializing_if = "Option::is_none")]
+    pub container_path: Option<String>,
+    #[serde(rename = "idx", default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<i32>,
+    #[serde(rename = "exp", default)]
+    pub in_expression_evaluation: bool,
+    /// 0 = tunnel, 1 = function, 2 = function evaluation from game.
+    #[serde(rename = "type")]
+    pub push_pop_type: u32,
+    #[serde(rename = "temp", default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub temporary_variables: BTreeMap<String, ValueType>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChoiceSnapshot {
+    pub text: String,
+    pub index: usize,
+    pub original_choice_path: String,
+    pub original_thread_index: usize,
+    pub target_path: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PendingExternalCallSnapshot {
+    #[serde(rename = "func")]
+    pub func_name: String,
+    /// The arguments, in the ink JSON encoding.
+    pub args: Vec<serde_json::Value>,
+}
+
+impl StoryStateSnapshot {
+    /// Builds the snapshot from the JSON written by `StoryState`.
+    pub(crate) fn from_save_json(mut j_state: serde_json::Value) -> Result<Self, StoryError> {
+        map_values(&mut j_state, &|token| {
+            let value = json_read::jtoken_to_runtime_object(token, None)?
+                .into_any()
+                .downcast::<Value>()
+                .map_err(|_| StoryError::BadJson(format!("Invalid variable value: {}", token)))?;
+
+            to_json_value(&value.value)
+        })?;
+
+        serde_json::from_value(j_state).map_err(|e| StoryError::BadJson(e.to_string()))
+    }
+
+    /// Builds the JSON expected by `StoryState` from the snapshot.
+    pub(crate) fn to_save_json(&self) -> Result<serde_json::Value, StoryError> {
+        let mut j_state = to_json_value(self)?;
+
+        map_values(&mut j_state, &|v| {
+            let value_type: ValueType = serde_json::from_value(v.clone())
+                .map_err(|e| StoryError::BadJson(e.to_string()))?;
+            let value: Brc<dyn RTObject> = Brc::new(Value::new_value_type(value_type));
+
+            json_write::write_rtobject(value)
+        })?;
+
+        Ok(j_state)
+    }
+}
+
+fn to_json_value<T: Serialize>(v: &T) -> Result<serde_json::Value, StoryError> {
+    serde_json::to_value(v).map_err(|e| StoryError::BadJson(e.to_string()))
+}
+
+type ValueMapper = dyn Fn(&serde_json::Value) -> Result<serde_json::Value, StoryError>;
+
+/// Applies `f` to every variable value in a JSON save: the globals and the
+/// temporaries of every callstack element.
+fn map_values(j_state: &mut serde_json::Value, f: &ValueMapper) -> Result<(), StoryError> {
+    if let Some(variables) = j_state.get_mut("variablesState") {
+        map_object_values(variables, f)?;
+    }
+
+    if let Some(flows) = j_state.get_mut("flows").and_then(|f| f.as_object_mut()) {
+        for flow in flows.values_mut() {
+            if let Some(threads) = flow
+                .pointer_mut("/callstack/threads")
+                .and_then(|t| t.as_array_mut())
+            {
+                for thread in threads.iter_mut() {
+                    map_thread_values(thread, f)?;
+                }
+            }
+
+            if let Some(choice_threads) = flow
+                .get_mut("choiceThreads")
+                .and_then(|c| c.as_object_mut())
+            {
+                for thread in choice_threads.values_mut() {
+                    map_thread_values(thread, f)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn map_thread_values(thread: &mut serde_json::Value, f: &ValueMapper) -> Result<(), StoryError> {
+    if let Some(elements) = thread.get_mut("callstack").and_then(|c| c.as_array_mut()) {
+        for element in elements.iter_mut() {
+            if let Some(temps) = element.get_mut("temp") {
+                map_object_values(temps, f)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn map_object_values(obj: &mut serde_json::Value, f: &ValueMapper) -> Result<(), StoryError> {
+    if let Some(obj) = obj.as_object_mut() {
+        for v in obj.values_mut() {
+            *v = f(v)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+//! Shared-ownership and interior mutability types used by the runtime.
+//!
+//! By default the runtime is single threaded and these are plain aliases of
+//! [`Rc`](std::rc::Rc) and [`RefCell`](std::cell::RefCell). When the
+//! `threadsafe` feature is enabled they become [`Arc`](std::sync::Arc) and a
+//! `RwLock` based cell, so a [`Story`](crate::story::Story) is `Send + Sync`
+//! and can be moved to another thread, and the compiled content can be shared
+//! between stories running on different threads.
+//!
+//! Use these types instead of `Rc`/`RefCell` when passing handlers to the
+//! story so your code builds with and without the feature:
+//!
+//! ```
+//! # use bladeink::{story::errors::{ErrorHandler, ErrorType}, threadsafe::{Brc, BrCell}};
+//! struct PrintErrors;
+//!
+//! impl ErrorHandler for PrintErrors {
+//!     fn error(&mut self, message: &str, _error_type: ErrorType) {
+//!         eprintln!("{message}");
+//!     }
+//! }
+//!
+//! let handler: Brc<BrCell<dyn ErrorHandler>> = Brc::new(BrCell::new(PrintErrors));
+//! ```
+
+/// Reference counted pointer. `Rc` by default, `Arc` with the `threadsafe`
+/// feature.
+#[cfg(not(feature = "threadsafe"))]
+pub type Brc<T> = std::rc::Rc<T>;
+/// Reference counted pointer. `Rc` by default, `Arc` with the `threadsafe`
+/// feature.
+#[cfg(feature = "threadsafe")]
+pub type Brc<T> = std::sync::Arc<T>;
+
+/// Weak version of [`Brc`].
+#[cfg(not(feature = "threadsafe"))]
+pub type BrWeak<T> = std::rc::Weak<T>;
+/// Weak version of [`Brc`].
+#[cfg(feature = "threadsafe")]
+pub type BrWeak<T> = std::sync::Weak<T>;
+
+/// Interior mutability cell. `RefCell` by default, a `RwLock` wrapper with
+/// the same `borrow`/`borrow_mut` API with the `threadsafe` feature.
+#[cfg(not(feature = "threadsafe"))]
+pub type BrCell<T> = std::cell::RefCell<T>;
+#[cfg(feature = "threadsafe")]
+pub use sync_cell::BrCell;
+
+#[cfg(not(feature = "threadsafe"))]
+pub(crate) type BrOnceCell<T> = std::cell::OnceCell<T>;
+#[cfg(feature = "threadsafe")]
+pub(crate) type BrOnceCell<T> = std::sync::OnceLock<T>;
+
+/// `dyn Any` flavour returned by `IntoAny::into_any`, it must be `Send + Sync`
+/// to be downcasted from an `Arc`.
+#[cfg(not(feature = "threadsafe"))]
+pub(crate) type BrAny = dyn std::any::Any;
+#[cfg(feature = "threadsafe")]
+pub(crate) type BrAny = dyn std::any::Any + Send + Sync;
+
+/// Marker trait that is `Send + Sync` with the `threadsafe` feature and
+/// implemented for every type otherwise. Used as supertrait of the runtime
+/// objects and of the handlers that the host registers in the story.
+#[cfg(not(feature = "threadsafe"))]
+pub trait BrSync {}
+#[cfg(not(feature = "threadsafe"))]
+impl<T: ?Sized> BrSync for T {}
+
+/// Marker trait that is `Send + Sync` with the `threadsafe` feature and
+/// implemented for every type otherwise. Used as supertrait of the runtime
+/// objects and of the handlers that the host registers in the story.
+#[cfg(feature = "threadsafe")]
+pub trait BrSync: Send + Sync {}
+#[cfg(feature = "threadsafe")]
+impl<T: Send + Sync + ?Sized> BrSync for T {}
+
+#[cfg(feature = "threadsafe")]
+mod sync_cell {
+    use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+    /// `RefCell` replacement used with the `threadsafe` feature.
+    ///
+    /// Borrowing mutably while the value is already borrowed blocks instead
+    /// of panicking.
+    #[derive(Default)]
+    pub struct BrCell<T: ?Sized>(RwLock<T>);
+
+    impl<T> BrCell<T> {
+        pub const fn new(value: T) -> Self {
+            Self(RwLock::new(value))
+        }
+
+        pub fn replace(&self, value: T) -> T {
+            std::mem::replace(&mut *self.borrow_mut(), value)
+        }
+
+        pub fn into_inner(self) -> T {
+            self.0.into_inner().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+
+    impl<T: Default> BrCell<T> {
+        pub fn take(&self) -> T {
+            self.replace(T::default())
+        }
+    }
+
+    impl<T: ?Sized> BrCell<T> {
+        pub fn borrow(&self) -> RwLockReadGuard<'_, T> {
+            self.0.read().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        pub fn borrow_mut(&self) -> RwLockWriteGuard<'_, T> {
+            self.0.write().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+
+    impl<T: Clone> Clone for BrCell<T> {
+        fn clone(&self) -> Self {
+            Self::new(self.borrow().clone())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "threadsafe"))]
+mod tests {
+    use crate::story::Story;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn story_is_send_and_sync_test() {
+        assert_send_sync::<Story>();
+
+        let json_string = r##"{"inkVersion":21,"root":[["^Hello from a thread.","\n",["done",{"#n":"g-0"}],null],"done",null],"listDefs":{}}"##;
+        let mut story = Story::new(json_string).unwrap();
+
+        let text = std::thread::spawn(move || story.cont().unwrap())
+            .join()
+            .unwrap();
+
+        assert_eq!("Hello from a thread.\n", text);
+    }
+}
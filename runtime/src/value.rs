@@ -29,15 +29,7 @@ impl RTObject for Value {
 
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &self.value {
-            ValueType::Bool(v) => write!(f, "{}", v),
-            ValueType::Int(v) => write!(f, "{}", v),
-            ValueType::Float(v) => write!(f, "{}", v),
-            ValueType::String(v) => write!(f, "{}", v.string),
-            ValueType::DivertTarget(p) => write!(f, "DivertTargetValue({})", p),
-            ValueType::VariablePointer(v) => write!(f, "VariablePointerValue({})", v.variable_name),
-            ValueType::List(l) => write!(f, "{}", l),
-        }
+        write!(f, "{}", self.value)
     }
 }
 
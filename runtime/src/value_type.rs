@@ -1,9 +1,12 @@
 //! A combination of an Ink value with its type.
+use std::{fmt, mem};
+
 use crate::{ink_list::InkList, path::Path, story_error::StoryError};
 
 /// An Ink value, tagged with its type.
 #[repr(u8)]
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ValueType {
     Bool(bool),
     Int(i32),
@@ -48,6 +51,12 @@ impl From<&str> for ValueType {
     }
 }
 
+impl From<String> for ValueType {
+    fn from(value: String) -> ValueType {
+        ValueType::from(value.as_str())
+    }
+}
+
 impl From<InkList> for ValueType {
     fn from(value: InkList) -> ValueType {
         ValueType::List(value)
@@ -106,6 +115,26 @@ impl<'val> TryFrom<&'val ValueType> for &'val str {
     }
 }
 
+impl TryFrom<&ValueType> for String {
+    type Error = ();
+    fn try_from(value: &ValueType) -> Result<Self, Self::Error> {
+        match value {
+            ValueType::String(v) => Ok(v.string.clone()),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TryFrom<&ValueType> for InkList {
+    type Error = ();
+    fn try_from(value: &ValueType) -> Result<Self, Self::Error> {
+        match value {
+            ValueType::List(v) => Ok(v.clone()),
+            _ => Err(()),
+        }
+    }
+}
+
 impl ValueType {
     pub fn new<T: Into<ValueType>>(v: T) -> Self {
         v.into()
@@ -118,6 +147,24 @@ impl ValueType {
         self.try_into().ok()
     }
 
+    /// Name of the type of the value, used in error messages.
+    pub(crate) fn get_type_name(&self) -> &'static str {
+        match self {
+            ValueType::Bool(_) => "bool",
+            ValueType::Int(_) => "int",
+            ValueType::Float(_) => "float",
+            ValueType::List(_) => "list",
+            ValueType::String(_) => "string",
+            ValueType::DivertTarget(_) => "divert target",
+            ValueType::VariablePointer(_) => "variable pointer",
+        }
+    }
+
+    /// `true` if both values have the same type and are shown the same.
+    pub(crate) fn same_value(&self, other: &ValueType) -> bool {
+        mem::discriminant(self) == mem::discriminant(other) && self.to_string() == other.to_string()
+    }
+
     /// Tries to convert the internal value of this `ValueType` to `i32`
     pub fn coerce_to_int(&self) -> Result<i32, StoryError> {
         match self {
@@ -183,6 +230,39 @@ impl ValueType {
     }
 }
 
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueType::Bool(v) => write!(f, "{}", v),
+            ValueType::Int(v) => write!(f, "{}", v),
+            ValueType::Float(v) => write!(f, "{}", v),
+            ValueType::String(v) => write!(f, "{}", v.string),
+            ValueType::DivertTarget(p) => write!(f, "DivertTargetValue({})", p),
+            ValueType::VariablePointer(v) => write!(f, "VariablePointerValue({})", v.variable_name),
+            ValueType::List(l) => write!(f, "{}", l),
+        }
+    }
+}
+
+impl fmt::Debug for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueType::Bool(v) => f.debug_tuple("Bool").field(v).finish(),
+            ValueType::Int(v) => f.debug_tuple("Int").field(v).finish(),
+            ValueType::Float(v) => f.debug_tuple("Float").field(v).finish(),
+            ValueType::List(l) => f.debug_tuple("List").field(&l.to_string()).finish(),
+            ValueType::String(v) => f.debug_tuple("String").field(&v.string).finish(),
+            ValueType::DivertTarget(p) => {
+                f.debug_tuple("DivertTarget").field(&p.to_string()).finish()
+            }
+            ValueType::VariablePointer(v) => f
+                .debug_tuple("VariablePointer")
+                .field(&v.variable_name)
+                .finish(),
+        }
+    }
+}
+
 /// Ink runtime representation of a string.
 #[derive(Clone)]
 pub struct StringValue {
@@ -198,8 +278,30 @@ impl StringValue {
     }
 }
 
+/// Serialized as a plain string, the whitespace flags are computed again
+/// when deserializing.
+#[cfg(feature = "serde")]
+impl serde::Serialize for StringValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.string)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for StringValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+
+        match ValueType::from(string.as_str()) {
+            ValueType::String(v) => Ok(v),
+            _ => Err(serde::de::Error::custom("expected a string value")),
+        }
+    }
+}
+
 /// Ink runtime representation of a reference to a variable.
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VariablePointerValue {
     pub(crate) variable_name: String,
 
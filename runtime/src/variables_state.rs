@@ -1,17 +1,18 @@
-use std::{
-    cell::RefCell,
-    collections::{HashMap, HashSet},
-    rc::Rc,
-};
+use std::collections::{HashMap, HashSet};
 
 use serde_json::Map;
 
 use crate::{
+    binary::{
+        binary_io::{BinaryReader, BinaryWriter},
+        binary_read, binary_write,
+    },
     callstack::CallStack,
     json::{json_read, json_write},
     list_definitions_origin::ListDefinitionsOrigin,
     state_patch::StatePatch,
     story_error::StoryError,
+    threadsafe::{BrCell, Brc},
     value::Value,
     value_type::{ValueType, VariablePointerValue},
     variable_assigment::VariableAssignment,
@@ -19,19 +20,19 @@ use crate::{
 
 #[derive(Clone)]
 pub(crate) struct VariablesState {
-    pub global_variables: HashMap<String, Rc<Value>>,
-    pub default_global_variables: HashMap<String, Rc<Value>>,
+    pub global_variables: HashMap<String, Brc<Value>>,
+    pub default_global_variables: HashMap<String, Brc<Value>>,
     pub batch_observing_variable_changes: bool,
-    pub callstack: Rc<RefCell<CallStack>>,
+    pub callstack: Brc<BrCell<CallStack>>,
     pub changed_variables_for_batch_obs: Option<HashSet<String>>,
     pub patch: Option<StatePatch>,
-    list_defs_origin: Rc<ListDefinitionsOrigin>,
+    list_defs_origin: Brc<ListDefinitionsOrigin>,
 }
 
 impl VariablesState {
     pub fn new(
-        callstack: Rc<RefCell<CallStack>>,
-        list_defs_origin: Rc<ListDefinitionsOrigin>,
+        callstack: Brc<BrCell<CallStack>>,
+        list_defs_origin: Brc<ListDefinitionsOrigin>,
     ) -> VariablesState {
         VariablesState {
             global_variables: HashMap::new(),
@@ -99,7 +100,7 @@ impl VariablesState {
     pub fn assign(
         &mut self,
         var_ass: &VariableAssignment,
-        value: Rc<Value>,
+        value: Brc<Value>,
     ) -> Result<(), StoryError> {
         let mut name = var_ass.variable_name.to_string();
         let mut context_index = -1;
@@ -164,7 +165,7 @@ impl VariablesState {
     // pointer that more specifically points to the exact instance: whether it's
     // global,
     // or the exact position of a temporary on the callstack.
-    fn resolve_variable_pointer(&self, var_pointer: &VariablePointerValue) -> Rc<Value> {
+    fn resolve_variable_pointer(&self, var_pointer: &VariablePointerValue) -> Brc<Value> {
         let mut context_index = var_pointer.context_index;
         if context_index == -1 {
             context_index = self.get_context_index_of_variable_named(&var_pointer.variable_name);
@@ -184,7 +185,7 @@ impl VariablesState {
             return value_of_variable_pointed_to;
         }
 
-        Rc::new(Value::new_variable_pointer(
+        Brc::new(Value::new_variable_pointer(
             &var_pointer.variable_name,
             context_index,
         ))
@@ -201,7 +202,7 @@ impl VariablesState {
 
         let val = Value::new_value_type(value_type);
 
-        let notify = self.set_global(variable_name, Rc::new(val));
+        let notify = self.set_global(variable_name, Brc::new(val));
 
         Ok(notify)
     }
@@ -239,7 +240,7 @@ impl VariablesState {
         return self.callstack.borrow().get_current_element_index();
     }
 
-    fn get_raw_variable_with_name(&self, name: &str, context_index: i32) -> Option<Rc<Value>> {
+    fn get_raw_variable_with_name(&self, name: &str, context_index: i32) -> Option<Brc<Value>> {
         // 0 context = global
         if context_index == 0 || context_index == -1 {
             if let Some(patch) = &self.patch
@@ -278,8 +279,8 @@ impl VariablesState {
     }
 
     // Returns true if global var has changed and we need to notify observers
-    fn set_global(&mut self, name: &str, value: Rc<Value>) -> bool {
-        let mut old_value: Option<Rc<Value>> = None;
+    fn set_global(&mut self, name: &str, value: Brc<Value>) -> bool {
+        let mut old_value: Option<Brc<Value>> = None;
 
         if let Some(patch) = &self.patch {
             old_value = patch.get_global(name);
@@ -300,7 +301,7 @@ impl VariablesState {
                 .insert(name.to_string(), value.clone());
         }
 
-        if old_value.is_none() || !Rc::ptr_eq(old_value.as_ref().unwrap(), &value) {
+        if old_value.is_none() || !Brc::ptr_eq(old_value.as_ref().unwrap(), &value) {
             if self.batch_observing_variable_changes {
                 if let Some(patch) = &mut self.patch {
                     patch.add_changed_variable(name);
@@ -315,7 +316,7 @@ impl VariablesState {
         false
     }
 
-    pub fn get_variable_with_name(&self, name: &str, context_index: i32) -> Option<Rc<Value>> {
+    pub fn get_variable_with_name(&self, name: &str, context_index: i32) -> Option<Brc<Value>> {
         let var_value = self.get_raw_variable_with_name(name, context_index);
         // Get value from pointer?
         if let Some(vv) = var_value.clone()
@@ -327,11 +328,11 @@ impl VariablesState {
         var_value
     }
 
-    fn value_at_variable_pointer(&self, pointer: &VariablePointerValue) -> Option<Rc<Value>> {
+    fn value_at_variable_pointer(&self, pointer: &VariablePointerValue) -> Option<Brc<Value>> {
         self.get_variable_with_name(&pointer.variable_name, pointer.context_index)
     }
 
-    pub fn set_callstack(&mut self, callstack: Rc<RefCell<CallStack>>) {
+    pub fn set_callstack(&mut self, callstack: Brc<BrCell<CallStack>>) {
         self.callstack = callstack;
     }
 
@@ -353,6 +354,39 @@ impl VariablesState {
         Ok(serde_json::Value::Object(jobj))
     }
 
+    /// Globals that were set while patching are merged in, so a state in
+    /// the middle of a background save is written as the player sees it.
+    pub(crate) fn write_binary(&self, w: &mut BinaryWriter) {
+        let mut globals = self.global_variables.clone();
+
+        if let Some(patch) = &self.patch {
+            for (name, val) in patch.globals.iter() {
+                globals.insert(name.clone(), val.clone());
+            }
+        }
+
+        // Don't write out values that are the same as the default global values
+        globals.retain(|name, val| match self.default_global_variables.get(name) {
+            Some(default_val) => !self.val_equal(val, default_val),
+            None => true,
+        });
+
+        binary_write::write_dictionary_values(w, &globals);
+    }
+
+    pub(crate) fn load_binary(&mut self, r: &mut BinaryReader) -> Result<(), StoryError> {
+        let mut loaded = binary_read::read_dictionary_values(r)?;
+
+        self.global_variables.clear();
+
+        for (k, v) in self.default_global_variables.iter() {
+            let val = loaded.remove(k).unwrap_or_else(|| v.clone());
+            self.global_variables.insert(k.clone(), val);
+        }
+
+        Ok(())
+    }
+
     fn val_equal(&self, val: &Value, default_val: &Value) -> bool {
         match &val.value {
             ValueType::Bool(val) => match default_val.value {